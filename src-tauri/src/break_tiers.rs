@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+
+/// One kind of break on its own schedule, e.g. a 20-second eye break every
+/// 20 minutes next to a 10-minute break every hour.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BreakTier {
+    pub name: String,
+    /// Work time between two breaks of this tier.
+    pub interval_seconds: u32,
+    pub duration_seconds: u32,
+    /// `force`, `notify` or `lock`.
+    pub mode: String,
+    /// How long before the break the pre-break warning shows; `None` for no warning.
+    pub pre_break_seconds: Option<u32>,
+}
+
+impl BreakTier {
+    pub fn is_valid(&self) -> bool {
+        self.interval_seconds > 0 && self.duration_seconds > 0
+    }
+}
+
+/// The break the work countdown runs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NextBreak {
+    /// Index into the tiers.
+    pub tier: usize,
+    /// Seconds of work until it starts.
+    pub in_seconds: u32,
+}
+
+/// The next break, given `worked[i]`, the work time in seconds since tier `i`'s
+/// last break. The tier due first wins, the longer break on a tie.
+///
+/// A longer break due within half the winner's interval after it absorbs it:
+/// the longer break is taken at the earlier time and counts for both, so a
+/// 20-minute eye break and an hourly break never run back to back.
+pub fn next_break(tiers: &[BreakTier], worked: &[u32]) -> Option<NextBreak> {
+    let due_in = |index: usize| {
+        let worked = worked.get(index).copied().unwrap_or(0);
+        tiers[index].interval_seconds.saturating_sub(worked)
+    };
+    let valid = || (0..tiers.len()).filter(|&index| tiers[index].is_valid());
+
+    let first = valid().min_by(|&a, &b| {
+        due_in(a)
+            .cmp(&due_in(b))
+            .then(tiers[b].duration_seconds.cmp(&tiers[a].duration_seconds))
+    })?;
+    let window = tiers[first].interval_seconds / 2;
+    let absorbing = valid()
+        .filter(|&index| {
+            tiers[index].duration_seconds > tiers[first].duration_seconds
+                && due_in(index) <= due_in(first) + window
+        })
+        .max_by_key(|&index| tiers[index].duration_seconds);
+
+    Some(NextBreak {
+        tier: absorbing.unwrap_or(first),
+        in_seconds: due_in(first),
+    })
+}
+
+/// Start the schedules over that a rest of `rested_seconds` counts for: a break
+/// covers every tier whose breaks are no longer than it.
+pub fn credit_rest(tiers: &[BreakTier], worked: &mut [u32], rested_seconds: u32) {
+    for (tier, worked) in tiers.iter().zip(worked.iter_mut()) {
+        if tier.duration_seconds <= rested_seconds {
+            *worked = 0;
+        }
+    }
+}
+
+/// The shortest break any tier takes, or `None` without tiers.
+pub fn shortest_break(tiers: &[BreakTier]) -> Option<u32> {
+    tiers
+        .iter()
+        .filter(|tier| tier.is_valid())
+        .map(|tier| tier.duration_seconds)
+        .min()
+}
+
+/// The longest break any tier takes, or `None` without tiers.
+pub fn longest_break(tiers: &[BreakTier]) -> Option<u32> {
+    tiers
+        .iter()
+        .filter(|tier| tier.is_valid())
+        .map(|tier| tier.duration_seconds)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(name: &str, interval_seconds: u32, duration_seconds: u32) -> BreakTier {
+        BreakTier {
+            name: name.to_string(),
+            interval_seconds,
+            duration_seconds,
+            mode: "notify".to_string(),
+            pre_break_seconds: None,
+        }
+    }
+
+    /// An hourly 10-minute break and a 20-second eye break every 20 minutes.
+    fn hourly_and_eye() -> Vec<BreakTier> {
+        vec![tier("Break", 3600, 600), tier("Eye", 1200, 20)]
+    }
+
+    #[test]
+    fn earliest_tier_comes_first() {
+        let next = next_break(&hourly_and_eye(), &[0, 0]);
+        assert_eq!(next, Some(NextBreak { tier: 1, in_seconds: 1200 }));
+    }
+
+    #[test]
+    fn longer_break_wins_a_tie() {
+        let next = next_break(&hourly_and_eye(), &[3600, 1200]);
+        assert_eq!(next, Some(NextBreak { tier: 0, in_seconds: 0 }));
+    }
+
+    #[test]
+    fn longer_break_due_soon_after_absorbs_the_shorter_one() {
+        // Eye break due in 20 minutes, main break 10 minutes later
+        let next = next_break(&hourly_and_eye(), &[1800, 0]);
+        assert_eq!(next, Some(NextBreak { tier: 0, in_seconds: 1200 }));
+
+        // 11 minutes later is outside half the eye interval
+        let next = next_break(&hourly_and_eye(), &[1740, 0]);
+        assert_eq!(next, Some(NextBreak { tier: 1, in_seconds: 1200 }));
+    }
+
+    #[test]
+    fn invalid_tiers_are_never_due() {
+        let tiers = vec![tier("Break", 3600, 600), tier("Broken", 0, 20), tier("Empty", 60, 0)];
+        assert_eq!(next_break(&tiers, &[0, 0, 0]), Some(NextBreak { tier: 0, in_seconds: 3600 }));
+        assert_eq!(next_break(&[], &[]), None);
+    }
+
+    #[test]
+    fn overdue_tier_is_due_now() {
+        let next = next_break(&hourly_and_eye(), &[0, 5000]);
+        assert_eq!(next, Some(NextBreak { tier: 1, in_seconds: 0 }));
+    }
+
+    #[test]
+    fn rest_resets_the_tiers_it_is_long_enough_for() {
+        let tiers = hourly_and_eye();
+        let mut worked = vec![2000, 1000];
+        credit_rest(&tiers, &mut worked, 20);
+        assert_eq!(worked, vec![2000, 0]);
+        credit_rest(&tiers, &mut worked, 600);
+        assert_eq!(worked, vec![0, 0]);
+    }
+
+    #[test]
+    fn shortest_and_longest_skip_invalid_tiers() {
+        let mut tiers = hourly_and_eye();
+        tiers.push(tier("Broken", 0, 5));
+        assert_eq!(shortest_break(&tiers), Some(20));
+        assert_eq!(longest_break(&tiers), Some(600));
+        assert_eq!(shortest_break(&[]), None);
+    }
+}
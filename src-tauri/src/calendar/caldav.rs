@@ -0,0 +1,272 @@
+//! CalDAV client (RFC 4791): finds the account's calendars and fetches the
+//! events of a time range as iCalendar text.
+//!
+//! The configured URL may point at a calendar, a calendar home, a principal or
+//! the server root; calendars are found through `current-user-principal` and
+//! `calendar-home-set` as needed. To try it against a local Radicale:
+//!
+//! ```text
+//! python -m radicale --storage-filesystem-folder /tmp/radicale --auth-type none
+//! ```
+//!
+//! then create a calendar at <http://localhost:5232/> and use that address with
+//! any user name.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::blocking::Client;
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Requests that take longer than this count as a failed sync.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Principal → calendar home → calendars is the longest discovery chain.
+const MAX_DISCOVERY_HOPS: usize = 3;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <d:current-user-principal/>
+    <c:calendar-home-set/>
+  </d:prop>
+</d:propfind>"#;
+
+/// Login details for a CalDAV server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalDavAccount {
+    pub url: String,
+    pub username: String,
+    pub password: Option<String>,
+}
+
+/// One calendar's events in the fetched range, as iCalendar objects.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FetchedCalendar {
+    pub url: String,
+    pub name: String,
+    pub objects: Vec<String>,
+}
+
+/// One `<response>` of a multistatus reply, with the properties we ask for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DavResponse {
+    pub href: String,
+    pub is_calendar: bool,
+    pub display_name: Option<String>,
+    pub principal: Option<String>,
+    pub calendar_home: Option<String>,
+    pub calendar_data: Option<String>,
+}
+
+/// Parse a `207 Multi-Status` body. Properties reported with a non-2xx status
+/// are empty elements and simply come out unset.
+pub fn parse_multistatus(xml: &str) -> Result<Vec<DavResponse>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut path: Vec<String> = Vec::new();
+    let mut responses = Vec::new();
+    let mut current: Option<DavResponse> = None;
+    let mut text = String::new();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("invalid multistatus XML at byte {}: {}", reader.buffer_position(), e))?;
+        match event {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                if name == "response" {
+                    current = Some(DavResponse::default());
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Empty(empty) => {
+                let name = empty.local_name();
+                if name.as_ref() == b"calendar" && path.last().is_some_and(|parent| parent == "resourcetype") {
+                    if let Some(response) = current.as_mut() {
+                        response.is_calendar = true;
+                    }
+                }
+            }
+            Event::Text(content) => {
+                text.push_str(&content.decode().map_err(|e| e.to_string())?);
+            }
+            Event::CData(content) => {
+                text.push_str(&content.decode().map_err(|e| e.to_string())?);
+            }
+            Event::GeneralRef(reference) => {
+                if let Some(c) = reference.resolve_char_ref().map_err(|e| e.to_string())? {
+                    text.push(c);
+                } else {
+                    let name = reference.decode().map_err(|e| e.to_string())?;
+                    match quick_xml::escape::resolve_predefined_entity(&name) {
+                        Some(value) => text.push_str(value),
+                        None => return Err(format!("unknown entity &{};", name)),
+                    }
+                }
+            }
+            Event::End(_) => {
+                let name = path.pop().unwrap_or_default();
+                let parent = path.last().map(String::as_str).unwrap_or("");
+                if name == "response" {
+                    responses.extend(current.take());
+                } else if let Some(response) = current.as_mut() {
+                    let value = text.trim();
+                    match (name.as_str(), parent) {
+                        ("href", "response") => response.href = value.to_string(),
+                        ("href", "current-user-principal") => response.principal = Some(value.to_string()),
+                        ("href", "calendar-home-set") => {
+                            // Several homes are allowed; the first is the user's own
+                            response.calendar_home.get_or_insert_with(|| value.to_string());
+                        }
+                        ("displayname", _) if !value.is_empty() => {
+                            response.display_name = Some(value.to_string())
+                        }
+                        ("calendar-data", _) if !value.is_empty() => {
+                            response.calendar_data = Some(text.clone())
+                        }
+                        ("calendar", "resourcetype") => response.is_calendar = true,
+                        _ => {}
+                    }
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(responses)
+}
+
+/// The `calendar-query` REPORT body for VEVENTs overlapping `start..end`
+/// (`YYYYMMDDTHHMMSSZ`).
+pub fn calendar_query_body(start: &str, end: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <c:calendar-data/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+        start, end
+    )
+}
+
+pub struct CalDavClient {
+    client: Client,
+    account: CalDavAccount,
+    base: Url,
+}
+
+impl CalDavClient {
+    pub fn new(account: CalDavAccount) -> Result<Self, String> {
+        let base = Url::parse(account.url.trim()).map_err(|e| format!("invalid CalDAV URL: {}", e))?;
+        if !matches!(base.scheme(), "http" | "https") {
+            return Err(format!("unsupported CalDAV URL scheme \"{}\"", base.scheme()));
+        }
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("failed to create HTTP client: {}", e))?;
+        Ok(Self { client, account, base })
+    }
+
+    /// Send a WebDAV request and parse the multistatus reply.
+    fn request(&self, method: &str, url: &Url, depth: u8, body: String) -> Result<Vec<DavResponse>, String> {
+        let method = Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+        let mut request = self
+            .client
+            .request(method.clone(), url.clone())
+            .header("Depth", depth.to_string())
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body);
+        if !self.account.username.is_empty() {
+            request = request.basic_auth(&self.account.username, self.account.password.as_ref());
+        }
+
+        let response = request
+            .send()
+            .map_err(|e| format!("{} {} failed: {}", method, url, e))?;
+        let status = response.status();
+        if status.as_u16() == 401 {
+            return Err(format!("{} rejected the user name or password", url));
+        }
+        if status.as_u16() != 207 {
+            return Err(format!("{} {} returned {}", method, url, status));
+        }
+        let xml = response
+            .text()
+            .map_err(|e| format!("failed to read reply from {}: {}", url, e))?;
+        parse_multistatus(&xml)
+    }
+
+    fn resolve(&self, href: &str) -> Result<Url, String> {
+        self.base
+            .join(href)
+            .map_err(|e| format!("invalid href \"{}\": {}", href, e))
+    }
+
+    /// The calendars reachable from the configured URL, as `(url, name)`.
+    pub fn discover(&self) -> Result<Vec<(Url, String)>, String> {
+        let mut url = self.base.clone();
+        for _ in 0..MAX_DISCOVERY_HOPS {
+            let responses = self.request("PROPFIND", &url, 1, PROPFIND_BODY.to_string())?;
+            let calendars: Vec<(Url, String)> = responses
+                .iter()
+                .filter(|response| response.is_calendar)
+                .map(|response| {
+                    let calendar_url = self.resolve(&response.href)?;
+                    let name = response.display_name.clone().unwrap_or_else(|| response.href.clone());
+                    Ok((calendar_url, name))
+                })
+                .collect::<Result<_, String>>()?;
+            if !calendars.is_empty() {
+                return Ok(calendars);
+            }
+
+            let next = responses
+                .iter()
+                .find_map(|response| response.calendar_home.clone())
+                .or_else(|| responses.iter().find_map(|response| response.principal.clone()));
+            match next {
+                Some(href) => {
+                    let next_url = self.resolve(&href)?;
+                    if next_url == url {
+                        break;
+                    }
+                    url = next_url;
+                }
+                None => break,
+            }
+        }
+        Err(format!("no calendars found at {}", self.base))
+    }
+
+    /// Events of every calendar overlapping `start..end` (`YYYYMMDDTHHMMSSZ`).
+    pub fn fetch(&self, start: &str, end: &str) -> Result<Vec<FetchedCalendar>, String> {
+        let mut fetched = Vec::new();
+        for (url, name) in self.discover()? {
+            let responses = self.request("REPORT", &url, 1, calendar_query_body(start, end))?;
+            fetched.push(FetchedCalendar {
+                url: url.to_string(),
+                name,
+                objects: responses
+                    .into_iter()
+                    .filter_map(|response| response.calendar_data)
+                    .collect(),
+            });
+        }
+        Ok(fetched)
+    }
+}
@@ -0,0 +1,693 @@
+//! iCalendar (RFC 5545) reader: VEVENTs with recurrences, exceptions and
+//! timezones.
+//!
+//! Times with a `TZID` are resolved with the file's own `VTIMEZONE` definitions,
+//! which every common calendar client exports. An unknown `TZID` and floating
+//! times are taken as the computer's local time.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+
+use super::rrule::RRule;
+
+/// One content line, e.g. `DTSTART;TZID=Europe/Berlin:20250101T090000`.
+#[derive(Clone, Debug)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Component {
+    name: String,
+    properties: Vec<Property>,
+    children: Vec<Component>,
+}
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn properties<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> + 'a {
+        self.properties.iter().filter(move |p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.property(name).map(|p| unescape_text(&p.value))
+    }
+}
+
+/// Join folded lines: a line starting with a space or tab continues the previous one.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(rest), Some(previous)) => previous.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split a content line into name, parameters and value. Quoted parameter
+/// values may contain `;`, `:` and `,`.
+fn parse_line(line: &str) -> Option<Property> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut value_start = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => parts.push(std::mem::take(&mut current)),
+            ':' if !quoted => {
+                value_start = Some(i + 1);
+                break;
+            }
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+    let value = line[value_start?..].to_string();
+
+    let mut parts = parts.into_iter();
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((key.to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        })
+        .collect();
+    Some(Property { name, params, value })
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+fn parse_components(text: &str) -> Result<Vec<Component>, String> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut top = Vec::new();
+    for (number, line) in unfold(text).iter().enumerate() {
+        let property = parse_line(line).ok_or_else(|| format!("line {}: not a content line", number + 1))?;
+        match property.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: property.value.to_ascii_uppercase(),
+                ..Default::default()
+            }),
+            "END" => {
+                let component = stack
+                    .pop()
+                    .ok_or_else(|| format!("line {}: END without BEGIN", number + 1))?;
+                if !component.name.eq_ignore_ascii_case(&property.value) {
+                    return Err(format!(
+                        "line {}: END:{} closes BEGIN:{}",
+                        number + 1,
+                        property.value,
+                        component.name
+                    ));
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(component),
+                    None => top.push(component),
+                }
+            }
+            _ => {
+                if let Some(component) = stack.last_mut() {
+                    component.properties.push(property);
+                }
+            }
+        }
+    }
+    if let Some(open) = stack.last() {
+        return Err(format!("BEGIN:{} is never closed", open.name));
+    }
+    Ok(top)
+}
+
+/// A DATE or DATE-TIME value with the zone it is expressed in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IcsTime {
+    Utc(NaiveDateTime),
+    Zoned(NaiveDateTime, String),
+    Floating(NaiveDateTime),
+    Date(NaiveDate),
+}
+
+impl IcsTime {
+    fn parse(value: &str, tzid: Option<&str>) -> Option<Self> {
+        let value = value.trim();
+        if value.len() == 8 {
+            return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(IcsTime::Date);
+        }
+        if let Some(utc) = value.strip_suffix('Z') {
+            return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(IcsTime::Utc);
+        }
+        let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        Some(match tzid {
+            Some(tzid) => IcsTime::Zoned(local, tzid.to_string()),
+            None => IcsTime::Floating(local),
+        })
+    }
+
+    fn from_property(property: &Property) -> Option<Self> {
+        IcsTime::parse(&property.value, property.param("TZID"))
+    }
+
+    /// All values of a multi-valued property such as `EXDATE:20250101T090000,20250108T090000`.
+    fn list_from_property(property: &Property) -> Vec<Self> {
+        property
+            .value
+            .split(',')
+            .filter_map(|value| IcsTime::parse(value, property.param("TZID")))
+            .collect()
+    }
+
+    /// Wall-clock time in the value's own zone (midnight for dates).
+    fn naive(&self) -> NaiveDateTime {
+        match self {
+            IcsTime::Utc(time) | IcsTime::Zoned(time, _) | IcsTime::Floating(time) => *time,
+            IcsTime::Date(date) => date.and_time(NaiveTime::MIN),
+        }
+    }
+
+    /// The same kind of value at another wall-clock time, e.g. for recurrences.
+    fn with_naive(&self, time: NaiveDateTime) -> Self {
+        match self {
+            IcsTime::Utc(_) => IcsTime::Utc(time),
+            IcsTime::Zoned(_, tzid) => IcsTime::Zoned(time, tzid.clone()),
+            IcsTime::Floating(_) => IcsTime::Floating(time),
+            IcsTime::Date(_) => IcsTime::Date(time.date()),
+        }
+    }
+}
+
+/// Parse `P1W`, `PT1H30M`, `-P1D`, … (RFC 5545 §3.3.6). `None` for malformed
+/// values and ones too long for a `Duration`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut rest = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = after;
+            continue;
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let part = match (unit, in_time) {
+            ('W', false) => Duration::try_weeks(amount),
+            ('D', false) => Duration::try_days(amount),
+            ('H', true) => Duration::try_hours(amount),
+            ('M', true) => Duration::try_minutes(amount),
+            ('S', true) => Duration::try_seconds(amount),
+            _ => return None,
+        };
+        total = total.checked_add(&part?)?;
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn parse_offset(value: &str) -> Option<i64> {
+    let sign = match value.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let hours: i64 = value.get(1..3)?.parse().ok()?;
+    let minutes: i64 = value.get(3..5)?.parse().ok()?;
+    let seconds: i64 = value.get(5..7).and_then(|s| s.parse().ok()).unwrap_or(0);
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+/// A STANDARD or DAYLIGHT block of a VTIMEZONE.
+#[derive(Clone, Debug)]
+struct Observance {
+    /// First onset, in local time before the change.
+    start: NaiveDateTime,
+    offset_from: i64,
+    offset_to: i64,
+    rrule: Option<RRule>,
+    rdates: Vec<NaiveDateTime>,
+}
+
+/// A VTIMEZONE: the offset changes of one named zone.
+#[derive(Clone, Debug, Default)]
+struct ZoneDefinition {
+    observances: Vec<Observance>,
+}
+
+impl ZoneDefinition {
+    fn from_component(component: &Component) -> Self {
+        let observances = component
+            .children
+            .iter()
+            .filter(|child| child.name == "STANDARD" || child.name == "DAYLIGHT")
+            .filter_map(|child| {
+                Some(Observance {
+                    start: IcsTime::from_property(child.property("DTSTART")?)?.naive(),
+                    offset_from: parse_offset(&child.property("TZOFFSETFROM")?.value)?,
+                    offset_to: parse_offset(&child.property("TZOFFSETTO")?.value)?,
+                    rrule: child.property("RRULE").and_then(|p| RRule::parse(&p.value).ok()),
+                    rdates: child
+                        .properties("RDATE")
+                        .flat_map(IcsTime::list_from_property)
+                        .map(|time| time.naive())
+                        .collect(),
+                })
+            })
+            .collect();
+        Self { observances }
+    }
+
+    /// Offset changes (in UTC) around `year`, sorted.
+    fn transitions(&self, year: i32) -> Vec<(NaiveDateTime, i64)> {
+        let window_end = NaiveDate::from_ymd_opt(year + 1, 12, 31)
+            .map(|date| date.and_time(NaiveTime::MIN))
+            .unwrap_or(NaiveDateTime::MAX);
+        let mut transitions = Vec::new();
+        for observance in &self.observances {
+            let mut onsets = vec![observance.start];
+            if let Some(rule) = &observance.rrule {
+                let until = rule
+                    .until
+                    .as_deref()
+                    .and_then(|until| IcsTime::parse(until, None))
+                    .map(|until| until.naive());
+                onsets = rule.expand(observance.start, until, window_end);
+            }
+            onsets.extend(observance.rdates.iter().copied());
+            transitions.extend(
+                onsets
+                    .into_iter()
+                    .filter(|onset| onset.year() >= year - 1)
+                    .map(|onset| (onset - Duration::seconds(observance.offset_from), observance.offset_to)),
+            );
+        }
+        transitions.sort();
+        transitions
+    }
+
+    /// UTC offset in effect at `utc`, in seconds.
+    fn offset_at(&self, utc: NaiveDateTime) -> i64 {
+        let year = utc.year();
+        self.transitions(year)
+            .into_iter()
+            .take_while(|(at, _)| *at <= utc)
+            .last()
+            .map(|(_, offset)| offset)
+            .or_else(|| self.observances.first().map(|o| o.offset_from))
+            .unwrap_or(0)
+    }
+
+    fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        // Guess with the offset half a day earlier, then correct once; good
+        // everywhere except inside the skipped or repeated hour of a change
+        let guess = self.offset_at(local - Duration::hours(12));
+        let utc = local - Duration::seconds(guess);
+        let offset = self.offset_at(utc);
+        local - Duration::seconds(offset)
+    }
+
+    fn to_local(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        utc + Duration::seconds(self.offset_at(utc))
+    }
+}
+
+fn is_utc_name(tzid: &str) -> bool {
+    matches!(tzid.to_ascii_uppercase().as_str(), "UTC" | "GMT" | "Z" | "ETC/UTC" | "ETC/GMT")
+}
+
+fn local_to_utc(local: NaiveDateTime) -> DateTime<Utc> {
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        // Inside a DST gap: the wall time doesn't exist; move past it
+        .unwrap_or_else(|| Utc.from_utc_datetime(&(local - Duration::hours(1))))
+}
+
+/// A single (possibly recurring) event.
+#[derive(Clone, Debug)]
+struct Event {
+    uid: String,
+    summary: String,
+    start: IcsTime,
+    duration: Duration,
+    rrule: Option<RRule>,
+    rdates: Vec<IcsTime>,
+    exdates: Vec<IcsTime>,
+    recurrence_id: Option<IcsTime>,
+    cancelled: bool,
+    transparent: bool,
+}
+
+impl Event {
+    fn from_component(component: &Component) -> Option<Self> {
+        let start = IcsTime::from_property(component.property("DTSTART")?)?;
+        let duration = match component.property("DTEND").and_then(IcsTime::from_property) {
+            Some(end) => end.naive() - start.naive(),
+            None => match component.property("DURATION").and_then(|p| parse_duration(&p.value)) {
+                Some(duration) => duration,
+                // RFC 5545: a date-only event without an end lasts one day
+                None if matches!(start, IcsTime::Date(_)) => Duration::days(1),
+                None => Duration::zero(),
+            },
+        };
+        Some(Event {
+            uid: component.text("UID").unwrap_or_default(),
+            summary: component.text("SUMMARY").unwrap_or_else(|| "Busy".to_string()),
+            start,
+            duration,
+            rrule: component.property("RRULE").and_then(|p| RRule::parse(&p.value).ok()),
+            rdates: component.properties("RDATE").flat_map(IcsTime::list_from_property).collect(),
+            exdates: component.properties("EXDATE").flat_map(IcsTime::list_from_property).collect(),
+            recurrence_id: component.property("RECURRENCE-ID").and_then(IcsTime::from_property),
+            cancelled: component
+                .property("STATUS")
+                .is_some_and(|p| p.value.eq_ignore_ascii_case("CANCELLED")),
+            transparent: component
+                .property("TRANSP")
+                .is_some_and(|p| p.value.eq_ignore_ascii_case("TRANSPARENT")),
+        })
+    }
+}
+
+/// One occurrence of an event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventInstance {
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub all_day: bool,
+    /// Cancelled, or marked "free" (`TRANSP:TRANSPARENT`).
+    pub free: bool,
+}
+
+/// The events and timezones of one or more `.ics` files.
+#[derive(Clone, Debug, Default)]
+pub struct Calendar {
+    events: Vec<Event>,
+    zones: HashMap<String, ZoneDefinition>,
+}
+
+impl Calendar {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut calendar = Calendar::default();
+        calendar.add(text)?;
+        Ok(calendar)
+    }
+
+    /// Merge another file's events and timezones into this calendar.
+    pub fn add(&mut self, text: &str) -> Result<(), String> {
+        for root in parse_components(text)? {
+            if root.name != "VCALENDAR" {
+                continue;
+            }
+            for component in &root.children {
+                match component.name.as_str() {
+                    "VTIMEZONE" => {
+                        if let Some(tzid) = component.property("TZID") {
+                            self.zones
+                                .insert(tzid.value.clone(), ZoneDefinition::from_component(component));
+                        }
+                    }
+                    "VEVENT" => self.events.extend(Event::from_component(component)),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn to_utc(&self, time: &IcsTime) -> DateTime<Utc> {
+        match time {
+            IcsTime::Utc(time) => Utc.from_utc_datetime(time),
+            IcsTime::Zoned(time, tzid) => match self.zones.get(tzid) {
+                Some(zone) => Utc.from_utc_datetime(&zone.to_utc(*time)),
+                None if is_utc_name(tzid) => Utc.from_utc_datetime(time),
+                None => local_to_utc(*time),
+            },
+            IcsTime::Floating(time) => local_to_utc(*time),
+            IcsTime::Date(date) => local_to_utc(date.and_time(NaiveTime::MIN)),
+        }
+    }
+
+    /// `utc` as wall-clock time in the zone `reference` is expressed in.
+    fn to_zone_local(&self, reference: &IcsTime, utc: NaiveDateTime) -> NaiveDateTime {
+        match reference {
+            IcsTime::Utc(_) => utc,
+            IcsTime::Zoned(_, tzid) => match self.zones.get(tzid) {
+                Some(zone) => zone.to_local(utc),
+                None if is_utc_name(tzid) => utc,
+                None => Local.from_utc_datetime(&utc).naive_local(),
+            },
+            IcsTime::Floating(_) | IcsTime::Date(_) => Local.from_utc_datetime(&utc).naive_local(),
+        }
+    }
+
+    /// Every occurrence overlapping `from..to`, sorted by start.
+    pub fn instances(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<EventInstance> {
+        // Occurrences replaced by a RECURRENCE-ID override, per UID
+        let mut overridden: HashMap<&str, HashSet<DateTime<Utc>>> = HashMap::new();
+        for event in &self.events {
+            if let Some(id) = &event.recurrence_id {
+                overridden.entry(event.uid.as_str()).or_default().insert(self.to_utc(id));
+            }
+        }
+
+        let mut instances = Vec::new();
+        for event in &self.events {
+            let starts: Vec<IcsTime> = match (&event.rrule, &event.recurrence_id) {
+                (Some(rule), None) => {
+                    // Rules run in the event's own wall-clock time; give the
+                    // window a day of slack for zone offsets
+                    let end = self.to_zone_local(&event.start, to.naive_utc()) + Duration::days(1);
+                    let until = rule.until.as_deref().and_then(|until| IcsTime::parse(until, None)).map(
+                        |until| match until {
+                            IcsTime::Utc(utc) => self.to_zone_local(&event.start, utc),
+                            other => other.naive(),
+                        },
+                    );
+                    let mut starts: Vec<IcsTime> = rule
+                        .expand(event.start.naive(), until, end)
+                        .into_iter()
+                        .map(|time| event.start.with_naive(time))
+                        .collect();
+                    starts.extend(event.rdates.iter().cloned());
+                    starts
+                }
+                _ => {
+                    let mut starts = vec![event.start.clone()];
+                    starts.extend(event.rdates.iter().cloned());
+                    starts
+                }
+            };
+
+            let excluded: HashSet<DateTime<Utc>> = event.exdates.iter().map(|time| self.to_utc(time)).collect();
+            let replaced = overridden.get(event.uid.as_str());
+            for start in starts {
+                let start_utc = self.to_utc(&start);
+                if excluded.contains(&start_utc) {
+                    continue;
+                }
+                if event.recurrence_id.is_none() && replaced.is_some_and(|set| set.contains(&start_utc)) {
+                    continue;
+                }
+                // Add the duration in wall-clock time so a change to or from
+                // DST in the middle of an event keeps its local end time
+                let Some(end) = start.naive().checked_add_signed(event.duration) else { continue };
+                let end_utc = self.to_utc(&start.with_naive(end));
+                if end_utc <= from || start_utc >= to {
+                    continue;
+                }
+                instances.push(EventInstance {
+                    summary: event.summary.clone(),
+                    start: start_utc,
+                    end: end_utc.max(start_utc),
+                    all_day: matches!(start, IcsTime::Date(_)),
+                    free: event.cancelled || event.transparent,
+                });
+            }
+        }
+        instances.sort_by_key(|instance| instance.start);
+        instances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap())
+    }
+
+    fn calendar(events: &str) -> Calendar {
+        Calendar::parse(&format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)).unwrap()
+    }
+
+    fn summaries(instances: &[EventInstance]) -> Vec<&str> {
+        instances.iter().map(|instance| instance.summary.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("-P1DT12H"), Some(-Duration::hours(36)));
+        assert_eq!(parse_duration("+PT45S"), Some(Duration::seconds(45)));
+        // Hours before the T, days after it, or no P at all
+        assert_eq!(parse_duration("P1H"), None);
+        assert_eq!(parse_duration("PT1D"), None);
+        assert_eq!(parse_duration("1H"), None);
+    }
+
+    #[test]
+    fn out_of_range_durations_are_rejected() {
+        assert_eq!(parse_duration("P99999999999W"), None);
+        assert_eq!(parse_duration("PT99999999999999999999S"), None);
+        // Each part fits, their sum doesn't
+        assert_eq!(parse_duration("P10000000000W10000000000W"), None);
+    }
+
+    #[test]
+    fn event_ending_past_the_calendar_range_is_dropped() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\r\nUID:forever\r\nDTSTART:20250106T090000Z\r\nDURATION:P9999999999W\r\nSUMMARY:Forever\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:standup\r\nDTSTART:20250106T100000Z\r\nDURATION:PT30M\r\nSUMMARY:Standup\r\nEND:VEVENT\r\n",
+        );
+        let instances = calendar.instances(utc("2025-01-06 00:00"), utc("2025-01-07 00:00"));
+        assert_eq!(summaries(&instances), vec!["Standup"]);
+        assert_eq!(instances[0].end, utc("2025-01-06 10:30"));
+    }
+
+    const BERLIN: &str = "BEGIN:VTIMEZONE\r\nTZID:Europe/Berlin\r\n\
+        BEGIN:DAYLIGHT\r\nDTSTART:19700329T020000\r\nTZOFFSETFROM:+0100\r\nTZOFFSETTO:+0200\r\n\
+        RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\nEND:DAYLIGHT\r\n\
+        BEGIN:STANDARD\r\nDTSTART:19701025T030000\r\nTZOFFSETFROM:+0200\r\nTZOFFSETTO:+0100\r\n\
+        RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n";
+
+    #[test]
+    fn unfolds_lines_and_unescapes_text() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\r\nUID:1\r\nDTSTART:20250106T090000Z\r\nDTEND:20250106T100000Z\r\n\
+             SUMMARY:Planning\\, part one\r\n  and two\r\nEND:VEVENT\r\n",
+        );
+        let instances = calendar.instances(utc("2025-01-06 00:00"), utc("2025-01-07 00:00"));
+        assert_eq!(summaries(&instances), vec!["Planning, part one and two"]);
+    }
+
+    #[test]
+    fn quoted_parameters_may_contain_colons() {
+        let property = parse_line("ATTENDEE;CN=\"Doe: Jane\";ROLE=CHAIR:mailto:jane@example.com").unwrap();
+        assert_eq!(property.name, "ATTENDEE");
+        assert_eq!(property.param("cn"), Some("Doe: Jane"));
+        assert_eq!(property.value, "mailto:jane@example.com");
+    }
+
+    #[test]
+    fn mismatched_components_are_errors() {
+        assert!(Calendar::parse("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VCALENDAR\r\n").is_err());
+        assert!(Calendar::parse("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\n").is_err());
+        assert!(Calendar::parse("END:VEVENT\r\n").is_err());
+    }
+
+    #[test]
+    fn zoned_times_use_the_files_timezone() {
+        let calendar = calendar(&format!(
+            "{}BEGIN:VEVENT\r\nUID:1\r\nSUMMARY:Winter\r\nDTSTART;TZID=Europe/Berlin:20250115T090000\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:2\r\nSUMMARY:Summer\r\nDTSTART;TZID=Europe/Berlin:20250715T090000\r\nEND:VEVENT\r\n",
+            BERLIN
+        ));
+        let instances = calendar.instances(utc("2025-01-01 00:00"), utc("2026-01-01 00:00"));
+        let starts: Vec<DateTime<Utc>> = instances.iter().map(|instance| instance.start).collect();
+        assert_eq!(starts, vec![utc("2025-01-15 08:00"), utc("2025-07-15 07:00")]);
+    }
+
+    #[test]
+    fn weekly_meeting_keeps_its_local_time_across_the_change() {
+        let calendar = calendar(&format!(
+            "{}BEGIN:VEVENT\r\nUID:1\r\nSUMMARY:Standup\r\n\
+             DTSTART;TZID=Europe/Berlin:20250327T090000\r\nDURATION:PT15M\r\n\
+             RRULE:FREQ=WEEKLY\r\nEND:VEVENT\r\n",
+            BERLIN
+        ));
+        let instances = calendar.instances(utc("2025-03-27 00:00"), utc("2025-04-04 00:00"));
+        let starts: Vec<DateTime<Utc>> = instances.iter().map(|instance| instance.start).collect();
+        assert_eq!(starts, vec![utc("2025-03-27 08:00"), utc("2025-04-03 07:00")]);
+    }
+
+    #[test]
+    fn exceptions_and_overrides_replace_occurrences() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\r\nUID:daily\r\nSUMMARY:Daily\r\nDTSTART:20250106T090000Z\r\nDURATION:PT15M\r\n\
+             RRULE:FREQ=DAILY;COUNT=4\r\nEXDATE:20250107T090000Z\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:daily\r\nSUMMARY:Daily (moved)\r\nRECURRENCE-ID:20250108T090000Z\r\n\
+             DTSTART:20250108T140000Z\r\nDURATION:PT15M\r\nEND:VEVENT\r\n",
+        );
+        let instances = calendar.instances(utc("2025-01-06 00:00"), utc("2025-01-10 00:00"));
+        let found: Vec<(&str, DateTime<Utc>)> = instances
+            .iter()
+            .map(|instance| (instance.summary.as_str(), instance.start))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Daily", utc("2025-01-06 09:00")),
+                ("Daily (moved)", utc("2025-01-08 14:00")),
+                ("Daily", utc("2025-01-09 09:00")),
+            ]
+        );
+    }
+
+    #[test]
+    fn cancelled_and_free_events_are_marked_free() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\r\nUID:1\r\nSUMMARY:Cancelled\r\nDTSTART:20250106T090000Z\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:2\r\nSUMMARY:Focus\r\nDTSTART:20250106T100000Z\r\nTRANSP:TRANSPARENT\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:3\r\nSUMMARY:Review\r\nDTSTART:20250106T110000Z\r\nEND:VEVENT\r\n",
+        );
+        let instances = calendar.instances(utc("2025-01-06 00:00"), utc("2025-01-07 00:00"));
+        let free: Vec<bool> = instances.iter().map(|instance| instance.free).collect();
+        assert_eq!(free, vec![true, true, false]);
+    }
+}
+
@@ -0,0 +1,515 @@
+//! Calendar awareness: busy blocks from local iCalendar files, used to keep
+//! breaks out of meetings and out of the minutes right before them.
+//!
+//! `calendar_paths` lists `.ics` files and directories; every `*.ics` file in a
+//! listed directory is read. Files are re-read when they change on disk.
+//!
+//! A CalDAV account (`caldav_url`) is synced in the background every
+//! `caldav_refresh_minutes`. The last successful sync is kept in
+//! `caldav_cache.json`, so the events stay in use while the server is offline.
+//! The password lives in `caldav_credentials.json`, separate from the settings.
+
+mod caldav;
+mod ics;
+mod rrule;
+pub mod schedule;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::AppHandle;
+
+use crate::settings::{self, AppSettings};
+use caldav::{CalDavAccount, CalDavClient, FetchedCalendar};
+use ics::Calendar;
+use schedule::{BusyWindow, SchedulePolicy};
+
+/// Breaks move at most this much earlier than planned.
+const MAX_ADVANCE_SECONDS: i64 = 15 * 60;
+
+/// Breaks move at most this much later than planned.
+const MAX_DELAY_SECONDS: i64 = 90 * 60;
+
+/// `report` looks at most this far ahead.
+const MAX_REPORT_HOURS: u32 = 24 * 31;
+
+const CREDENTIALS_FILE: &str = "caldav_credentials.json";
+const CACHE_FILE: &str = "caldav_cache.json";
+
+/// CalDAV events are fetched from a day back to this many days ahead.
+const CALDAV_FETCH_DAYS: i64 = 7;
+
+/// How often the sync thread checks whether a CalDAV sync is due.
+const SYNC_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// After a failed sync, wait at least this long before trying again.
+const SYNC_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// A busy stretch from the calendar.
+#[derive(Clone, Debug, Serialize)]
+pub struct BusyBlock {
+    pub summary: String,
+    pub start_unix_ms: i64,
+    pub end_unix_ms: i64,
+}
+
+/// State of the CalDAV account, for `get_calendar_busy` and `sync_caldav`.
+#[derive(Clone, Debug, Serialize)]
+pub struct CalDavStatus {
+    pub url: String,
+    pub calendars: Vec<String>,
+    pub event_count: usize,
+    /// When the events in use were fetched; `None` before the first sync.
+    pub synced_unix_ms: Option<i64>,
+    /// Why the last sync failed. Earlier events stay in use meanwhile.
+    pub error: Option<String>,
+}
+
+/// What `get_calendar_busy` reports.
+#[derive(Clone, Debug, Serialize)]
+pub struct CalendarReport {
+    pub files: Vec<String>,
+    pub event_count: usize,
+    /// Files that could not be read or parsed.
+    pub errors: Vec<String>,
+    pub caldav: Option<CalDavStatus>,
+    pub busy: Vec<BusyBlock>,
+}
+
+/// The password, remembered for one server and user name only.
+#[derive(Serialize, Deserialize)]
+struct CalDavCredentials {
+    url: String,
+    username: String,
+    password: String,
+}
+
+/// The last successful CalDAV sync, as written to `caldav_cache.json`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct CalDavCache {
+    url: String,
+    fetched_unix_ms: i64,
+    calendars: Vec<FetchedCalendar>,
+}
+
+#[derive(Clone, Default, PartialEq)]
+struct CalDavSettings {
+    url: String,
+    username: String,
+    refresh: Duration,
+}
+
+#[derive(Default)]
+struct CalendarState {
+    enabled: bool,
+    paths: Vec<PathBuf>,
+    /// Modification times of the files `calendar` was built from.
+    loaded: BTreeMap<PathBuf, Option<SystemTime>>,
+    calendar: Calendar,
+    errors: Vec<String>,
+    data_dir: Option<PathBuf>,
+    caldav: Option<CalDavSettings>,
+    caldav_cache: CalDavCache,
+    caldav_calendar: Calendar,
+    caldav_error: Option<String>,
+    /// When the last sync was attempted, successful or not.
+    caldav_attempted: Option<Instant>,
+}
+
+static CALENDAR: OnceLock<Mutex<CalendarState>> = OnceLock::new();
+
+fn state() -> &'static Mutex<CalendarState> {
+    CALENDAR.get_or_init(|| Mutex::new(CalendarState::default()))
+}
+
+/// Expand a leading `~` to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")), home) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Pick up `calendar_paths` and the CalDAV account from the settings. The files
+/// are read on next use; a changed account is synced by the background thread.
+pub fn apply_settings(settings: &AppSettings) {
+    if let Ok(mut state) = state().lock() {
+        state.enabled = settings.calendar_enabled;
+        state.paths = settings
+            .calendar_paths
+            .iter()
+            .map(|path| path.trim())
+            .filter(|path| !path.is_empty())
+            .map(expand_home)
+            .collect();
+        state.loaded.clear();
+        state.calendar = Calendar::default();
+        state.errors.clear();
+
+        let caldav = Some(settings.caldav_url.trim())
+            .filter(|url| !url.is_empty())
+            .map(|url| CalDavSettings {
+                url: url.to_string(),
+                username: settings.caldav_username.trim().to_string(),
+                refresh: Duration::from_secs(settings.caldav_refresh_minutes.max(1) as u64 * 60),
+            });
+        let account_changed = caldav.as_ref().map(|c| (&c.url, &c.username))
+            != state.caldav.as_ref().map(|c| (&c.url, &c.username));
+        state.caldav = caldav;
+        if account_changed {
+            state.caldav_error = None;
+            state.caldav_attempted = None;
+            let cache = load_cache(&state);
+            use_cache(&mut state, cache);
+        }
+    }
+}
+
+/// Locate the credentials and cache files, load the cached CalDAV events, and
+/// start the background sync.
+pub fn init(app_handle: &AppHandle) {
+    match settings::app_data_dir(app_handle) {
+        Ok(dir) => {
+            if let Ok(mut state) = state().lock() {
+                state.data_dir = Some(dir);
+                let cache = load_cache(&state);
+                use_cache(&mut state, cache);
+            }
+        }
+        Err(e) => println!("⚠️ {}, CalDAV events won't be cached", e),
+    }
+
+    thread::spawn(|| loop {
+        thread::sleep(SYNC_CHECK_INTERVAL);
+        if sync_due() {
+            let _ = sync_caldav();
+        }
+    });
+}
+
+/// The scheduling policy for the settings, or `None` when calendar awareness is off.
+pub fn schedule_policy(settings: &AppSettings) -> Option<SchedulePolicy> {
+    let has_source = !settings.calendar_paths.is_empty() || !settings.caldav_url.trim().is_empty();
+    if !settings.calendar_enabled || !has_source {
+        return None;
+    }
+    Some(SchedulePolicy {
+        lead: settings.calendar_lead_minutes as i64 * 60,
+        long_meeting: settings.calendar_long_meeting_minutes.max(1) as i64 * 60,
+        max_advance: MAX_ADVANCE_SECONDS,
+        max_delay: MAX_DELAY_SECONDS,
+    })
+}
+
+/// The `.ics` files named by `paths`, directories expanded, with their modification times.
+fn ics_files(paths: &[PathBuf]) -> BTreeMap<PathBuf, Option<SystemTime>> {
+    let mut files = BTreeMap::new();
+    for path in paths {
+        if path.is_dir() {
+            let entries = match fs::read_dir(path) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let file = entry.path();
+                let is_ics = file
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"));
+                if is_ics {
+                    let modified = entry.metadata().and_then(|m| m.modified()).ok();
+                    files.insert(file, modified);
+                }
+            }
+        } else {
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+            files.insert(path.clone(), modified);
+        }
+    }
+    files
+}
+
+/// Re-read the files if any was added, removed or changed since the last load.
+fn refresh(state: &mut CalendarState) {
+    let files = ics_files(&state.paths);
+    if files == state.loaded {
+        return;
+    }
+
+    let mut calendar = Calendar::default();
+    let mut errors = Vec::new();
+    for path in files.keys() {
+        let result = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| calendar.add(&text));
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+    println!(
+        "📅 Loaded {} calendar events from {} file(s){}",
+        calendar.event_count(),
+        files.len(),
+        if errors.is_empty() {
+            String::new()
+        } else {
+            format!(", {} failed: {}", errors.len(), errors.join("; "))
+        }
+    );
+    state.calendar = calendar;
+    state.errors = errors;
+    state.loaded = files;
+}
+
+/// The cached CalDAV events, if they belong to the configured account.
+fn load_cache(state: &CalendarState) -> CalDavCache {
+    let (dir, caldav) = match (&state.data_dir, &state.caldav) {
+        (Some(dir), Some(caldav)) => (dir, caldav),
+        _ => return CalDavCache::default(),
+    };
+    fs::read_to_string(dir.join(CACHE_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str::<CalDavCache>(&json).ok())
+        .filter(|cache| cache.url == caldav.url)
+        .unwrap_or_default()
+}
+
+/// Make `cache` the CalDAV events in use. Objects that fail to parse are
+/// skipped and reported.
+fn use_cache(state: &mut CalendarState, cache: CalDavCache) {
+    let mut calendar = Calendar::default();
+    let mut failed = 0;
+    for object in cache.calendars.iter().flat_map(|c| &c.objects) {
+        if calendar.add(object).is_err() {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        state.caldav_error = Some(format!("{} CalDAV event(s) could not be read", failed));
+    }
+    state.caldav_calendar = calendar;
+    state.caldav_cache = cache;
+}
+
+/// Write `contents` readable by the current user only, where the platform allows.
+fn write_private(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp_path)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    std::io::Write::write_all(&mut file, contents.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Remember the CalDAV password for the configured server and user name. An
+/// empty password forgets it.
+pub fn set_caldav_password(password: &str) -> Result<(), String> {
+    let (path, caldav) = {
+        let mut state = state().lock().map_err(|e| format!("Calendar unavailable: {}", e))?;
+        state.caldav_attempted = None;
+        let dir = state.data_dir.clone().ok_or("App data directory unavailable")?;
+        let caldav = state.caldav.clone().ok_or("Enter the CalDAV server address first")?;
+        (dir.join(CREDENTIALS_FILE), caldav)
+    };
+    if password.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        };
+    }
+    let credentials = CalDavCredentials {
+        url: caldav.url,
+        username: caldav.username,
+        password: password.to_string(),
+    };
+    let json = serde_json::to_string_pretty(&credentials).map_err(|e| e.to_string())?;
+    write_private(&path, &json)
+}
+
+/// Whether a password is stored for the configured server and user name.
+pub fn has_caldav_password() -> bool {
+    state()
+        .lock()
+        .ok()
+        .and_then(|state| caldav_account(&state))
+        .is_some_and(|account| account.password.is_some())
+}
+
+fn caldav_account(state: &CalendarState) -> Option<CalDavAccount> {
+    let caldav = state.caldav.as_ref()?;
+    let password = state
+        .data_dir
+        .as_ref()
+        .and_then(|dir| fs::read_to_string(dir.join(CREDENTIALS_FILE)).ok())
+        .and_then(|json| serde_json::from_str::<CalDavCredentials>(&json).ok())
+        .filter(|c| c.url == caldav.url && c.username == caldav.username)
+        .map(|c| c.password);
+    Some(CalDavAccount {
+        url: caldav.url.clone(),
+        username: caldav.username.clone(),
+        password,
+    })
+}
+
+fn sync_due() -> bool {
+    let state = match state().lock() {
+        Ok(state) => state,
+        Err(_) => return false,
+    };
+    let caldav = match (&state.caldav, state.enabled) {
+        (Some(caldav), true) => caldav,
+        _ => return false,
+    };
+    let interval = if state.caldav_error.is_some() {
+        caldav.refresh.min(SYNC_RETRY_DELAY)
+    } else {
+        caldav.refresh
+    };
+    state
+        .caldav_attempted
+        .is_none_or(|attempted| attempted.elapsed() >= interval)
+}
+
+fn caldav_status(state: &CalendarState) -> Option<CalDavStatus> {
+    let caldav = state.caldav.as_ref()?;
+    let cache = &state.caldav_cache;
+    Some(CalDavStatus {
+        url: caldav.url.clone(),
+        calendars: cache.calendars.iter().map(|c| c.name.clone()).collect(),
+        event_count: state.caldav_calendar.event_count(),
+        synced_unix_ms: (cache.fetched_unix_ms > 0).then_some(cache.fetched_unix_ms),
+        error: state.caldav_error.clone(),
+    })
+}
+
+/// Fetch the CalDAV events now. On failure the previously synced events stay
+/// in use and the error is reported in the status.
+pub fn sync_caldav() -> Result<CalDavStatus, String> {
+    static SYNCING: Mutex<()> = Mutex::new(());
+    let _syncing = SYNCING.lock().map_err(|e| e.to_string())?;
+
+    let (account, data_dir) = {
+        let mut state = state().lock().map_err(|e| format!("Calendar unavailable: {}", e))?;
+        let account = caldav_account(&state).ok_or("No CalDAV server configured")?;
+        state.caldav_attempted = Some(Instant::now());
+        (account, state.data_dir.clone())
+    };
+
+    // The network round trips happen without holding the state lock
+    let now = Utc::now();
+    let format = "%Y%m%dT%H%M%SZ";
+    let start = (now - chrono::Duration::days(1)).format(format).to_string();
+    let end = (now + chrono::Duration::days(CALDAV_FETCH_DAYS)).format(format).to_string();
+    let fetched = CalDavClient::new(account.clone()).and_then(|client| client.fetch(&start, &end));
+
+    let mut state = state().lock().map_err(|e| format!("Calendar unavailable: {}", e))?;
+    if state.caldav.as_ref().map(|c| &c.url) != Some(&account.url) {
+        return Err("The CalDAV account changed during the sync".to_string());
+    }
+    match fetched {
+        Ok(calendars) => {
+            let cache = CalDavCache {
+                url: account.url,
+                fetched_unix_ms: now.timestamp_millis(),
+                calendars,
+            };
+            println!(
+                "📅 Synced {} CalDAV calendar(s) with {} event(s)",
+                cache.calendars.len(),
+                cache.calendars.iter().map(|c| c.objects.len()).sum::<usize>()
+            );
+            if let Some(dir) = data_dir {
+                let written = serde_json::to_string(&cache)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| write_private(&dir.join(CACHE_FILE), &json));
+                if let Err(e) = written {
+                    println!("⚠️ Failed to cache CalDAV events: {}", e);
+                }
+            }
+            state.caldav_error = None;
+            use_cache(&mut state, cache);
+        }
+        Err(e) => {
+            println!("⚠️ CalDAV sync failed, keeping the last synced events: {}", e);
+            state.caldav_error = Some(e);
+        }
+    }
+    caldav_status(&state).ok_or_else(|| "No CalDAV server configured".to_string())
+}
+
+/// Busy blocks overlapping `from..to`, sorted by start. All-day events and
+/// events marked free or cancelled don't count.
+pub fn busy_blocks(from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<BusyBlock> {
+    let mut state = match state().lock() {
+        Ok(state) => state,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if state.paths.is_empty() && state.caldav.is_none() {
+        return Vec::new();
+    }
+    refresh(&mut state);
+    let mut instances = state.calendar.instances(from, to);
+    if state.caldav.is_some() {
+        instances.extend(state.caldav_calendar.instances(from, to));
+        instances.sort_by_key(|instance| instance.start);
+    }
+    instances
+        .into_iter()
+        .filter(|instance| !instance.all_day && !instance.free)
+        .map(|instance| BusyBlock {
+            summary: instance.summary,
+            start_unix_ms: instance.start.timestamp_millis(),
+            end_unix_ms: instance.end.timestamp_millis(),
+        })
+        .collect()
+}
+
+/// Busy blocks of the next `horizon`, relative to `now`, for `schedule::plan_break`.
+pub fn busy_windows(now: SystemTime, horizon: std::time::Duration) -> Vec<BusyWindow> {
+    let now: DateTime<Utc> = now.into();
+    let to = now + chrono::Duration::from_std(horizon).unwrap_or(chrono::Duration::days(1));
+    let now_ms = now.timestamp_millis();
+    busy_blocks(now, to)
+        .into_iter()
+        .map(|block| BusyWindow {
+            start: (block.start_unix_ms - now_ms).div_euclid(1000),
+            end: (block.end_unix_ms - now_ms).div_euclid(1000),
+            summary: block.summary,
+        })
+        .collect()
+}
+
+/// The files in use, any errors, and the busy blocks of the next `hours`, up to
+/// a month.
+pub fn report(hours: u32) -> CalendarReport {
+    let now = Utc::now();
+    let hours = hours.min(MAX_REPORT_HOURS);
+    let busy = busy_blocks(now, now + chrono::Duration::hours(hours as i64));
+    let state = match state().lock() {
+        Ok(state) => state,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    CalendarReport {
+        files: state.loaded.keys().map(|path| path.display().to_string()).collect(),
+        event_count: state.calendar.event_count(),
+        errors: state.errors.clone(),
+        caldav: caldav_status(&state),
+        busy,
+    }
+}
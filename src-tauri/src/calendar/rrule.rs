@@ -0,0 +1,418 @@
+//! `RRULE` recurrence expansion (RFC 5545 §3.3.10).
+//!
+//! Supports DAILY, WEEKLY, MONTHLY and YEARLY rules with INTERVAL, COUNT,
+//! UNTIL, BYDAY (with ordinals), BYMONTHDAY, BYMONTH and BYSETPOS, which covers
+//! what calendar clients export for meetings and what VTIMEZONE definitions use.
+//! Occurrences are computed in the event's local wall-clock time.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+
+/// Upper bound on the periods walked for one rule, so a malformed rule can't spin.
+const MAX_PERIODS: u32 = 50_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    /// Raw UNTIL value; the caller converts it to the event's local time.
+    pub until: Option<String>,
+    /// Weekdays, with an optional ordinal (`2SU` → `(Some(2), Sun)`, `-1FR` → `(Some(-1), Fri)`).
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str, part: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|item| {
+            item.trim_start_matches('+')
+                .parse()
+                .map_err(|_| format!("invalid {} value \"{}\"", part, item))
+        })
+        .collect()
+}
+
+impl RRule {
+    /// Parse an RRULE value such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut freq = None;
+        let mut rule = RRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+        };
+
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid RRULE part \"{}\"", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(format!("unsupported FREQ {}", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|&interval| interval > 0)
+                        .ok_or_else(|| format!("invalid INTERVAL \"{}\"", value))?
+                }
+                "COUNT" => {
+                    rule.count = Some(value.parse().map_err(|_| format!("invalid COUNT \"{}\"", value))?)
+                }
+                "UNTIL" => rule.until = Some(value.to_string()),
+                "BYDAY" => {
+                    for item in value.split(',') {
+                        let item = item.trim().to_ascii_uppercase();
+                        let invalid = || format!("invalid BYDAY value \"{}\"", item);
+                        let split = item.len().saturating_sub(2);
+                        let weekday = item.get(split..).and_then(parse_weekday).ok_or_else(invalid)?;
+                        let ordinal = match item.get(..split).ok_or_else(invalid)? {
+                            "" => None,
+                            n => Some(
+                                n.trim_start_matches('+').parse().map_err(|_| invalid())?,
+                            ),
+                        };
+                        rule.by_day.push((ordinal, weekday));
+                    }
+                }
+                "BYMONTHDAY" => rule.by_month_day = parse_list(value, "BYMONTHDAY")?,
+                "BYMONTH" => rule.by_month = parse_list(value, "BYMONTH")?,
+                "BYSETPOS" => rule.by_set_pos = parse_list(value, "BYSETPOS")?,
+                // WKST only matters for weekly rules with BYDAY and INTERVAL > 1;
+                // weeks are taken to start on Monday, the RFC default
+                "WKST" => {}
+                // BYHOUR, BYMINUTE, … are not used by meeting invitations
+                _ => {}
+            }
+        }
+
+        rule.freq = freq.ok_or("RRULE without FREQ")?;
+        Ok(rule)
+    }
+
+    /// Occurrence start times from `start` up to and including `end`, stopping
+    /// early at `until` (already in local time) or after COUNT occurrences.
+    pub fn expand(&self, start: NaiveDateTime, until: Option<NaiveDateTime>, end: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let mut occurrences = Vec::new();
+        let mut produced = 0;
+        let limit = until.map_or(end, |until| until.min(end));
+
+        for period in 0..MAX_PERIODS {
+            let (period_start, mut dates) = match self.period_dates(start.date(), period) {
+                Some(period) => period,
+                None => break,
+            };
+            if period_start.and_time(start.time()) > limit {
+                break;
+            }
+            dates.sort();
+            dates.dedup();
+            let dates = apply_set_pos(dates, &self.by_set_pos);
+
+            for date in dates {
+                let occurrence = date.and_time(start.time());
+                if occurrence < start {
+                    continue;
+                }
+                if self.count.is_some_and(|count| produced >= count) || occurrence > limit {
+                    return occurrences;
+                }
+                produced += 1;
+                occurrences.push(occurrence);
+            }
+        }
+        occurrences
+    }
+
+    /// First day of the `index`-th period and the candidate dates in it.
+    fn period_dates(&self, start: NaiveDate, index: u32) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = index.checked_mul(self.interval)?;
+        match self.freq {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(Duration::days(step as i64))?;
+                let matches = self.month_matches(date)
+                    && (self.by_month_day.is_empty() || month_day_matches(date, &self.by_month_day))
+                    && (self.by_day.is_empty() || self.by_day.iter().any(|(_, day)| *day == date.weekday()));
+                Some((date, if matches { vec![date] } else { Vec::new() }))
+            }
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+                let week = monday.checked_add_signed(Duration::weeks(step as i64))?;
+                let weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![start.weekday()]
+                } else {
+                    self.by_day.iter().map(|(_, day)| *day).collect()
+                };
+                let dates = weekdays
+                    .into_iter()
+                    .map(|day| week + Duration::days(day.num_days_from_monday() as i64))
+                    .filter(|date| self.month_matches(*date))
+                    .collect();
+                Some((week, dates))
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + step as i64;
+                let (year, month) = ((months / 12) as i32, (months % 12) as u32 + 1);
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let dates = if self.by_month.is_empty() || self.by_month.contains(&month) {
+                    self.month_dates(year, month, start.day())
+                } else {
+                    Vec::new()
+                };
+                Some((first, dates))
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(step as i32)?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                let dates = if !self.by_month.is_empty() {
+                    self.by_month
+                        .iter()
+                        .flat_map(|&month| self.month_dates(year, month, start.day()))
+                        .collect()
+                } else if !self.by_day.is_empty() && self.by_month_day.is_empty() {
+                    year_weekdays(year, &self.by_day)
+                } else if !self.by_month_day.is_empty() {
+                    (1..=12)
+                        .flat_map(|month| self.month_dates(year, month, start.day()))
+                        .collect()
+                } else {
+                    NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                        .into_iter()
+                        .collect()
+                };
+                Some((first, dates))
+            }
+        }
+    }
+
+    fn month_matches(&self, date: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&date.month())
+    }
+
+    /// Dates in one month selected by BYMONTHDAY and/or BYDAY, or `default_day`
+    /// when neither is given.
+    fn month_dates(&self, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+        let days: Vec<NaiveDate> = match NaiveDate::from_ymd_opt(year, month, 1) {
+            Some(first) => first.iter_days().take_while(|date| date.month() == month).collect(),
+            None => return Vec::new(),
+        };
+
+        let by_month_day: Vec<NaiveDate> = days
+            .iter()
+            .copied()
+            .filter(|date| month_day_matches(*date, &self.by_month_day))
+            .collect();
+        let by_day = weekdays_in(&days, &self.by_day);
+
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            (true, true) => days.into_iter().filter(|date| date.day() == default_day).collect(),
+            (false, true) => by_month_day,
+            (true, false) => by_day,
+            (false, false) => by_month_day.into_iter().filter(|date| by_day.contains(date)).collect(),
+        }
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> i32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day() as i32)
+}
+
+/// Whether `date` is one of `days`; negative entries count from the month's end.
+fn month_day_matches(date: NaiveDate, days: &[i32]) -> bool {
+    let last = days_in_month(date);
+    days.iter().any(|&day| {
+        let day = if day < 0 { last + day + 1 } else { day };
+        day == date.day() as i32
+    })
+}
+
+/// The dates in `days` (one month or one year, in order) selected by BYDAY
+/// entries. An ordinal picks the n-th such weekday, counting from the end when negative.
+fn weekdays_in(days: &[NaiveDate], by_day: &[(Option<i32>, Weekday)]) -> Vec<NaiveDate> {
+    let mut selected = Vec::new();
+    for &(ordinal, weekday) in by_day {
+        let matching: Vec<NaiveDate> = days.iter().copied().filter(|date| date.weekday() == weekday).collect();
+        match ordinal {
+            None => selected.extend(matching),
+            Some(n) if n > 0 => selected.extend(matching.get(n as usize - 1)),
+            Some(n) if n < 0 => selected.extend(matching.len().checked_sub(n.unsigned_abs() as usize).map(|i| matching[i])),
+            Some(_) => {}
+        }
+    }
+    selected.sort();
+    selected
+}
+
+fn year_weekdays(year: i32, by_day: &[(Option<i32>, Weekday)]) -> Vec<NaiveDate> {
+    let days: Vec<NaiveDate> = match NaiveDate::from_ymd_opt(year, 1, 1) {
+        Some(first) => first.iter_days().take_while(|date| date.year() == year).collect(),
+        None => return Vec::new(),
+    };
+    weekdays_in(&days, by_day)
+}
+
+/// Keep only the BYSETPOS-th entries of one period's sorted candidates.
+fn apply_set_pos(dates: Vec<NaiveDate>, positions: &[i32]) -> Vec<NaiveDate> {
+    if positions.is_empty() {
+        return dates;
+    }
+    let len = dates.len() as i32;
+    let mut selected: Vec<NaiveDate> = positions
+        .iter()
+        .filter_map(|&pos| {
+            let index = if pos > 0 { pos - 1 } else { len + pos };
+            (0..len).contains(&index).then(|| dates[index as usize])
+        })
+        .collect();
+    selected.sort();
+    selected.dedup();
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    /// Dates of the occurrences of `rule` from `start` up to `end`.
+    fn dates(rule: &str, start: &str, end: &str) -> Vec<String> {
+        RRule::parse(rule)
+            .unwrap()
+            .expand(at(start), None, at(end))
+            .iter()
+            .map(|time| time.format("%Y-%m-%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parses_rule_parts() {
+        let rule = RRule::parse("FREQ=MONTHLY;INTERVAL=2;COUNT=5;BYDAY=2SU,-1FR,MO;WKST=SU").unwrap();
+        assert_eq!(rule.freq, Frequency::Monthly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.count, Some(5));
+        assert_eq!(
+            rule.by_day,
+            vec![(Some(2), Weekday::Sun), (Some(-1), Weekday::Fri), (None, Weekday::Mon)]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        assert!(RRule::parse("INTERVAL=2").is_err());
+        assert!(RRule::parse("FREQ=HOURLY").is_err());
+        assert!(RRule::parse("FREQ=DAILY;INTERVAL=0").is_err());
+        assert!(RRule::parse("FREQ=WEEKLY;BYDAY=XX").is_err());
+        assert!(RRule::parse("FREQ=WEEKLY;COUNT").is_err());
+    }
+
+    #[test]
+    fn weekly_on_several_days_with_count() {
+        assert_eq!(
+            dates("FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4", "2025-01-06 09:00", "2026-01-01 00:00"),
+            ["2025-01-06", "2025-01-08", "2025-01-13", "2025-01-15"]
+        );
+    }
+
+    #[test]
+    fn every_other_week() {
+        assert_eq!(
+            dates("FREQ=WEEKLY;INTERVAL=2", "2025-01-08 09:00", "2025-02-06 00:00"),
+            ["2025-01-08", "2025-01-22", "2025-02-05"]
+        );
+    }
+
+    #[test]
+    fn stops_at_until() {
+        let rule = RRule::parse("FREQ=DAILY").unwrap();
+        let occurrences = rule.expand(at("2025-01-06 09:00"), Some(at("2025-01-08 09:00")), at("2026-01-01 00:00"));
+        assert_eq!(occurrences.len(), 3);
+    }
+
+    #[test]
+    fn last_friday_of_the_month() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR", "2025-01-31 15:00", "2025-04-30 00:00"),
+            ["2025-01-31", "2025-02-28", "2025-03-28", "2025-04-25"]
+        );
+    }
+
+    #[test]
+    fn monthly_on_the_31st_skips_shorter_months() {
+        assert_eq!(
+            dates("FREQ=MONTHLY", "2025-01-31 09:00", "2025-06-01 00:00"),
+            ["2025-01-31", "2025-03-31", "2025-05-31"]
+        );
+    }
+
+    #[test]
+    fn last_workday_of_the_month() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1", "2025-05-30 16:00", "2025-09-01 00:00"),
+            ["2025-05-30", "2025-06-30", "2025-07-31", "2025-08-29"]
+        );
+    }
+
+    #[test]
+    fn yearly_daylight_saving_onset() {
+        assert_eq!(
+            dates("FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU", "1970-03-29 02:00", "2026-12-31 00:00")
+                .split_off(55),
+            ["2025-03-30", "2026-03-29"]
+        );
+    }
+
+    #[test]
+    fn occurrences_before_the_start_are_skipped() {
+        // The month's first Monday comes before the start
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=MO", "2025-01-15 09:00", "2025-02-04 00:00"),
+            ["2025-01-20", "2025-01-27", "2025-02-03"]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Placing a break around busy calendar blocks.
+
+/// A busy block relative to now, in seconds (negative when it already started).
+#[derive(Clone, Debug, PartialEq)]
+pub struct BusyWindow {
+    pub start: i64,
+    pub end: i64,
+    pub summary: String,
+}
+
+/// How breaks are placed around busy blocks. All values in seconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchedulePolicy {
+    /// Free time wanted between the end of a break and the next meeting.
+    pub lead: i64,
+    /// Meetings at least this long move the break before them instead of after.
+    pub long_meeting: i64,
+    /// A break is moved at most this much earlier than planned.
+    pub max_advance: i64,
+    /// A break is moved at most this much later; beyond that it stays put and
+    /// meeting detection decides at break time.
+    pub max_delay: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BreakPlan {
+    Keep,
+    /// Start the break this many seconds from now instead.
+    Move { seconds: u32, reason: String },
+}
+
+/// Join blocks that overlap or leave too little room for a break between them.
+fn merge(busy: &[BusyWindow], min_gap: i64) -> Vec<BusyWindow> {
+    let mut blocks: Vec<BusyWindow> = busy.to_vec();
+    blocks.sort_by_key(|block| block.start);
+    let mut merged: Vec<BusyWindow> = Vec::new();
+    for block in blocks {
+        match merged.last_mut() {
+            Some(last) if block.start - last.end < min_gap => {
+                last.end = last.end.max(block.end);
+            }
+            _ => merged.push(block),
+        }
+    }
+    merged
+}
+
+/// Decide where a break due in `break_in` seconds and lasting `break_length`
+/// seconds should go.
+///
+/// A break that would overlap a busy block, or end less than `lead` before one,
+/// is moved. Before a long meeting that hasn't started yet it moves earlier, so
+/// the user gets a break before sitting through it; otherwise it moves to the
+/// end of the busy stretch.
+pub fn plan_break(policy: &SchedulePolicy, break_in: i64, break_length: i64, busy: &[BusyWindow]) -> BreakPlan {
+    let needed = break_length + policy.lead;
+    let blocks = merge(busy, needed);
+    let conflict = match blocks
+        .iter()
+        .find(|block| break_in < block.end && break_in + needed > block.start)
+    {
+        Some(block) => block,
+        None => return BreakPlan::Keep,
+    };
+
+    let long = conflict.end - conflict.start >= policy.long_meeting;
+    if long && conflict.start > 0 {
+        let earlier = conflict.start - needed;
+        if earlier >= 0 && break_in - earlier <= policy.max_advance {
+            return BreakPlan::Move {
+                seconds: earlier as u32,
+                reason: format!("Moved earlier, before \"{}\"", conflict.summary),
+            };
+        }
+    }
+
+    let later = conflict.end.max(0);
+    if later - break_in <= policy.max_delay {
+        return BreakPlan::Move {
+            seconds: later as u32,
+            reason: format!("Moved after \"{}\"", conflict.summary),
+        };
+    }
+    BreakPlan::Keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60;
+
+    fn policy() -> SchedulePolicy {
+        SchedulePolicy {
+            lead: 5 * MINUTE,
+            long_meeting: 60 * MINUTE,
+            max_advance: 15 * MINUTE,
+            max_delay: 90 * MINUTE,
+        }
+    }
+
+    fn busy(summary: &str, start: i64, end: i64) -> BusyWindow {
+        BusyWindow {
+            start: start * MINUTE,
+            end: end * MINUTE,
+            summary: summary.to_string(),
+        }
+    }
+
+    fn moved(minutes: i64, reason: &str) -> BreakPlan {
+        BreakPlan::Move {
+            seconds: (minutes * MINUTE) as u32,
+            reason: reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn plans_breaks_around_busy_blocks() {
+        // A 10-minute break due in 30 minutes, against the busy blocks, in minutes
+        let cases = [
+            ("no meetings", vec![], BreakPlan::Keep),
+            ("room to spare", vec![busy("Sync", 45, 75)], BreakPlan::Keep),
+            ("overlap", vec![busy("Sync", 35, 50)], moved(50, "Moved after \"Sync\"")),
+            ("ends within lead", vec![busy("Sync", 43, 55)], moved(55, "Moved after \"Sync\"")),
+            ("already started", vec![busy("Sync", -10, 40)], moved(40, "Moved after \"Sync\"")),
+            (
+                "long meeting ahead",
+                vec![busy("Review", 40, 100)],
+                moved(25, "Moved earlier, before \"Review\""),
+            ),
+            (
+                "long meeting too close to move before",
+                vec![busy("Review", 10, 70)],
+                moved(70, "Moved after \"Review\""),
+            ),
+            ("past the longest delay", vec![busy("Offsite", 20, 240)], BreakPlan::Keep),
+            (
+                "blocks too close for a break between",
+                vec![busy("Sync", 55, 70), busy("Standup", 35, 50)],
+                moved(70, "Moved after \"Standup\""),
+            ),
+        ];
+        for (name, blocks, expected) in cases {
+            assert_eq!(plan_break(&policy(), 30 * MINUTE, 10 * MINUTE, &blocks), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn break_moves_after_a_long_meeting_when_before_is_too_early() {
+        // Moving 20 minutes earlier is more than the 15 allowed
+        let blocks = [busy("Review", 35, 95)];
+        assert_eq!(
+            plan_break(&policy(), 40 * MINUTE, 10 * MINUTE, &blocks),
+            moved(95, "Moved after \"Review\"")
+        );
+    }
+}
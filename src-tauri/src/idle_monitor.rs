@@ -0,0 +1,301 @@
+use std::time::Duration;
+
+/// A way of asking the OS how long the user has been idle.
+pub trait IdleSource: Send {
+    fn name(&self) -> &'static str;
+
+    /// Time since the last keyboard/mouse input, or `None` if the source stopped working.
+    fn idle_time(&mut self) -> Option<Duration>;
+}
+
+/// Reports how long the user has been away from the keyboard and mouse.
+///
+/// Backends are tried in order and the first one that answers is kept:
+/// - Windows: `GetLastInputInfo`
+/// - Linux/Wayland: the `ext-idle-notify-v1` protocol
+/// - Linux/X11: the XScreenSaver extension
+/// - Linux fallback: logind's `IdleHint` / `IdleSinceHint` session properties
+pub struct IdleMonitor {
+    source: Option<Box<dyn IdleSource>>,
+}
+
+impl IdleMonitor {
+    pub fn detect() -> Self {
+        for connect in candidate_sources() {
+            let Some(mut source) = connect() else { continue };
+            if source.idle_time().is_some() {
+                println!("⌨️ Idle detection using {}", source.name());
+                return Self { source: Some(source) };
+            }
+        }
+        println!("ℹ️ No idle detection backend available");
+        Self { source: None }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.source.is_some()
+    }
+
+    pub fn idle_time(&mut self) -> Option<Duration> {
+        self.source.as_mut()?.idle_time()
+    }
+}
+
+/// Connects to one backend, or `None` if it isn't available here.
+type Connect = fn() -> Option<Box<dyn IdleSource>>;
+
+/// The backends to try, in order. Each one only connects when its turn comes,
+/// so a working earlier backend leaves the later ones untouched.
+fn candidate_sources() -> Vec<Connect> {
+    #[allow(unused_mut)]
+    let mut sources: Vec<Connect> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    sources.push(|| Some(Box::new(windows_idle::LastInputSource)));
+
+    #[cfg(target_os = "linux")]
+    {
+        // Under Wayland, XScreenSaver (via XWayland) only sees X11 clients' input,
+        // so the Wayland protocol goes first.
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            sources.push(|| Some(Box::new(wayland_idle::WaylandIdleSource::connect()?)));
+        }
+        if std::env::var_os("DISPLAY").is_some() {
+            sources.push(|| Some(Box::new(x11_idle::XScreenSaverSource::connect()?)));
+        }
+        sources.push(|| Some(Box::new(logind_idle::LogindIdleSource::connect()?)));
+    }
+
+    sources
+}
+
+#[cfg(target_os = "windows")]
+mod windows_idle {
+    use super::IdleSource;
+    use std::time::Duration;
+
+    pub struct LastInputSource;
+
+    impl IdleSource for LastInputSource {
+        fn name(&self) -> &'static str {
+            "GetLastInputInfo"
+        }
+
+        fn idle_time(&mut self) -> Option<Duration> {
+            use winapi::um::sysinfoapi::GetTickCount;
+            use winapi::um::winuser::{GetLastInputInfo, LASTINPUTINFO};
+
+            let mut info = LASTINPUTINFO {
+                cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+            unsafe {
+                if GetLastInputInfo(&mut info) == 0 {
+                    return None;
+                }
+                // Both are 32-bit tick counts; wrapping_sub handles the 49-day rollover
+                let idle_ms = GetTickCount().wrapping_sub(info.dwTime);
+                Some(Duration::from_millis(idle_ms as u64))
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11_idle {
+    use super::IdleSource;
+    use std::time::Duration;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::screensaver::ConnectionExt as _;
+    use x11rb::protocol::xproto::Window;
+    use x11rb::rust_connection::RustConnection;
+
+    pub struct XScreenSaverSource {
+        conn: RustConnection,
+        root: Window,
+    }
+
+    impl XScreenSaverSource {
+        pub fn connect() -> Option<Self> {
+            let (conn, screen_num) = x11rb::connect(None).ok()?;
+            let root = conn.setup().roots.get(screen_num)?.root;
+            Some(Self { conn, root })
+        }
+    }
+
+    impl IdleSource for XScreenSaverSource {
+        fn name(&self) -> &'static str {
+            "XScreenSaver"
+        }
+
+        fn idle_time(&mut self) -> Option<Duration> {
+            let info = self.conn.screensaver_query_info(self.root).ok()?.reply().ok()?;
+            Some(Duration::from_millis(info.ms_since_user_input as u64))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod wayland_idle {
+    use super::IdleSource;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use wayland_client::protocol::{wl_registry, wl_seat};
+    use wayland_client::{delegate_noop, Connection, Dispatch, QueueHandle};
+    use wayland_protocols::ext::idle_notify::v1::client::{
+        ext_idle_notification_v1, ext_idle_notifier_v1,
+    };
+
+    /// The compositor reports "idled" once no input arrived for this long, so idle
+    /// times below it read as zero.
+    const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[derive(Default)]
+    struct State {
+        seat: Option<wl_seat::WlSeat>,
+        notifier: Option<ext_idle_notifier_v1::ExtIdleNotifierV1>,
+        idle_since: Arc<Mutex<Option<Instant>>>,
+    }
+
+    impl Dispatch<wl_registry::WlRegistry, ()> for State {
+        fn event(
+            state: &mut Self,
+            registry: &wl_registry::WlRegistry,
+            event: wl_registry::Event,
+            _: &(),
+            _: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_registry::Event::Global { name, interface, .. } = event {
+                match interface.as_str() {
+                    "wl_seat" if state.seat.is_none() => {
+                        state.seat = Some(registry.bind(name, 1, qh, ()));
+                    }
+                    "ext_idle_notifier_v1" => {
+                        state.notifier = Some(registry.bind(name, 1, qh, ()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    impl Dispatch<ext_idle_notification_v1::ExtIdleNotificationV1, ()> for State {
+        fn event(
+            state: &mut Self,
+            _: &ext_idle_notification_v1::ExtIdleNotificationV1,
+            event: ext_idle_notification_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            let mut idle_since = match state.idle_since.lock() {
+                Ok(guard) => guard,
+                Err(_) => return,
+            };
+            match event {
+                ext_idle_notification_v1::Event::Idled => {
+                    *idle_since = Instant::now().checked_sub(NOTIFY_TIMEOUT);
+                }
+                ext_idle_notification_v1::Event::Resumed => *idle_since = None,
+                _ => {}
+            }
+        }
+    }
+
+    delegate_noop!(State: ignore wl_seat::WlSeat);
+    delegate_noop!(State: ext_idle_notifier_v1::ExtIdleNotifierV1);
+
+    pub struct WaylandIdleSource {
+        idle_since: Arc<Mutex<Option<Instant>>>,
+        alive: Arc<Mutex<bool>>,
+    }
+
+    impl WaylandIdleSource {
+        /// Connect to the compositor and subscribe to idle notifications.
+        /// Returns `None` if the compositor lacks `ext-idle-notify-v1`.
+        pub fn connect() -> Option<Self> {
+            let conn = Connection::connect_to_env().ok()?;
+            let mut queue = conn.new_event_queue();
+            let qh = queue.handle();
+            conn.display().get_registry(&qh, ());
+
+            let mut state = State::default();
+            queue.roundtrip(&mut state).ok()?;
+
+            let seat = state.seat.clone()?;
+            let notifier = state.notifier.clone()?;
+            notifier.get_idle_notification(NOTIFY_TIMEOUT.as_millis() as u32, &seat, &qh, ());
+
+            let idle_since = state.idle_since.clone();
+            let alive = Arc::new(Mutex::new(true));
+            let thread_alive = alive.clone();
+            std::thread::spawn(move || {
+                while queue.blocking_dispatch(&mut state).is_ok() {}
+                println!("⚠️ Wayland idle notifications stopped");
+                if let Ok(mut alive) = thread_alive.lock() {
+                    *alive = false;
+                }
+            });
+
+            Some(Self { idle_since, alive })
+        }
+    }
+
+    impl IdleSource for WaylandIdleSource {
+        fn name(&self) -> &'static str {
+            "ext-idle-notify-v1"
+        }
+
+        fn idle_time(&mut self) -> Option<Duration> {
+            if !*self.alive.lock().ok()? {
+                return None;
+            }
+            let idle_since = *self.idle_since.lock().ok()?;
+            Some(idle_since.map(|since| since.elapsed()).unwrap_or(Duration::ZERO))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod logind_idle {
+    use super::IdleSource;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use zbus::blocking::{Connection, Proxy};
+
+    /// Reads the idle hint that desktop environments publish on the logind session.
+    pub struct LogindIdleSource {
+        proxy: Proxy<'static>,
+    }
+
+    impl LogindIdleSource {
+        pub fn connect() -> Option<Self> {
+            let connection = Connection::system().ok()?;
+            let proxy = Proxy::new_owned(
+                connection,
+                "org.freedesktop.login1",
+                "/org/freedesktop/login1/session/auto",
+                "org.freedesktop.login1.Session",
+            )
+            .ok()?;
+            Some(Self { proxy })
+        }
+    }
+
+    impl IdleSource for LogindIdleSource {
+        fn name(&self) -> &'static str {
+            "logind IdleHint"
+        }
+
+        fn idle_time(&mut self) -> Option<Duration> {
+            let idle: bool = self.proxy.get_property("IdleHint").ok()?;
+            if !idle {
+                return Some(Duration::ZERO);
+            }
+            // Microseconds since the epoch (CLOCK_REALTIME)
+            let since_us: u64 = self.proxy.get_property("IdleSinceHint").ok()?;
+            let since = UNIX_EPOCH + Duration::from_micros(since_us);
+            Some(SystemTime::now().duration_since(since).unwrap_or(Duration::ZERO))
+        }
+    }
+}
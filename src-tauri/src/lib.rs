@@ -1,12 +1,12 @@
 use log::{error, info};
-use serde::{Deserialize, Serialize};
 use simplelog::{
     ColorChoice, CombinedLogger, Config, LevelFilter, TermLogger, TerminalMode, WriteLogger,
 };
 use std::fs;
 use std::fs::OpenOptions;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
@@ -16,23 +16,62 @@ use tauri_plugin_autostart::ManagerExt;
 
 // Import our window manager module
 mod window_manager;
-use window_manager::{WindowConfig, WindowManager};
+mod window_payload;
+use window_payload::WindowPayload;
+mod window_placement;
+mod window_registry;
+use window_registry::WindowInfo;
+use window_manager::{WindowConfig, WindowManager, FORCE_BREAK_GROUP};
+
+mod break_tiers;
+
+mod pomodoro;
+
+mod settings;
+use settings::AppSettings;
+
+mod timer_persistence;
+
+mod sleep_monitor;
+
+mod idle_monitor;
+
+mod screen_lock;
+
+mod window_titles;
+
+mod meeting_rules;
+use meeting_rules::MeetingRules;
+
+mod meeting_detector;
+use meeting_detector::{MeetingAssessment, RulesReport};
+
+mod media;
+use media::{media_registry, MediaSession};
+
+mod screen_activity;
+use screen_activity::ScreenActivity;
+
+mod calendar;
+use calendar::{CalDavStatus, CalendarReport};
+
+mod postpone_policy;
+use postpone_policy::PostponeRecord;
+
+mod skip_policy;
+use skip_policy::{SkipKind, SkipStatus};
+
+mod lifecycle;
+use lifecycle::LifecycleEvent;
+
+mod timer_engine;
+use timer_engine::{timer_engine, TimerConfig, TimerPhase, TimerSnapshot};
 
 /// Shared flag: was media playing when the break started?
 /// Written by main window before break, read by break windows on close.
 /// Using AtomicBool so it's safe to access from any thread/webview.
 static MEDIA_WAS_PLAYING: AtomicBool = AtomicBool::new(false);
 
-/// Was VLC playing when we sent the pause command? Set by Core Audio session check.
-static VLC_WAS_PLAYING: AtomicBool = AtomicBool::new(false);
-
-/// SMTC sources that were paused by us at break start.
-static SMTC_PAUSED_SOURCES: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
-
-fn smtc_paused_sources() -> &'static Mutex<Vec<String>> {
-    SMTC_PAUSED_SOURCES.get_or_init(|| Mutex::new(Vec::new()))
-}
-
 #[tauri::command]
 fn set_media_was_playing(was_playing: bool) {
     MEDIA_WAS_PLAYING.store(was_playing, Ordering::SeqCst);
@@ -46,10 +85,13 @@ fn get_media_was_playing() -> bool {
 #[tauri::command]
 fn clear_media_was_playing() {
     MEDIA_WAS_PLAYING.store(false, Ordering::SeqCst);
-    VLC_WAS_PLAYING.store(false, Ordering::SeqCst);
-    if let Ok(mut guard) = smtc_paused_sources().lock() {
-        guard.clear();
-    }
+    // A lowered volume must never outlive the break, even when resuming was skipped
+    std::thread::spawn(|| {
+        if let Ok(mut registry) = media_registry().lock() {
+            registry.restore_ducked();
+            registry.clear_paused();
+        }
+    });
 }
 
 /// Initialise file + terminal logging.
@@ -109,40 +151,75 @@ fn init_logging() {
 }
 
 
-#[derive(Serialize, Deserialize)]
-struct AppSettings {
-    break_minutes: u32,
-    break_seconds: u32,
-    break_duration_minutes: u32,
-    break_duration_seconds: u32,
-    break_mode: String,
-    auto_pause: bool,
-    meeting_detect: bool,
-    pre_break: bool,
-    pre_break_minutes: u32,
-    pre_break_seconds: u32,
-    break_chime: bool,
-    recurring: bool,
-    autostart: bool,
-    auto_start_timer: bool,
+#[tauri::command]
+async fn force_break_window(app_handle: tauri::AppHandle, duration: Option<u32>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || open_force_break_window(app_handle, duration))
+        .await
+        .map_err(|e| format!("Failed to open force break window: {}", e))?
 }
 
-#[tauri::command]
-fn force_break_window(app_handle: tauri::AppHandle, duration: Option<u32>) -> Result<(), String> {
+/// Cover every monitor with a force break window. Blocks until the windows are built.
+fn open_force_break_window(app_handle: tauri::AppHandle, duration: Option<u32>) -> Result<(), String> {
     let break_duration = duration.unwrap_or(300);
     println!(
         "💥 Creating force break window with duration: {} seconds",
         break_duration
     );
 
-    WindowManager::close_existing_window(&app_handle, "force_break");
-    let config = WindowConfig::force_break(break_duration);
-    WindowManager::create_window(app_handle, config)
+    WindowManager::close_group(&app_handle, FORCE_BREAK_GROUP);
+    let break_index = timer_engine::current_cycle();
+    // Closing a window by hand ends the break early, which the skip policy may refuse
+    let guarded = |config: WindowConfig| WindowConfig {
+        close_guard: Some(force_break_may_close),
+        ..config
+    };
+
+    // One window per monitor so the other screens can't be used during the break
+    let areas = WindowManager::monitor_areas(&app_handle);
+    if areas.is_empty() {
+        let config = guarded(WindowConfig::force_break(break_duration, break_index, 0, None));
+        return WindowManager::create_window(app_handle, config);
+    }
+    println!("🖥️ Covering {} monitor(s)", areas.len());
+    for (index, area) in areas.iter().enumerate() {
+        let config = guarded(WindowConfig::force_break(break_duration, break_index, index, Some(area)));
+        WindowManager::create_window(app_handle.clone(), config)?;
+    }
+    Ok(())
 }
 
+/// Windows opened by the app and their lifecycle state, for diagnostics.
 #[tauri::command]
-fn close_window(app_handle: tauri::AppHandle, label: String) -> Result<(), String> {
+fn list_windows(app_handle: tauri::AppHandle) -> Vec<WindowInfo> {
+    WindowManager::list_windows(&app_handle)
+}
+
+/// The payload the calling window was opened with.
+#[tauri::command]
+fn get_window_payload(window: tauri::WebviewWindow) -> Result<WindowPayload, String> {
+    window_payload::get(window.label())
+        .ok_or_else(|| format!("No payload for window '{}'", window.label()))
+}
+
+/// Close a window by label. `force_break` closes the force break windows on every monitor.
+#[tauri::command]
+async fn close_window(app_handle: tauri::AppHandle, label: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || close_window_now(app_handle, label))
+        .await
+        .map_err(|e| format!("Failed to close window: {}", e))?
+}
+
+/// Waits for a window still being built, so it runs off the main thread.
+fn close_window_now(app_handle: tauri::AppHandle, label: String) -> Result<(), String> {
+    if label.starts_with(FORCE_BREAK_GROUP) {
+        end_break_early(&app_handle, SkipRequest::Close(&label))?;
+        WindowManager::close_group(&app_handle, FORCE_BREAK_GROUP);
+        return Ok(());
+    }
+    let label_lock = window_registry::label_lock(&label);
+    let _serialized = label_lock.lock().unwrap_or_else(|e| e.into_inner());
     if let Some(window) = app_handle.get_webview_window(&label) {
+        window_registry::mark_closing(&label);
         window
             .close()
             .map_err(|e| format!("Failed to close window {}: {}", label, e))?;
@@ -151,31 +228,46 @@ fn close_window(app_handle: tauri::AppHandle, label: String) -> Result<(), Strin
 }
 
 #[tauri::command]
-fn notify_window(app_handle: tauri::AppHandle, duration: Option<u32>) -> Result<(), String> {
+async fn notify_window(app_handle: tauri::AppHandle, duration: Option<u32>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || open_notify_window(app_handle, duration))
+        .await
+        .map_err(|e| format!("Failed to open notify window: {}", e))?
+}
+
+fn open_notify_window(app_handle: tauri::AppHandle, duration: Option<u32>) -> Result<(), String> {
     let break_duration = duration.unwrap_or(600);
     println!(
         "🔔 Creating notify window with duration: {} seconds",
         break_duration
     );
 
-    WindowManager::close_existing_window(&app_handle, "notify");
-    let config = WindowConfig::notify(&app_handle, break_duration);
+    let config = WindowConfig::notify(&app_handle, break_duration, timer_engine::current_cycle());
     WindowManager::create_window(app_handle, config)
 }
 
 #[tauri::command]
-fn pre_break_notification_window(
+async fn pre_break_notification_window(
     app_handle: tauri::AppHandle,
     remaining_seconds: Option<u32>,
 ) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || open_pre_break_window(app_handle, remaining_seconds))
+        .await
+        .map_err(|e| format!("Failed to open pre-break window: {}", e))?
+}
+
+fn open_pre_break_window(app_handle: tauri::AppHandle, remaining_seconds: Option<u32>) -> Result<(), String> {
     let seconds = remaining_seconds.unwrap_or(30);
     println!(
         "⏰ Creating pre-break window with {} seconds remaining...",
         seconds
     );
 
-    WindowManager::close_existing_window(&app_handle, "pre_break");
-    let config = WindowConfig::pre_break(&app_handle, seconds);
+    let (force_next, snooze) = timer_engine()
+        .lock()
+        .map(|engine| (engine.snapshot(Instant::now()).break_mode == "force", engine.snooze_offer()))
+        .unwrap_or((false, None));
+    let skips = force_next.then(skip_policy::status);
+    let config = WindowConfig::pre_break(&app_handle, seconds, skips, snooze);
     WindowManager::create_window(app_handle, config)
 }
 
@@ -274,7 +366,7 @@ async fn open_url(url: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-fn show_update_notification(
+async fn show_update_notification(
     app_handle: tauri::AppHandle,
     version: String,
     notes: String,
@@ -283,15 +375,24 @@ fn show_update_notification(
 ) -> Result<(), String> {
     println!("🔔 Showing update notification for version: {}", version);
 
-    WindowManager::close_existing_window(&app_handle, "update_notification");
-    let config =
-        WindowConfig::update_notification(&app_handle, version, notes, download_url, published_at);
-    WindowManager::create_window(app_handle, config)
+    tauri::async_runtime::spawn_blocking(move || {
+        let config =
+            WindowConfig::update_notification(&app_handle, version, notes, download_url, published_at);
+        WindowManager::create_window(app_handle, config)
+    })
+    .await
+    .map_err(|e| format!("Failed to open update notification: {}", e))?
 }
 
 
 #[tauri::command]
-fn lock_screen() -> Result<(), String> {
+async fn lock_screen() -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(lock_screen_now)
+        .await
+        .map_err(|e| format!("Failed to lock screen: {}", e))?
+}
+
+fn lock_screen_now() -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
         use std::process::Command;
@@ -301,88 +402,30 @@ fn lock_screen() -> Result<(), String> {
             .map_err(|e| format!("Failed to lock screen: {}", e))?;
         Ok(())
     }
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "linux")]
     {
-        Err("Lock screen only supported on Windows".to_string())
+        screen_lock::lock()
     }
-}
-
-/// Check if VLC is currently playing by inspecting its Windows Core Audio session state.
-/// A playing VLC has an Active audio session; a paused VLC has an Inactive one.
-/// Returns false if VLC is not running or has no audio session.
-#[cfg(target_os = "windows")]
-fn is_vlc_playing_via_audio(vlc_pid: u32) -> bool {
-    use windows::Win32::Media::Audio::{
-        eMultimedia, eRender, IMMDeviceEnumerator, MMDeviceEnumerator,
-        IAudioSessionControl2, IAudioSessionManager2,
-        AudioSessionStateActive,
-    };
-    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
-    use windows::core::ComInterface;
-
-    unsafe {
-        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
-
-        let enumerator: IMMDeviceEnumerator =
-            match CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL) {
-                Ok(e) => e,
-                Err(e) => { println!("⚠️ CoCreateInstance failed: {:?}", e); return false; }
-            };
-
-        let device = match enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia) {
-            Ok(d) => d,
-            Err(e) => { println!("⚠️ GetDefaultAudioEndpoint failed: {:?}", e); return false; }
-        };
-
-        // IMMDevice::Activate is generic in windows 0.52 — type inferred from return type
-        let session_manager: IAudioSessionManager2 =
-            match device.Activate(CLSCTX_ALL, None) {
-                Ok(m) => m,
-                Err(e) => { println!("⚠️ Activate IAudioSessionManager2 failed: {:?}", e); return false; }
-            };
-
-        let session_enum = match session_manager.GetSessionEnumerator() {
-            Ok(e) => e,
-            Err(e) => { println!("⚠️ GetSessionEnumerator failed: {:?}", e); return false; }
-        };
-
-        let count = match session_enum.GetCount() {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
-
-        for i in 0..count {
-            let session = match session_enum.GetSession(i) {
-                Ok(s) => s,
-                Err(_) => continue,
-            };
-            let session2: IAudioSessionControl2 = match session.cast() {
-                Ok(s) => s,
-                Err(_) => continue,
-            };
-            let pid = match session2.GetProcessId() {
-                Ok(p) => p,
-                Err(_) => continue,
-            };
-            if pid == vlc_pid {
-                let state = match session2.GetState() {
-                    Ok(s) => s,
-                    Err(_) => continue,
-                };
-                let is_active = state == AudioSessionStateActive;
-                println!("  VLC audio session state: {:?} → playing={}", state, is_active);
-                return is_active;
-            }
-        }
-        println!("  No audio session found for VLC PID {} (no audio or muted)", vlc_pid);
-        false
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Err("Lock screen only supported on Windows and Linux".to_string())
     }
 }
 
 /// Detects the lock screen by checking if LogonUI.exe is running — Windows always
 /// launches this process when the workstation is locked, regardless of desktop access.
+/// On Linux, reads the logind session's `LockedHint`.
 #[tauri::command]
-fn is_screen_locked() -> bool {
+async fn is_screen_locked() -> bool {
+    tauri::async_runtime::spawn_blocking(is_screen_locked_now)
+        .await
+        .unwrap_or_else(|e| {
+            println!("⚠️ Screen lock check failed: {}", e);
+            false
+        })
+}
+
+fn is_screen_locked_now() -> bool {
     #[cfg(target_os = "windows")]
     {
         use sysinfo::System;
@@ -397,249 +440,87 @@ fn is_screen_locked() -> bool {
         }
         locked
     }
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "linux")]
+    {
+        let locked = screen_lock::is_locked().unwrap_or(false);
+        println!("{} Screen locked (logind LockedHint): {}", if locked { "🔒" } else { "🔓" }, locked);
+        locked
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     false
 }
 
+/// Pause ("pause"), resume ("play") or toggle any other action on every media
+/// backend. Only sessions paused by a previous "pause" are resumed.
+/// "duck" lowers per-application volume instead; "unduck" (and "play") restore it.
 #[tauri::command]
 async fn control_media(action: String) -> Result<(), String> {
     println!("🎵 Media control requested: {}", action);
 
-    #[cfg(target_os = "windows")]
-    {
-        // ── VLC: state-aware WM_APPCOMMAND ──────────────────────────────────────
-        // VLC never registers with SMTC. We check its actual playing state first
-        // so we only toggle it when needed (pause only if playing, play only if paused).
-        {
-            use winapi::um::winuser::{
-                EnumWindows, GetWindowThreadProcessId, IsWindowVisible,
-                SendMessageTimeoutW,
-                WM_APPCOMMAND, SMTO_ABORTIFHUNG,
-            };
-            use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
-            use winapi::shared::windef::HWND;
-            use sysinfo::System;
-
-            let sys = System::new_all();
-            let vlc_pid: Option<u32> = sys.processes().values()
-                .find(|p| p.name().to_lowercase() == "vlc.exe")
-                .map(|p| p.pid().as_u32());
-
-            if let Some(pid) = vlc_pid {
-                struct SearchData { pid: u32, hwnd: HWND }
-                let mut data = SearchData { pid, hwnd: std::ptr::null_mut() };
-
-                unsafe extern "system" fn find_vlc_visible_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
-                    let data = &mut *(lparam as *mut SearchData);
-                    let mut wpid: u32 = 0;
-                    GetWindowThreadProcessId(hwnd, &mut wpid);
-                    if wpid == data.pid && IsWindowVisible(hwnd) != 0 {
-                        data.hwnd = hwnd;
-                        return 0;
-                    }
-                    TRUE
-                }
-                unsafe { EnumWindows(Some(find_vlc_visible_window), &mut data as *mut SearchData as LPARAM); }
-
-                if !data.hwnd.is_null() {
-                    // Use explicit PAUSE/PLAY commands — but only act if VLC is in
-                    // the expected state, detected via its Core Audio session.
-                    // Active audio session = playing; Inactive = paused.
-                    use winapi::um::winuser::{
-                        APPCOMMAND_MEDIA_PAUSE, APPCOMMAND_MEDIA_PLAY,
-                        APPCOMMAND_MEDIA_PLAY_PAUSE,
-                    };
-
-                    let should_send = match action.as_str() {
-                        "pause" => {
-                            let playing = is_vlc_playing_via_audio(pid);
-                            VLC_WAS_PLAYING.store(playing, Ordering::SeqCst);
-                            println!("  VLC audio state → playing={}, will_pause={}", playing, playing);
-                            playing
-                        }
-                        "play" => {
-                            let was = VLC_WAS_PLAYING.load(Ordering::SeqCst);
-                            println!("  VLC was_playing={}, will_resume={}", was, was);
-                            was
-                        }
-                        _ => true,
-                    };
-
-                    if should_send {
-                        let appcommand: i16 = match action.as_str() {
-                            "pause" => APPCOMMAND_MEDIA_PAUSE,
-                            "play"  => APPCOMMAND_MEDIA_PLAY,
-                            _       => APPCOMMAND_MEDIA_PLAY_PAUSE,
-                        };
-                        let lparam_val = ((appcommand as isize) << 16) as isize;
-                        let mut result: usize = 0;
-                        let ret = unsafe {
-                            SendMessageTimeoutW(
-                                data.hwnd, WM_APPCOMMAND, data.hwnd as usize,
-                                lparam_val, SMTO_ABORTIFHUNG, 1000, &mut result,
-                            )
-                        };
-                        println!("✅ WM_APPCOMMAND {} sent to VLC (ret={} result={})", action, ret, result);
-                    } else {
-                        println!("⏭️ Skipping VLC WM_APPCOMMAND for action '{}' (wrong state)", action);
-                    }
-                } else {
-                    println!("⚠️ VLC running but no visible window found");
-                }
-            } else {
-                println!("ℹ️ VLC not running");
-            }
-        }
-
-        // ── SMTC: track which sessions we pause, only resume those ──────────────
-        // On pause: record which sources were Playing → pause them → store their IDs.
-        // On play:  only resume the sources we recorded, not all paused sessions.
-        let smtc_action = action.clone();
-        let smtc_thread = std::thread::spawn(move || {
-            use windows::Media::Control::{
-                GlobalSystemMediaTransportControlsSessionManager,
-                GlobalSystemMediaTransportControlsSessionPlaybackStatus,
-            };
-
-            let manager = match GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
-                .and_then(|op| op.get())
-            {
-                Ok(m) => m,
-                Err(e) => { println!("⚠️ SMTC manager failed: {:?}", e); return; }
-            };
-
-            let sessions_view = match manager.GetSessions() {
-                Ok(s) => s,
-                Err(e) => { println!("⚠️ GetSessions failed: {:?}", e); return; }
-            };
-            let count = sessions_view.Size().unwrap_or(0);
-            println!("🎵 SMTC sessions: {}", count);
-
-            match smtc_action.as_str() {
-                "pause" => {
-                    // Pause only currently-playing sessions and record their IDs
-                    let mut paused_sources: Vec<String> = Vec::new();
-                    for i in 0..count {
-                        let session = match sessions_view.GetAt(i) { Ok(s) => s, Err(_) => continue };
-                        let source = session.SourceAppUserModelId()
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| format!("session_{}", i));
-                        let is_playing = session.GetPlaybackInfo()
-                            .and_then(|info| info.PlaybackStatus())
-                            .map(|st| st == GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing)
-                            .unwrap_or(false);
-
-                        if is_playing {
-                            println!("  ⏸ Pausing SMTC: {}", source);
-                            let _ = session.TryPauseAsync().and_then(|op| op.get());
-                            paused_sources.push(source);
-                        } else {
-                            println!("  ⏭️ Skipped (not playing): {}", source);
-                        }
-                    }
-                    // Store the list of sources we paused
-                    if let Ok(mut guard) = smtc_paused_sources().lock() {
-                        *guard = paused_sources.clone();
-                        println!("💾 Stored {} paused SMTC sources: {:?}", paused_sources.len(), paused_sources);
-                    }
-                }
-                "play" => {
-                    // Resume only the sessions we previously paused
-                    let paused_sources = if let Ok(guard) = smtc_paused_sources().lock() {
-                        guard.clone()
-                    } else {
-                        Vec::new()
-                    };
-                    println!("  Resuming {} recorded SMTC sources: {:?}", paused_sources.len(), paused_sources);
-
-                    for i in 0..count {
-                        let session = match sessions_view.GetAt(i) { Ok(s) => s, Err(_) => continue };
-                        let source = session.SourceAppUserModelId()
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| format!("session_{}", i));
-
-                        if paused_sources.contains(&source) {
-                            println!("  ▶ Resuming SMTC: {}", source);
-                            let _ = session.TryPlayAsync().and_then(|op| op.get());
-                        } else {
-                            println!("  ⏭️ Not our session, skipping: {}", source);
-                        }
-                    }
-                }
-                _ => {
-                    // playpause toggle: act on all playing sessions
-                    for i in 0..count {
-                        let session = match sessions_view.GetAt(i) { Ok(s) => s, Err(_) => continue };
-                        let _ = session.TryTogglePlayPauseAsync().and_then(|op| op.get());
-                    }
-                }
-            }
-        });
-
-        let _ = smtc_thread.join();
-        return Ok(());
-    }
-
-    // Non-Windows fallback
+    let backend_action = action.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        media_registry()
+            .lock()
+            .map_err(|e| format!("Media registry lock poisoned: {}", e))?
+            .control(&backend_action)
+    })
+    .await
+    .map_err(|e| format!("Media control task failed: {}", e))?;
+
+    // Without any backend, a toggle is still worth a media key (not needed on
+    // Windows, where SMTC is always there). A key can't tell pause from play,
+    // so those report the error instead of maybe starting playback
     #[cfg(not(target_os = "windows"))]
-    {
+    if let (Err(e), false) = (&result, matches!(action.as_str(), "pause" | "play" | "duck" | "unduck")) {
+        println!("⚠️ {}, falling back to media key", e);
         use enigo::{Enigo, Key, KeyboardControllable};
         let mut enigo = Enigo::new();
         enigo.key_click(Key::MediaPlayPause);
-        Ok(())
+        return Ok(());
     }
+
+    result
 }
 
-/// Returns true if ANY SMTC session is currently Playing.
-/// Falls back to false (assume not playing) if SMTC is unavailable.
+/// Media players currently visible to any backend, plus ones seen earlier in
+/// this run. Used by the settings UI to build the auto-pause allow/deny lists.
 #[tauri::command]
-async fn is_media_playing() -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        use windows::Media::Control::{
-            GlobalSystemMediaTransportControlsSessionManager,
-            GlobalSystemMediaTransportControlsSessionPlaybackStatus,
-        };
+async fn list_media_sessions() -> Result<Vec<MediaSession>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        media_registry()
+            .lock()
+            .map(|mut registry| registry.known_sessions())
+            .map_err(|e| format!("Media registry lock poisoned: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Media session task failed: {}", e))?
+}
 
-        let result = async {
-            let manager =
-                GlobalSystemMediaTransportControlsSessionManager::RequestAsync()?.await?;
-            let sessions = manager.GetSessions()?;
-            let count = sessions.Size().unwrap_or(0);
-            for i in 0..count {
-                let session = match sessions.GetAt(i) {
-                    Ok(s) => s,
-                    Err(_) => continue,
-                };
-                let is_playing = session
-                    .GetPlaybackInfo()
-                    .and_then(|info| info.PlaybackStatus())
-                    .map(|st| {
-                        st == GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing
-                    })
-                    .unwrap_or(false);
-                if is_playing {
-                    return Ok::<bool, windows::core::Error>(true);
-                }
-            }
-            Ok::<bool, windows::core::Error>(false)
+/// Returns true if any media backend (SMTC/VLC on Windows, MPRIS on Linux) has
+/// a playing session. Falls back to false if no backend is available.
+#[tauri::command]
+async fn is_media_playing() -> bool {
+    let result = tauri::async_runtime::spawn_blocking(|| {
+        media_registry()
+            .lock()
+            .map_err(|e| format!("Media registry lock poisoned: {}", e))?
+            .is_any_playing()
+    })
+    .await;
+    match result {
+        Ok(Ok(playing)) => {
+            println!("🎵 Media playing state: {}", playing);
+            playing
         }
-        .await;
-
-        match result {
-            Ok(playing) => {
-                println!("🎵 Media playing state: {}", playing);
-                return playing;
-            }
-            Err(e) => {
-                println!("⚠️ Could not read SMTC playback state: {:?}", e);
-                return false;
-            }
+        Ok(Err(e)) => {
+            println!("⚠️ Could not read media playback state: {}", e);
+            false
+        }
+        Err(e) => {
+            println!("⚠️ Media state task failed: {}", e);
+            false
         }
     }
-
-    #[cfg(not(target_os = "windows"))]
-    false
 }
 
 #[tauri::command]
@@ -691,217 +572,194 @@ fn play_chime() -> Result<(), String> {
     }
 }
 
+/// True if the meeting detector's confidence reaches `MEETING_THRESHOLD`.
 #[tauri::command]
-fn is_meeting_active() -> Result<bool, String> {
-    use sysinfo::System;
-
-    // Check for desktop meeting applications
-    let meeting_processes = [
-        "zoom.exe",
-        "teams.exe",
-        "skype.exe",
-        "webex.exe",
-        "meet.exe",
-    ];
-
-    let sys = System::new_all();
-    let desktop_meeting_found = sys.processes().values().any(|proc| {
-        let name = proc.name().to_lowercase();
-        meeting_processes.iter().any(|mp| name.contains(mp))
-    });
-
-    if desktop_meeting_found {
-        return Ok(true);
-    }
-
-    // Check for browser-based meetings (Windows only)
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(browser_meeting_found) = check_browser_meetings() {
-            return Ok(browser_meeting_found);
-        }
-    }
-
-    Ok(false)
-}
-
-#[cfg(target_os = "windows")]
-fn check_browser_meetings() -> Result<bool, String> {
-    use std::ffi::OsString;
-    use std::os::windows::ffi::OsStringExt;
-    use winapi::shared::minwindef::{BOOL, FALSE, LPARAM, TRUE};
-    use winapi::shared::windef::HWND;
-    use winapi::um::winuser::{EnumWindows, GetWindowTextW, IsWindowVisible};
-
-    // Meeting indicators to look for in browser window titles
-    let meeting_indicators = [
-        "google meet",
-        "meet.google.com",
-        "zoom meeting",
-        "microsoft teams",
-        "teams.microsoft.com",
-        "webex meeting",
-        "webex.com",
-        "gotomeeting",
-        "join.me",
-        "bluejeans",
-        "whereby.com",
-        "discord",
-        "slack call",
-        "skype",
-        "hangouts",
-        "jitsi meet",
-        "bigbluebutton",
-        "8x8.vc",
-        "ringcentral meetings",
-        "cisco webex",
-        "amazon chime",
-        "facebook messenger rooms",
-        "whatsapp web",
-    ];
-
-    // Browser process names to check
-    let browser_processes = [
-        "chrome.exe",
-        "firefox.exe",
-        "msedge.exe",
-        "opera.exe",
-        "brave.exe",
-        "vivaldi.exe",
-        "iexplore.exe",
-    ];
-
-    // First check if any browsers are running
-    let sys = sysinfo::System::new_all();
-    let browser_running = sys.processes().values().any(|proc| {
-        let name = proc.name().to_lowercase();
-        browser_processes.iter().any(|bp| name.contains(bp))
-    });
+async fn is_meeting_active() -> Result<bool, String> {
+    Ok(detect_meeting().await?.is_meeting())
+}
 
-    if !browser_running {
-        return Ok(false);
-    }
+/// Every meeting signal found (meeting apps, browser titles, camera and
+/// microphone use) with the combined confidence score.
+#[tauri::command]
+async fn detect_meeting() -> Result<MeetingAssessment, String> {
+    tauri::async_runtime::spawn_blocking(meeting_detector::detect)
+        .await
+        .map_err(|e| format!("Failed to detect meeting: {}", e))
+}
 
-    // Structure to pass data to the callback
-    struct CallbackData {
-        meeting_indicators: Vec<String>,
-        found_meeting: bool,
-    }
+/// Look for a fullscreen window and a presenting or recording app right now.
+#[tauri::command]
+async fn detect_screen_activity() -> Result<ScreenActivity, String> {
+    tauri::async_runtime::spawn_blocking(|| screen_activity::detect(true, true))
+        .await
+        .map_err(|e| format!("Failed to detect screen activity: {}", e))
+}
 
-    let mut callback_data = CallbackData {
-        meeting_indicators: meeting_indicators
-            .iter()
-            .map(|s| s.to_lowercase())
-            .collect(),
-        found_meeting: false,
+/// Run the meeting rules against the current processes and windows and report
+/// what matched. `rules` tests a draft instead of `meeting_rules.json`.
+#[tauri::command]
+async fn test_meeting_rules(rules: Option<MeetingRules>) -> Result<RulesReport, String> {
+    let (compiled, error) = match rules {
+        Some(draft) => (Arc::new(draft.compile()?), None),
+        None => (meeting_rules::current(), meeting_rules::last_error()),
     };
+    tauri::async_runtime::spawn_blocking(move || meeting_detector::test_rules(&compiled, error))
+        .await
+        .map_err(|e| format!("Failed to test meeting rules: {}", e))
+}
 
-    // Callback function for EnumWindows
-    unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
-        let callback_data = &mut *(lparam as *mut CallbackData);
-
-        // Only check visible windows
-        if IsWindowVisible(hwnd) == 0 {
-            return TRUE;
-        }
-
-        // Get window title
-        let mut title: [u16; 512] = [0; 512];
-        let title_len = GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32);
-
-        if title_len > 0 {
-            let title_os_string = OsString::from_wide(&title[..title_len as usize]);
-            if let Ok(title_string) = title_os_string.into_string() {
-                let title_lower = title_string.to_lowercase();
-
-                // Check if the window title contains any meeting indicators
-                for indicator in &callback_data.meeting_indicators {
-                    if title_lower.contains(indicator) {
-                        println!("🔍 Meeting detected in browser window: {}", title_string);
-                        callback_data.found_meeting = true;
-                        return FALSE; // Stop enumeration
-                    }
-                }
-            }
-        }
-
-        TRUE // Continue enumeration
-    }
+#[tauri::command]
+async fn check_browser_meeting_debug() -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(browser_meeting_report)
+        .await
+        .map_err(|e| format!("Failed to check browser meetings: {}", e))?
+}
 
-    // Enumerate all windows
-    unsafe {
-        EnumWindows(
-            Some(enum_windows_proc),
-            &mut callback_data as *mut CallbackData as LPARAM,
-        );
+fn browser_meeting_report() -> Result<String, String> {
+    let backend = window_titles::backend_name()
+        .ok_or("No window title backend available on this desktop")?;
+    let rules = meeting_rules::current();
+    let titles = window_titles::visible_window_titles();
+    let found = titles
+        .iter()
+        .find_map(|title| rules.title_rule(title).map(|rule| (title.clone(), rule.source.clone())));
+    match found {
+        Some((title, rule)) => Ok(format!(
+            "Browser meeting detected via {}: {} (rule: {})",
+            backend, title, rule
+        )),
+        None => Ok(format!(
+            "No browser meeting detected via {} ({} windows checked)",
+            backend,
+            titles.len()
+        )),
     }
-
-    Ok(callback_data.found_meeting)
 }
 
 #[tauri::command]
-fn check_browser_meeting_debug() -> Result<String, String> {
-    #[cfg(target_os = "windows")]
-    {
-        match check_browser_meetings() {
-            Ok(found) => {
-                if found {
-                    Ok("Browser meeting detected".to_string())
-                } else {
-                    Ok("No browser meeting detected".to_string())
-                }
-            }
-            Err(e) => Ok(format!("Error checking browser meetings: {}", e)),
-        }
+fn save_settings(app_handle: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
+    settings::save(&app_handle, &settings)?;
+    media::apply_settings(&settings);
+    calendar::apply_settings(&settings);
+    skip_policy::apply_settings(&settings);
+
+    // A running cycle picks the new values up at its next phase change
+    if let Ok(mut engine) = timer_engine().lock() {
+        engine.update_config(TimerConfig::from(&settings));
     }
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        Ok("Browser meeting detection only supported on Windows".to_string())
-    }
+    Ok(())
 }
 
 #[tauri::command]
-fn save_settings(app_handle: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+fn load_settings(app_handle: tauri::AppHandle) -> Result<Option<AppSettings>, String> {
+    settings::load(&app_handle)
+}
 
-    // Create the directory if it doesn't exist
-    fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+/// Start a work cycle on the Rust-side timer engine.
+/// `seconds` overrides the saved break interval for the first cycle.
+#[tauri::command]
+fn start_timer(app_handle: tauri::AppHandle, seconds: Option<u32>) -> Result<TimerSnapshot, String> {
+    let settings = settings::load_or_default(&app_handle);
+    let snapshot = {
+        let mut engine = timer_engine()
+            .lock()
+            .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+        let now = Instant::now();
+        engine.start(now, TimerConfig::from(&settings), seconds)?;
+        engine.snapshot(now)
+    };
+    println!("🚀 Timer started: {} seconds until break", snapshot.remaining_seconds);
+    timer_engine::emit_state(&app_handle);
+    Ok(snapshot)
+}
 
-    let settings_path = app_data_dir.join("settings.json");
-    let settings_json = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+#[tauri::command]
+fn pause_timer(app_handle: tauri::AppHandle) -> Result<TimerSnapshot, String> {
+    let snapshot = {
+        let mut engine = timer_engine()
+            .lock()
+            .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+        let now = Instant::now();
+        engine.pause(now);
+        engine.snapshot(now)
+    };
+    println!("⏸️ Timer paused");
+    timer_engine::emit_state(&app_handle);
+    Ok(snapshot)
+}
 
-    fs::write(settings_path, settings_json)
-        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+#[tauri::command]
+fn resume_timer(app_handle: tauri::AppHandle) -> Result<TimerSnapshot, String> {
+    let snapshot = {
+        let mut engine = timer_engine()
+            .lock()
+            .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+        let now = Instant::now();
+        engine.resume(now);
+        engine.snapshot(now)
+    };
+    println!("▶️ Timer resumed");
+    timer_engine::emit_state(&app_handle);
+    Ok(snapshot)
+}
 
-    Ok(())
+#[tauri::command]
+fn stop_timer(app_handle: tauri::AppHandle) -> Result<TimerSnapshot, String> {
+    let snapshot = {
+        let mut engine = timer_engine()
+            .lock()
+            .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+        engine.stop();
+        engine.snapshot(Instant::now())
+    };
+    println!("⏹️ Timer stopped");
+    timer_engine::emit_state(&app_handle);
+    Ok(snapshot)
 }
 
 #[tauri::command]
-fn load_settings(app_handle: tauri::AppHandle) -> Result<Option<AppSettings>, String> {
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+fn get_timer_state() -> Result<TimerSnapshot, String> {
+    let engine = timer_engine()
+        .lock()
+        .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+    Ok(engine.snapshot(Instant::now()))
+}
 
-    let settings_path = app_data_dir.join("settings.json");
+/// Calendar files in use and the busy blocks of the next `hours` (default 24).
+#[tauri::command]
+async fn get_calendar_busy(hours: Option<u32>) -> Result<CalendarReport, String> {
+    tauri::async_runtime::spawn_blocking(move || calendar::report(hours.unwrap_or(24)))
+        .await
+        .map_err(|e| format!("Calendar check failed: {}", e))
+}
 
-    if !settings_path.exists() {
-        return Ok(None);
-    }
+/// Fetch the CalDAV calendars now instead of waiting for the next background sync.
+#[tauri::command]
+async fn sync_caldav() -> Result<CalDavStatus, String> {
+    tauri::async_runtime::spawn_blocking(calendar::sync_caldav)
+        .await
+        .map_err(|e| format!("CalDAV sync failed: {}", e))?
+}
 
-    let settings_json = fs::read_to_string(settings_path)
-        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+/// Store the CalDAV password for the configured server and user name; empty forgets it.
+#[tauri::command]
+fn set_caldav_password(password: String) -> Result<(), String> {
+    calendar::set_caldav_password(&password)
+}
 
-    let settings: AppSettings = serde_json::from_str(&settings_json)
-        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+#[tauri::command]
+fn has_caldav_password() -> bool {
+    calendar::has_caldav_password()
+}
 
-    Ok(Some(settings))
+/// Recent meeting postponements with their reasons, oldest first.
+#[tauri::command]
+fn get_postponements() -> Result<Vec<PostponeRecord>, String> {
+    let engine = timer_engine()
+        .lock()
+        .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+    Ok(engine.postponements())
 }
 
 #[tauri::command]
@@ -970,64 +828,146 @@ async fn get_primary_monitor_size(app_handle: tauri::AppHandle) -> Result<(u32,
 }
 
 #[tauri::command]
-fn meeting_detected_notification(app_handle: tauri::AppHandle) -> Result<(), String> {
+async fn meeting_detected_notification(app_handle: tauri::AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || open_meeting_notification(app_handle))
+        .await
+        .map_err(|e| format!("Failed to open meeting notification: {}", e))?
+}
+
+fn open_meeting_notification(app_handle: tauri::AppHandle) -> Result<(), String> {
     println!("🤝 Creating meeting detected notification window...");
 
-    WindowManager::close_existing_window(&app_handle, "meeting_notification");
     let config = WindowConfig::meeting_notification(&app_handle);
     WindowManager::create_window(app_handle, config)
 }
 
-#[tauri::command]
-fn break_ended_early(app_handle: tauri::AppHandle) -> Result<(), String> {
-    println!("🏃 Break ended early - user returned");
+/// How an early return reached the backend.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SkipRequest<'a> {
+    /// The Early Return button or the notify window.
+    Button,
+    /// The force break window with this label being closed, by the window
+    /// manager or by itself.
+    Close(&'a str),
+}
 
-    // Try to notify the main window about early return
-    if let Some(main_window) = app_handle.get_webview_window("main") {
-        println!("📱 Found main window, calling handleEarlyBreakReturn");
-        match main_window.eval("if (window.handleEarlyBreakReturn) { window.handleEarlyBreakReturn(); } else { console.error('handleEarlyBreakReturn function not found on window!'); }") {
-            Ok(_) => println!("✅ Successfully called handleEarlyBreakReturn"),
-            Err(e) => println!("❌ Error calling handleEarlyBreakReturn: {}", e),
+/// End the running break before its time. A force break spends one of today's
+/// skips first, and stays on if the skip policy refuses. Does nothing once no
+/// break is running, so an early return reported twice spends one skip.
+fn end_break_early(app_handle: &AppHandle, request: SkipRequest) -> Result<(), String> {
+    let (cycle, user_left) = {
+        let mut engine = timer_engine()
+            .lock()
+            .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+        let snapshot = engine.snapshot(Instant::now());
+        if snapshot.phase != TimerPhase::OnBreak {
+            return Ok(());
         }
-    } else {
-        println!("❌ Main window not found!");
+        // A window closing as its countdown runs out leaves the end to the timer
+        let closing = matches!(request, SkipRequest::Close(_));
+        if closing && snapshot.remaining_seconds <= skip_policy::END_GRACE_SECONDS {
+            return Ok(());
+        }
+        if snapshot.break_mode == "force" {
+            let shown = Some(snapshot.elapsed_seconds);
+            match request {
+                SkipRequest::Button => skip_policy::request(SkipKind::EarlyReturn, shown)?,
+                SkipRequest::Close(label) => {
+                    skip_policy::request_close(SkipKind::EarlyReturn, shown, snapshot.cycle, label)?
+                }
+            };
+        }
+        (snapshot.cycle, engine.end_break_early(Instant::now()))
+    };
+    println!("🏃 Break ended early - user returned");
+
+    match user_left {
+        Some(true) => println!("🚶 Idle detection confirms the user was away during the break"),
+        Some(false) => println!("⌨️ User kept working during the break"),
+        None => {}
     }
+    timer_engine::emit_state(app_handle);
+    lifecycle::emit(
+        app_handle,
+        LifecycleEvent::BreakEndedEarly { cycle, user_left },
+    );
 
     Ok(())
 }
 
+/// Used as the force break windows' close guard: closing one by hand is an
+/// early return.
+fn force_break_may_close(app_handle: &AppHandle, label: &str) -> bool {
+    end_break_early(app_handle, SkipRequest::Close(label)).is_ok()
+}
+
+#[tauri::command]
+fn break_ended_early(app_handle: tauri::AppHandle) -> Result<(), String> {
+    end_break_early(&app_handle, SkipRequest::Button)
+}
+
 #[tauri::command]
-fn skip_break(app_handle: tauri::AppHandle) -> Result<(), String> {
+async fn skip_break(app_handle: tauri::AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || skip_break_now(&app_handle))
+        .await
+        .map_err(|e| format!("Failed to skip break: {}", e))?
+}
+
+/// Closing the break windows waits for ones still being built, so this runs
+/// off the main thread.
+fn skip_break_now(app_handle: &tauri::AppHandle) -> Result<(), String> {
     println!("⏭️ Skip break requested");
 
-    // Close pre-break window if it exists
-    if let Some(window) = app_handle.get_webview_window("pre_break") {
-        let _ = window.close();
+    // Skipping a force break, running or upcoming, spends one of today's skips
+    let snapshot = timer_engine()
+        .lock()
+        .map(|engine| engine.snapshot(Instant::now()))
+        .ok();
+    if let Some(snapshot) = snapshot.filter(|s| s.phase != TimerPhase::Idle && s.break_mode == "force") {
+        let shown = (snapshot.phase == TimerPhase::OnBreak).then_some(snapshot.elapsed_seconds);
+        skip_policy::request(SkipKind::Skip, shown)?;
     }
 
+    // Close pre-break window if it exists
+    WindowManager::close_existing_window(app_handle, "pre_break");
+
     // Close any active break windows
-    if let Some(window) = app_handle.get_webview_window("force_break") {
-        let _ = window.close();
-    }
-    if let Some(window) = app_handle.get_webview_window("notify") {
-        let _ = window.close();
-    }
+    WindowManager::close_group(app_handle, FORCE_BREAK_GROUP);
+    WindowManager::close_existing_window(app_handle, "notify");
 
-    // Notify main window that break was skipped
-    if let Some(main_window) = app_handle.get_webview_window("main") {
-        println!("📱 Found main window, calling handleBreakSkipped");
-        match main_window.eval("if (window.handleBreakSkipped) { window.handleBreakSkipped(); } else { console.error('handleBreakSkipped function not found on window!'); }") {
-            Ok(_) => println!("✅ Successfully called handleBreakSkipped"),
-            Err(e) => println!("❌ Error calling handleBreakSkipped: {}", e),
-        }
-    } else {
-        println!("❌ Main window not found!");
+    // The skipped break belongs to the cycle that is ending
+    let cycle = timer_engine::current_cycle();
+    if let Ok(mut engine) = timer_engine().lock() {
+        engine.finish_break(Instant::now());
     }
+    timer_engine::emit_state(app_handle);
+    lifecycle::emit(app_handle, LifecycleEvent::BreakSkipped { cycle });
 
     println!("✅ Break skipped successfully");
     Ok(())
 }
 
+/// Today's skip budget.
+#[tauri::command]
+fn get_skip_status() -> SkipStatus {
+    skip_policy::status()
+}
+
+/// Snooze the upcoming break by `minutes` (the configured step if not given),
+/// or until the current meeting ends.
+#[tauri::command]
+async fn postpone_break(
+    app_handle: tauri::AppHandle,
+    minutes: Option<u32>,
+    until_meeting_ends: Option<bool>,
+) -> Result<TimerSnapshot, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        timer_engine::snooze_break(&app_handle, minutes, until_meeting_ends.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Failed to snooze break: {}", e))?
+}
+
 #[tauri::command]
 fn show_index_window(app_handle: tauri::AppHandle) -> Result<(), String> {
     println!("Showing index window...");
@@ -1043,13 +983,57 @@ fn show_index_window(app_handle: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Tooltip of the tray icon while no Pomodoro is running.
+const TRAY_TOOLTIP: &str = "Break Reminder Pro - Click to toggle window";
+
+/// Show `status` next to the tray icon and as its tooltip, or restore the
+/// default tooltip for `None`. Unchanged text is not sent again.
+pub fn set_tray_status(app_handle: &AppHandle, status: Option<&str>) {
+    static LAST_STATUS: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+    let mut last = match LAST_STATUS.get_or_init(|| Mutex::new(None)).lock() {
+        Ok(last) => last,
+        Err(_) => return,
+    };
+    if last.as_deref() == status {
+        return;
+    }
+    let tray = match app_handle.tray_by_id("main-tray") {
+        Some(tray) => tray,
+        None => return,
+    };
+    // The title shows next to the icon on macOS and Linux only
+    let result = tray
+        .set_tooltip(Some(status.unwrap_or(TRAY_TOOLTIP)))
+        .and_then(|_| tray.set_title(status));
+    match result {
+        Ok(()) => *last = status.map(str::to_string),
+        Err(e) => println!("⚠️ Failed to update tray status: {}", e),
+    }
+}
+
 fn setup_system_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     info!("Tray: creating menu items...");
     let quit_item = MenuItem::with_id(app, "quit", "Quit Break Reminder Pro", true, None::<&str>)?;
     let show_item = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
     let hide_item = MenuItem::with_id(app, "hide", "Hide to Tray", true, None::<&str>)?;
+    let start_item = MenuItem::with_id(app, "start_timer", "Start Timer", true, None::<&str>)?;
+    let pause_item = MenuItem::with_id(app, "pause_timer", "Pause / Resume Timer", true, None::<&str>)?;
+    let snooze_item = MenuItem::with_id(app, "snooze_break", "Snooze Break", true, None::<&str>)?;
+    let snooze_meeting_item =
+        MenuItem::with_id(app, "snooze_meeting", "Snooze Until Meeting Ends", true, None::<&str>)?;
     info!("Tray: assembling menu...");
-    let menu = Menu::with_items(app, &[&show_item, &hide_item, &quit_item])?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &start_item,
+            &pause_item,
+            &snooze_item,
+            &snooze_meeting_item,
+            &show_item,
+            &hide_item,
+            &quit_item,
+        ],
+    )?;
 
     info!("Tray: loading icon...");
     let icon = app
@@ -1059,7 +1043,7 @@ fn setup_system_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>>
 
     info!("Tray: building tray icon...");
     let _tray = TrayIconBuilder::with_id("main-tray")
-        .tooltip("Break Reminder Pro - Click to toggle window")
+        .tooltip(TRAY_TOOLTIP)
         .icon(icon)
         .menu(&menu)
         .show_menu_on_left_click(false)
@@ -1081,6 +1065,36 @@ fn setup_system_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>>
                     let _ = window.hide();
                 }
             }
+            "start_timer" => {
+                println!("🚀 Start timer selected from tray menu");
+                if let Err(e) = start_timer(app.clone(), None) {
+                    println!("❌ Failed to start timer from tray: {}", e);
+                }
+            }
+            "pause_timer" => {
+                let paused = timer_engine()
+                    .lock()
+                    .map(|engine| engine.is_paused())
+                    .unwrap_or(false);
+                let result = if paused {
+                    resume_timer(app.clone())
+                } else {
+                    pause_timer(app.clone())
+                };
+                if let Err(e) = result {
+                    println!("❌ Failed to toggle timer from tray: {}", e);
+                }
+            }
+            "snooze_break" | "snooze_meeting" => {
+                let until_meeting_ends = event.id.as_ref() == "snooze_meeting";
+                let app_handle = app.clone();
+                // Meeting detection blocks, so keep it off the main thread
+                std::thread::spawn(move || {
+                    if let Err(e) = timer_engine::snooze_break(&app_handle, None, until_meeting_ends) {
+                        println!("❌ Failed to snooze from tray: {}", e);
+                    }
+                });
+            }
             _ => {}
         })
         .on_tray_icon_event(|tray, event| {
@@ -1170,6 +1184,18 @@ pub fn run() {
                 error!("❌ Main window not found during setup!");
             }
 
+            let settings = settings::load_or_default(app.handle());
+            media::apply_settings(&settings);
+            calendar::apply_settings(&settings);
+            calendar::init(app.handle());
+            skip_policy::apply_settings(&settings);
+            skip_policy::init(app.handle());
+            meeting_rules::init(app.handle());
+
+            // Drive break scheduling from Rust so it keeps running while the main window is hidden
+            timer_engine::spawn_driver(app.handle().clone());
+            info!("✅ Timer engine started");
+
             info!("Step 4: Setup callback complete");
             Ok(())
         })
@@ -1178,10 +1204,16 @@ pub fn run() {
             is_screen_locked,
             control_media,
             is_media_playing,
+            list_media_sessions,
             play_chime,
             is_meeting_active,
+            detect_meeting,
+            test_meeting_rules,
+            detect_screen_activity,
             check_browser_meeting_debug,
             force_break_window,
+            get_window_payload,
+            list_windows,
             close_window,
             notify_window,
             pre_break_notification_window,
@@ -1189,6 +1221,8 @@ pub fn run() {
             get_primary_monitor_size,
             break_ended_early,
             skip_break,
+            postpone_break,
+            get_skip_status,
             enable_autostart,
             disable_autostart,
             is_autostart_enabled,
@@ -1204,7 +1238,17 @@ pub fn run() {
             show_update_notification,
             set_media_was_playing,
             get_media_was_playing,
-            clear_media_was_playing
+            clear_media_was_playing,
+            start_timer,
+            pause_timer,
+            resume_timer,
+            stop_timer,
+            get_timer_state,
+            get_postponements,
+            get_calendar_busy,
+            sync_caldav,
+            set_caldav_password,
+            has_caldav_password
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,68 @@
+//! Break lifecycle events, emitted to every window as `LIFECYCLE_EVENT`.
+//!
+//! Each payload is a JSON object with `version`, `timestamp` (Unix time in
+//! milliseconds) and a `type` naming the event, plus that event's fields:
+//!
+//! ```json
+//! {"version":1,"timestamp":1760000000000,"type":"break_skipped","cycle":3}
+//! ```
+//!
+//! New event types and fields may be added within a version; `version` is bumped
+//! when an existing field changes meaning or goes away.
+
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+use crate::timer_engine::TimerSnapshot;
+
+pub const LIFECYCLE_EVENT: &str = "lifecycle";
+
+pub const LIFECYCLE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    /// The timer state changed; sent at most once per visible change.
+    TimerTick { state: TimerSnapshot },
+    /// The pre-break warning is showing.
+    PreBreakWarning { cycle: u32, remaining_seconds: u32 },
+    /// The break was held back because a meeting or presentation is going on.
+    MeetingPostponed { cycle: u32, reason: String },
+    /// The user snoozed the upcoming break, by `minutes` or (`None`) until the
+    /// meeting ends.
+    BreakSnoozed { cycle: u32, minutes: Option<u32>, reason: String },
+    /// The break UI opened (or the screen was locked).
+    BreakStarted { cycle: u32, mode: String, duration: u32 },
+    /// The break ran its full length.
+    BreakFinished { cycle: u32 },
+    /// The upcoming or running break was skipped.
+    BreakSkipped { cycle: u32 },
+    /// A break window was closed before the break was over. `user_left` says
+    /// whether idle detection saw the user away, when it is available.
+    BreakEndedEarly { cycle: u32, user_left: Option<bool> },
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u32,
+    timestamp: u64,
+    #[serde(flatten)]
+    event: &'a LifecycleEvent,
+}
+
+/// Send the event to every window.
+pub fn emit(app_handle: &AppHandle, event: LifecycleEvent) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0);
+    let envelope = Envelope {
+        version: LIFECYCLE_VERSION,
+        timestamp,
+        event: &event,
+    };
+    if let Err(e) = app_handle.emit(LIFECYCLE_EVENT, &envelope) {
+        println!("⚠️ Failed to emit lifecycle event: {}", e);
+    }
+}
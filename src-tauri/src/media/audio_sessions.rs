@@ -0,0 +1,90 @@
+//! Core Audio sessions on the default render device (Windows).
+//!
+//! Each session's `ISimpleAudioVolume` master level is a single 0.0–1.0 scalar.
+//! Sessions are addressed by their session instance identifier.
+
+use super::volume::{AudioStream, VolumeMixer};
+use windows::core::ComInterface;
+use windows::Win32::Media::Audio::{
+    eMultimedia, eRender, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator,
+    ISimpleAudioVolume, MMDeviceEnumerator,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_MULTITHREADED,
+};
+
+pub struct AudioSessionMixer;
+
+fn sessions() -> Result<Vec<(String, u32, ISimpleAudioVolume)>, String> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+            .map_err(|e| format!("CoCreateInstance failed: {:?}", e))?;
+        let device = enumerator
+            .GetDefaultAudioEndpoint(eRender, eMultimedia)
+            .map_err(|e| format!("GetDefaultAudioEndpoint failed: {:?}", e))?;
+        let session_manager: IAudioSessionManager2 = device
+            .Activate(CLSCTX_ALL, None)
+            .map_err(|e| format!("Activate IAudioSessionManager2 failed: {:?}", e))?;
+        let session_enum = session_manager
+            .GetSessionEnumerator()
+            .map_err(|e| format!("GetSessionEnumerator failed: {:?}", e))?;
+        let count = session_enum.GetCount().unwrap_or(0);
+
+        let mut sessions = Vec::new();
+        for i in 0..count {
+            let session = match session_enum.GetSession(i) { Ok(s) => s, Err(_) => continue };
+            let session2: IAudioSessionControl2 = match session.cast() { Ok(s) => s, Err(_) => continue };
+            let volume: ISimpleAudioVolume = match session.cast() { Ok(v) => v, Err(_) => continue };
+            let pid = session2.GetProcessId().unwrap_or(0);
+            let id = match session2.GetSessionInstanceIdentifier() {
+                Ok(pwstr) => {
+                    let id = pwstr.to_string().unwrap_or_default();
+                    CoTaskMemFree(Some(pwstr.0 as *const _));
+                    id
+                }
+                Err(_) => continue,
+            };
+            sessions.push((id, pid, volume));
+        }
+        Ok(sessions)
+    }
+}
+
+impl VolumeMixer for AudioSessionMixer {
+    fn name(&self) -> &'static str {
+        "audio_sessions"
+    }
+
+    fn list_streams(&self) -> Result<Vec<AudioStream>, String> {
+        use sysinfo::{Pid, System};
+
+        let sys = System::new_all();
+        let own_pid = std::process::id();
+        let mut streams = Vec::new();
+        for (id, pid, volume) in sessions()? {
+            // pid 0 is the system sounds session
+            if pid == 0 || pid == own_pid {
+                continue;
+            }
+            let level = match unsafe { volume.GetMasterVolume() } { Ok(l) => l, Err(_) => continue };
+            let name = sys
+                .process(Pid::from_u32(pid))
+                .map(|p| p.name().trim_end_matches(".exe").to_string())
+                .unwrap_or_else(|| format!("pid {}", pid));
+            streams.push(AudioStream { id, name, volume: vec![level as f64] });
+        }
+        Ok(streams)
+    }
+
+    fn set_volume(&self, id: &str, volume: &[f64]) -> Result<(), String> {
+        let level = *volume.first().ok_or("No volume given")? as f32;
+        let (_, _, session) = sessions()?
+            .into_iter()
+            .find(|(session_id, _, _)| session_id == id)
+            .ok_or_else(|| format!("Audio session {} not found", id))?;
+        unsafe { session.SetMasterVolume(level, std::ptr::null()) }
+            .map_err(|e| format!("SetMasterVolume failed: {:?}", e))
+    }
+}
@@ -0,0 +1,712 @@
+//! Media control through pluggable per-player backends.
+//!
+//! Each backend knows how to list its sessions and pause/resume one of them.
+//! The registry runs every available backend and remembers, per backend, which
+//! sessions it paused, so a resume only touches media we stopped ourselves.
+//! Volume mixers are handled the same way for ducking: the registry lowers
+//! per-application streams and remembers their exact levels for the restore.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+use crate::settings::AppSettings;
+use volume::VolumeMixer;
+
+#[cfg(target_os = "windows")]
+mod audio_sessions;
+#[cfg(target_os = "linux")]
+mod mpris;
+#[cfg(target_os = "linux")]
+mod pulse;
+#[cfg(target_os = "windows")]
+mod smtc;
+#[cfg(target_os = "windows")]
+mod vlc;
+mod volume;
+
+/// Default ducked level as a fraction of each stream's own volume.
+const DEFAULT_DUCK_LEVEL: f64 = 0.3;
+
+/// One controllable player as seen by a backend.
+#[derive(Clone, Debug, Serialize)]
+pub struct MediaSession {
+    /// Backend-specific token used to address the session again later
+    /// (SMTC app id, MPRIS bus name, ...).
+    pub id: String,
+    /// Human-readable player name for the settings UI.
+    pub name: String,
+    pub backend: &'static str,
+    pub playing: bool,
+}
+
+/// The user's allow/deny lists for auto-pause.
+///
+/// Entries match case-insensitively against any part of a session's id or
+/// name, so "spotify" covers both `Spotify.exe` and
+/// `org.mpris.MediaPlayer2.spotify`.
+#[derive(Clone, Debug, Default)]
+pub struct MediaFilter {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl MediaFilter {
+    pub fn new(allow: &[String], deny: &[String]) -> Self {
+        let normalize = |entries: &[String]| {
+            entries
+                .iter()
+                .map(|entry| entry.trim().to_lowercase())
+                .filter(|entry| !entry.is_empty())
+                .collect()
+        };
+        Self {
+            allow: normalize(allow),
+            deny: normalize(deny),
+        }
+    }
+
+    /// Whether auto-pause may touch this session. An empty allow list allows everything.
+    pub fn allows(&self, session: &MediaSession) -> bool {
+        self.allows_name(&session.id, &session.name)
+    }
+
+    pub fn allows_name(&self, id: &str, name: &str) -> bool {
+        let id = id.to_lowercase();
+        let name = name.to_lowercase();
+        let matches = |entry: &String| id.contains(entry.as_str()) || name.contains(entry.as_str());
+        (self.allow.is_empty() || self.allow.iter().any(matches)) && !self.deny.iter().any(matches)
+    }
+}
+
+/// A family of media players that can be paused and resumed individually.
+pub trait MediaBackend: Send {
+    fn name(&self) -> &'static str;
+
+    fn list_sessions(&self) -> Result<Vec<MediaSession>, String>;
+
+    fn pause(&self, id: &str) -> Result<(), String>;
+
+    fn resume(&self, id: &str) -> Result<(), String>;
+
+    fn is_playing(&self) -> Result<bool, String> {
+        Ok(self.list_sessions()?.iter().any(|session| session.playing))
+    }
+}
+
+/// Runs every backend and keeps the "paused by us" tokens for each of them.
+pub struct MediaRegistry {
+    backends: Vec<Box<dyn MediaBackend>>,
+    paused: HashMap<&'static str, HashSet<String>>,
+    filter: MediaFilter,
+    /// Every session seen since startup, keyed by "backend:id".
+    seen: BTreeMap<String, MediaSession>,
+    mixers: Vec<Box<dyn VolumeMixer>>,
+    /// Original per-channel volume of every stream we ducked, per mixer.
+    ducked: HashMap<&'static str, HashMap<String, Vec<f64>>>,
+    duck_level: f64,
+}
+
+impl MediaRegistry {
+    pub fn new(backends: Vec<Box<dyn MediaBackend>>, mixers: Vec<Box<dyn VolumeMixer>>) -> Self {
+        Self {
+            backends,
+            paused: HashMap::new(),
+            filter: MediaFilter::default(),
+            seen: BTreeMap::new(),
+            mixers,
+            ducked: HashMap::new(),
+            duck_level: DEFAULT_DUCK_LEVEL,
+        }
+    }
+
+    /// Registry with every backend and mixer supported on this platform.
+    pub fn detect() -> Self {
+        let registry = Self::new(available_backends(), available_mixers());
+        let mixers: Vec<&str> = registry.mixers.iter().map(|mixer| mixer.name()).collect();
+        println!(
+            "🎵 Media backends: {:?}, volume mixers: {:?}",
+            registry.backend_names(),
+            mixers
+        );
+        registry
+    }
+
+    pub fn backend_names(&self) -> Vec<&'static str> {
+        self.backends.iter().map(|backend| backend.name()).collect()
+    }
+
+    pub fn set_filter(&mut self, filter: MediaFilter) {
+        self.filter = filter;
+    }
+
+    /// Ducked volume as a fraction (0.0–1.0) of each stream's own level.
+    pub fn set_duck_level(&mut self, level: f64) {
+        self.duck_level = level.clamp(0.0, 1.0);
+    }
+
+    /// Sessions of every backend that answered, plus players seen earlier that
+    /// are gone now (reported as not playing).
+    pub fn known_sessions(&mut self) -> Vec<MediaSession> {
+        for session in self.seen.values_mut() {
+            session.playing = false;
+        }
+        for backend in &self.backends {
+            match backend.list_sessions() {
+                Ok(sessions) => {
+                    for session in sessions {
+                        self.seen
+                            .insert(format!("{}:{}", session.backend, session.id), session);
+                    }
+                }
+                Err(e) => println!("⚠️ {}: {}", backend.name(), e),
+            }
+        }
+        self.seen.values().cloned().collect()
+    }
+
+    /// True if any backend reports a playing session.
+    /// Fails only if no backend could be asked at all.
+    pub fn is_any_playing(&self) -> Result<bool, String> {
+        let mut answered = false;
+        for backend in &self.backends {
+            match backend.is_playing() {
+                Ok(true) => return Ok(true),
+                Ok(false) => answered = true,
+                Err(e) => println!("⚠️ {}: {}", backend.name(), e),
+            }
+        }
+        if answered {
+            Ok(false)
+        } else {
+            Err("No media backend available".to_string())
+        }
+    }
+
+    /// Pause every playing session and record it as paused by us.
+    /// Returns how many sessions were paused.
+    pub fn pause_playing(&mut self) -> Result<usize, String> {
+        let mut answered = false;
+        let mut count = 0;
+        for backend in &self.backends {
+            let sessions = match backend.list_sessions() {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    println!("⚠️ {}: {}", backend.name(), e);
+                    continue;
+                }
+            };
+            answered = true;
+            let tokens = self.paused.entry(backend.name()).or_default();
+            for session in sessions {
+                self.seen
+                    .insert(format!("{}:{}", session.backend, session.id), session.clone());
+                if !session.playing {
+                    println!("  ⏭️ Skipped (not playing): {} {}", backend.name(), session.id);
+                    continue;
+                }
+                if !self.filter.allows(&session) {
+                    println!("  ⏭️ Skipped (excluded by player rules): {} {}", backend.name(), session.id);
+                    continue;
+                }
+                println!("  ⏸ Pausing {}: {}", backend.name(), session.id);
+                match backend.pause(&session.id) {
+                    Ok(()) => {
+                        tokens.insert(session.id);
+                        count += 1;
+                    }
+                    Err(e) => println!("  ⚠️ {}", e),
+                }
+            }
+            println!("💾 {} paused by us: {:?}", backend.name(), tokens);
+        }
+        if answered {
+            Ok(count)
+        } else {
+            Err("No media backend available".to_string())
+        }
+    }
+
+    /// Resume only the sessions recorded by `pause_playing`, skipping any that
+    /// went away or were started again by the user in the meantime.
+    /// The recorded tokens are consumed. Returns how many sessions were resumed.
+    pub fn resume_paused(&mut self) -> Result<usize, String> {
+        let mut answered = false;
+        let mut count = 0;
+        for backend in &self.backends {
+            let tokens = self.paused.remove(backend.name()).unwrap_or_default();
+            if tokens.is_empty() {
+                answered = true;
+                continue;
+            }
+            let sessions = match backend.list_sessions() {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    // Keep the tokens so a later resume can still find them
+                    println!("⚠️ {}: {}", backend.name(), e);
+                    self.paused.insert(backend.name(), tokens);
+                    continue;
+                }
+            };
+            answered = true;
+            for token in tokens {
+                match sessions.iter().find(|session| session.id == token) {
+                    None => println!("  ⏭️ No longer running: {} {}", backend.name(), token),
+                    Some(session) if session.playing => {}
+                    Some(_) => {
+                        println!("  ▶ Resuming {}: {}", backend.name(), token);
+                        match backend.resume(&token) {
+                            Ok(()) => count += 1,
+                            Err(e) => println!("  ⚠️ {}", e),
+                        }
+                    }
+                }
+            }
+        }
+        if answered {
+            Ok(count)
+        } else {
+            Err("No media backend available".to_string())
+        }
+    }
+
+    /// Flip every session the player rules allow between playing and paused,
+    /// without bookkeeping.
+    pub fn toggle_all(&mut self) -> Result<(), String> {
+        let mut answered = false;
+        for backend in &self.backends {
+            let sessions = match backend.list_sessions() {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    println!("⚠️ {}: {}", backend.name(), e);
+                    continue;
+                }
+            };
+            answered = true;
+            for session in sessions.iter().filter(|session| self.filter.allows(session)) {
+                let result = if session.playing {
+                    backend.pause(&session.id)
+                } else {
+                    backend.resume(&session.id)
+                };
+                if let Err(e) = result {
+                    println!("  ⚠️ {}", e);
+                }
+            }
+        }
+        if answered {
+            Ok(())
+        } else {
+            Err("No media backend available".to_string())
+        }
+    }
+
+    pub fn clear_paused(&mut self) {
+        self.paused.clear();
+    }
+
+    /// Lower every stream the player rules allow to the duck level and remember
+    /// its previous volume. Streams that are already ducked are left alone, so
+    /// ducking twice never loses the original level. Returns how many were ducked.
+    pub fn duck(&mut self) -> Result<usize, String> {
+        if self.mixers.is_empty() {
+            return Err("No volume mixer available".to_string());
+        }
+        let mut count = 0;
+        for mixer in &self.mixers {
+            let streams = match mixer.list_streams() {
+                Ok(streams) => streams,
+                Err(e) => {
+                    println!("⚠️ {}: {}", mixer.name(), e);
+                    continue;
+                }
+            };
+            let ducked = self.ducked.entry(mixer.name()).or_default();
+            for stream in streams {
+                if ducked.contains_key(&stream.id) {
+                    continue;
+                }
+                if !self.filter.allows_name(&stream.id, &stream.name) {
+                    println!("  ⏭️ Not ducking (excluded by player rules): {}", stream.name);
+                    continue;
+                }
+                let lowered: Vec<f64> = stream.volume.iter().map(|v| v * self.duck_level).collect();
+                println!("  🔉 Ducking {} {}: {:?} → {:?}", mixer.name(), stream.name, stream.volume, lowered);
+                match mixer.set_volume(&stream.id, &lowered) {
+                    Ok(()) => {
+                        ducked.insert(stream.id, stream.volume);
+                        count += 1;
+                    }
+                    Err(e) => println!("  ⚠️ {}", e),
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Put every ducked stream back to its exact previous volume. Streams that
+    /// went away are forgotten. Returns how many were restored.
+    pub fn restore_ducked(&mut self) -> usize {
+        let mut count = 0;
+        for mixer in &self.mixers {
+            let Some(ducked) = self.ducked.remove(mixer.name()) else { continue };
+            let present: HashSet<String> = match mixer.list_streams() {
+                Ok(streams) => streams.into_iter().map(|stream| stream.id).collect(),
+                Err(e) => {
+                    // Keep the levels so a later restore can retry
+                    println!("⚠️ {}: {}", mixer.name(), e);
+                    self.ducked.insert(mixer.name(), ducked);
+                    continue;
+                }
+            };
+            for (id, volume) in ducked {
+                if !present.contains(&id) {
+                    continue;
+                }
+                println!("  🔊 Restoring {} stream {}: {:?}", mixer.name(), id, volume);
+                match mixer.set_volume(&id, &volume) {
+                    Ok(()) => count += 1,
+                    Err(e) => println!("  ⚠️ {}", e),
+                }
+            }
+        }
+        count
+    }
+
+    /// Entry point for `control_media`: "pause", "play" (resume and un-duck),
+    /// "duck", "unduck", or anything else to toggle.
+    pub fn control(&mut self, action: &str) -> Result<(), String> {
+        match action {
+            "pause" => self.pause_playing().map(|_| ()),
+            "play" => {
+                let was_ducking = !self.ducked.is_empty();
+                self.restore_ducked();
+                match self.resume_paused() {
+                    // A ducked break paused nothing, so there is nothing to fall back for
+                    Err(_) if was_ducking => Ok(()),
+                    result => result.map(|_| ()),
+                }
+            }
+            "duck" => match self.duck() {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    println!("⚠️ {}, pausing instead", e);
+                    self.pause_playing().map(|_| ())
+                }
+            },
+            "unduck" => {
+                self.restore_ducked();
+                Ok(())
+            }
+            _ => self.toggle_all(),
+        }
+    }
+}
+
+fn available_backends() -> Vec<Box<dyn MediaBackend>> {
+    #[allow(unused_mut)]
+    let mut backends: Vec<Box<dyn MediaBackend>> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        // VLC never registers with SMTC, so it needs its own backend
+        backends.push(Box::new(vlc::VlcBackend));
+        backends.push(Box::new(smtc::SmtcBackend));
+    }
+
+    #[cfg(target_os = "linux")]
+    match mpris::MprisBackend::connect() {
+        Ok(backend) => backends.push(Box::new(backend)),
+        Err(e) => println!("ℹ️ MPRIS unavailable: {}", e),
+    }
+
+    backends
+}
+
+fn available_mixers() -> Vec<Box<dyn VolumeMixer>> {
+    #[allow(unused_mut)]
+    let mut mixers: Vec<Box<dyn VolumeMixer>> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    mixers.push(Box::new(audio_sessions::AudioSessionMixer));
+
+    #[cfg(target_os = "linux")]
+    match pulse::PulseMixer::connect() {
+        Some(mixer) => mixers.push(Box::new(mixer)),
+        None => println!("ℹ️ No PulseAudio/PipeWire server found, ducking unavailable"),
+    }
+
+    mixers
+}
+
+static MEDIA_REGISTRY: OnceLock<Mutex<MediaRegistry>> = OnceLock::new();
+
+/// The process-wide registry, created on first use.
+pub fn media_registry() -> &'static Mutex<MediaRegistry> {
+    MEDIA_REGISTRY.get_or_init(|| Mutex::new(MediaRegistry::detect()))
+}
+
+/// Pick up the allow/deny lists and the duck level from the settings.
+pub fn apply_settings(settings: &AppSettings) {
+    let filter = MediaFilter::new(&settings.media_allow_list, &settings.media_deny_list);
+    if let Ok(mut registry) = media_registry().lock() {
+        registry.set_filter(filter);
+        registry.set_duck_level(settings.duck_volume_percent as f64 / 100.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use volume::AudioStream;
+
+    /// Players shared between a test and its fake backend, as (id, playing).
+    type Players = Arc<Mutex<Vec<(String, bool)>>>;
+
+    struct FakeBackend {
+        name: &'static str,
+        players: Players,
+        /// Answer every call with an error, like a backend whose service went away.
+        broken: Arc<Mutex<bool>>,
+    }
+
+    impl FakeBackend {
+        fn check(&self) -> Result<(), String> {
+            if *self.broken.lock().unwrap() {
+                return Err(format!("{} is down", self.name));
+            }
+            Ok(())
+        }
+
+        fn set_playing(&self, id: &str, playing: bool) -> Result<(), String> {
+            self.check()?;
+            let mut players = self.players.lock().unwrap();
+            let player = players
+                .iter_mut()
+                .find(|(player_id, _)| player_id == id)
+                .ok_or(format!("No player {}", id))?;
+            player.1 = playing;
+            Ok(())
+        }
+    }
+
+    impl MediaBackend for FakeBackend {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn list_sessions(&self) -> Result<Vec<MediaSession>, String> {
+            self.check()?;
+            Ok(self
+                .players
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(id, playing)| MediaSession {
+                    id: id.clone(),
+                    name: id.clone(),
+                    backend: self.name,
+                    playing: *playing,
+                })
+                .collect())
+        }
+
+        fn pause(&self, id: &str) -> Result<(), String> {
+            self.set_playing(id, false)
+        }
+
+        fn resume(&self, id: &str) -> Result<(), String> {
+            self.set_playing(id, true)
+        }
+    }
+
+    /// A fake backend plus the handles a test uses to change its players.
+    fn backend(name: &'static str, players: &[(&str, bool)]) -> (Box<dyn MediaBackend>, Players, Arc<Mutex<bool>>) {
+        let players: Players = Arc::new(Mutex::new(
+            players.iter().map(|(id, playing)| (id.to_string(), *playing)).collect(),
+        ));
+        let broken = Arc::new(Mutex::new(false));
+        let backend = FakeBackend {
+            name,
+            players: players.clone(),
+            broken: broken.clone(),
+        };
+        (Box::new(backend), players, broken)
+    }
+
+    fn playing(players: &Players, id: &str) -> bool {
+        players.lock().unwrap().iter().any(|(player, playing)| player == id && *playing)
+    }
+
+    #[test]
+    fn resumes_only_what_it_paused() {
+        let (smtc, smtc_players, _) = backend("smtc", &[("spotify", true), ("podcasts", false)]);
+        let (vlc, vlc_players, _) = backend("vlc", &[("vlc", true)]);
+        let mut registry = MediaRegistry::new(vec![smtc, vlc], Vec::new());
+
+        assert_eq!(registry.pause_playing(), Ok(2));
+        assert!(!playing(&smtc_players, "spotify"));
+        assert!(!playing(&vlc_players, "vlc"));
+
+        assert_eq!(registry.resume_paused(), Ok(2));
+        assert!(playing(&smtc_players, "spotify"));
+        assert!(playing(&vlc_players, "vlc"));
+        assert!(!playing(&smtc_players, "podcasts"));
+        // The tokens were used up
+        assert_eq!(registry.resume_paused(), Ok(0));
+    }
+
+    #[test]
+    fn resume_skips_players_restarted_or_gone() {
+        let (smtc, players, _) = backend("smtc", &[("spotify", true), ("browser", true)]);
+        let mut registry = MediaRegistry::new(vec![smtc], Vec::new());
+        assert_eq!(registry.pause_playing(), Ok(2));
+
+        players.lock().unwrap().retain(|(id, _)| id != "browser");
+        registry.control("toggle").unwrap();
+        assert!(playing(&players, "spotify"));
+        assert_eq!(registry.resume_paused(), Ok(0));
+    }
+
+    #[test]
+    fn broken_backend_does_not_stop_the_others() {
+        let (smtc, _, smtc_broken) = backend("smtc", &[("spotify", true)]);
+        let (vlc, vlc_players, _) = backend("vlc", &[("vlc", true)]);
+        *smtc_broken.lock().unwrap() = true;
+        let mut registry = MediaRegistry::new(vec![smtc, vlc], Vec::new());
+
+        assert_eq!(registry.is_any_playing(), Ok(true));
+        assert_eq!(registry.pause_playing(), Ok(1));
+        assert!(!playing(&vlc_players, "vlc"));
+        assert_eq!(registry.backend_names(), vec!["smtc", "vlc"]);
+    }
+
+    #[test]
+    fn fails_when_no_backend_answers() {
+        let (smtc, _, broken) = backend("smtc", &[("spotify", true)]);
+        *broken.lock().unwrap() = true;
+        let mut registry = MediaRegistry::new(vec![smtc], Vec::new());
+        assert!(registry.is_any_playing().is_err());
+        assert!(registry.pause_playing().is_err());
+        assert!(MediaRegistry::new(Vec::new(), Vec::new()).control("pause").is_err());
+    }
+
+    #[test]
+    fn resume_keeps_tokens_while_the_backend_is_down() {
+        let (smtc, players, broken) = backend("smtc", &[("spotify", true)]);
+        let mut registry = MediaRegistry::new(vec![smtc], Vec::new());
+        assert_eq!(registry.pause_playing(), Ok(1));
+
+        *broken.lock().unwrap() = true;
+        assert!(registry.resume_paused().is_err());
+        *broken.lock().unwrap() = false;
+        assert_eq!(registry.resume_paused(), Ok(1));
+        assert!(playing(&players, "spotify"));
+    }
+
+    #[test]
+    fn players_that_left_are_still_listed_as_stopped() {
+        let (smtc, players, _) = backend("smtc", &[("spotify", true)]);
+        let mut registry = MediaRegistry::new(vec![smtc], Vec::new());
+        assert_eq!(registry.known_sessions().len(), 1);
+
+        players.lock().unwrap().clear();
+        let sessions = registry.known_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert!(!sessions[0].playing);
+    }
+
+    /// Streams shared between a test and its fake mixer.
+    type Streams = Arc<Mutex<Vec<AudioStream>>>;
+
+    struct FakeMixer {
+        streams: Streams,
+    }
+
+    impl VolumeMixer for FakeMixer {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn list_streams(&self) -> Result<Vec<AudioStream>, String> {
+            Ok(self.streams.lock().unwrap().clone())
+        }
+
+        fn set_volume(&self, id: &str, volume: &[f64]) -> Result<(), String> {
+            let mut streams = self.streams.lock().unwrap();
+            let stream = streams
+                .iter_mut()
+                .find(|stream| stream.id == id)
+                .ok_or(format!("No stream {}", id))?;
+            stream.volume = volume.to_vec();
+            Ok(())
+        }
+    }
+
+    /// A registry with just a fake mixer, and the handle to its streams.
+    fn mixer(streams: &[(&str, &[f64])]) -> (MediaRegistry, Streams) {
+        let streams: Streams = Arc::new(Mutex::new(
+            streams
+                .iter()
+                .map(|(id, volume)| AudioStream {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    volume: volume.to_vec(),
+                })
+                .collect(),
+        ));
+        let mixer = FakeMixer { streams: streams.clone() };
+        (MediaRegistry::new(Vec::new(), vec![Box::new(mixer)]), streams)
+    }
+
+    fn volume(streams: &Streams, id: &str) -> Vec<f64> {
+        let streams = streams.lock().unwrap();
+        streams.iter().find(|stream| stream.id == id).unwrap().volume.clone()
+    }
+
+    #[test]
+    fn ducking_restores_the_exact_levels() {
+        let (mut registry, streams) = mixer(&[("music", &[65536.0, 32768.0]), ("call", &[40000.0])]);
+        assert_eq!(registry.duck(), Ok(2));
+        assert_eq!(volume(&streams, "music"), vec![65536.0 * DEFAULT_DUCK_LEVEL, 32768.0 * DEFAULT_DUCK_LEVEL]);
+
+        assert_eq!(registry.restore_ducked(), 2);
+        assert_eq!(volume(&streams, "music"), vec![65536.0, 32768.0]);
+        assert_eq!(volume(&streams, "call"), vec![40000.0]);
+        // Nothing left to restore
+        assert_eq!(registry.restore_ducked(), 0);
+    }
+
+    #[test]
+    fn streams_gone_before_the_restore_are_forgotten() {
+        let (mut registry, streams) = mixer(&[("music", &[65536.0]), ("video", &[50000.0])]);
+        assert_eq!(registry.duck(), Ok(2));
+
+        streams.lock().unwrap().retain(|stream| stream.id != "video");
+        assert_eq!(registry.restore_ducked(), 1);
+        assert_eq!(volume(&streams, "music"), vec![65536.0]);
+        assert!(registry.ducked.is_empty());
+    }
+
+    #[test]
+    fn ducking_twice_keeps_the_first_saved_level() {
+        let (mut registry, streams) = mixer(&[("music", &[65536.0])]);
+        assert_eq!(registry.duck(), Ok(1));
+        assert_eq!(registry.duck(), Ok(0));
+        assert_eq!(volume(&streams, "music"), vec![65536.0 * DEFAULT_DUCK_LEVEL]);
+
+        assert_eq!(registry.restore_ducked(), 1);
+        assert_eq!(volume(&streams, "music"), vec![65536.0]);
+    }
+
+    #[test]
+    fn control_falls_back_to_pausing_without_a_mixer() {
+        let (smtc, players, _) = backend("smtc", &[("spotify", true)]);
+        let mut registry = MediaRegistry::new(vec![smtc], Vec::new());
+        assert_eq!(registry.control("duck"), Ok(()));
+        assert!(!playing(&players, "spotify"));
+        assert_eq!(registry.control("play"), Ok(()));
+        assert!(playing(&players, "spotify"));
+    }
+}
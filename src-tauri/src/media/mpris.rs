@@ -0,0 +1,192 @@
+//! MPRIS2 players on the D-Bus session bus (Linux).
+//!
+//! Sessions are addressed by their well-known bus name
+//! (`org.mpris.MediaPlayer2.<player>`).
+
+use super::{MediaBackend, MediaSession};
+use zbus::blocking::fdo::DBusProxy;
+use zbus::blocking::{connection, proxy, Connection, Proxy};
+use zbus::proxy::CacheProperties;
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+pub struct MprisBackend {
+    conn: Connection,
+}
+
+impl MprisBackend {
+    /// Connect to the session bus.
+    pub fn connect() -> Result<Self, String> {
+        let conn =
+            Connection::session().map_err(|e| format!("D-Bus session bus unavailable: {}", e))?;
+        Ok(Self { conn })
+    }
+
+    /// Connect to the bus at a D-Bus address, such as `unix:path=/run/bus`.
+    pub fn connect_to(address: &str) -> Result<Self, String> {
+        let conn = connection::Builder::address(address)
+            .and_then(|builder| builder.build())
+            .map_err(|e| format!("D-Bus bus at {} unavailable: {}", address, e))?;
+        Ok(Self { conn })
+    }
+
+    /// Well-known bus names of every MPRIS player on the bus.
+    fn list_players(&self) -> Result<Vec<String>, String> {
+        let dbus = DBusProxy::new(&self.conn).map_err(|e| format!("DBus proxy failed: {}", e))?;
+        let names = dbus
+            .list_names()
+            .map_err(|e| format!("ListNames failed: {}", e))?;
+        Ok(names
+            .into_iter()
+            .map(|name| name.to_string())
+            .filter(|name| name.starts_with(MPRIS_PREFIX))
+            .collect())
+    }
+
+    fn player_proxy<'a>(&self, bus_name: &'a str) -> Result<Proxy<'a>, String> {
+        proxy::Builder::<Proxy>::new(&self.conn)
+            .destination(bus_name)
+            .and_then(|b| b.path(MPRIS_PATH))
+            .and_then(|b| b.interface(PLAYER_INTERFACE))
+            .map(|b| b.cache_properties(CacheProperties::No))
+            .and_then(|b| b.build())
+            .map_err(|e| format!("Failed to create MPRIS proxy for {}: {}", bus_name, e))
+    }
+
+    /// `PlaybackStatus` of a player: "Playing", "Paused" or "Stopped".
+    fn playback_status(&self, bus_name: &str) -> Option<String> {
+        let proxy = self.player_proxy(bus_name).ok()?;
+        proxy.get_property::<String>("PlaybackStatus").ok()
+    }
+
+    fn call_player(&self, bus_name: &str, method: &str) -> Result<(), String> {
+        let proxy = self.player_proxy(bus_name)?;
+        proxy
+            .call_method(method, &())
+            .map(|_| ())
+            .map_err(|e| format!("{} on {} failed: {}", method, bus_name, e))
+    }
+}
+
+/// `org.mpris.MediaPlayer2.vlc.instance1234` → `vlc`
+fn player_name(bus_name: &str) -> String {
+    let name = bus_name.strip_prefix(MPRIS_PREFIX).unwrap_or(bus_name);
+    match name.split_once(".instance") {
+        Some((player, _)) => player.to_string(),
+        None => name.to_string(),
+    }
+}
+
+impl MediaBackend for MprisBackend {
+    fn name(&self) -> &'static str {
+        "mpris"
+    }
+
+    fn list_sessions(&self) -> Result<Vec<MediaSession>, String> {
+        Ok(self
+            .list_players()?
+            .into_iter()
+            .map(|player| {
+                let playing = self.playback_status(&player).as_deref() == Some("Playing");
+                MediaSession {
+                    name: player_name(&player),
+                    id: player,
+                    backend: self.name(),
+                    playing,
+                }
+            })
+            .collect())
+    }
+
+    fn pause(&self, id: &str) -> Result<(), String> {
+        self.call_player(id, "Pause")
+    }
+
+    fn resume(&self, id: &str) -> Result<(), String> {
+        self.call_player(id, "Play")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn player_name_drops_the_prefix_and_instance() {
+        assert_eq!(player_name("org.mpris.MediaPlayer2.vlc.instance1234"), "vlc");
+        assert_eq!(player_name("org.mpris.MediaPlayer2.spotify"), "spotify");
+    }
+
+    /// A private bus, killed when the test ends.
+    struct Bus(Child);
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    /// The `org.mpris.MediaPlayer2.Player` calls the backend makes.
+    struct FakePlayer {
+        status: Arc<Mutex<String>>,
+    }
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl FakePlayer {
+        fn pause(&self) {
+            *self.status.lock().unwrap() = "Paused".to_string();
+        }
+
+        fn play(&self) {
+            *self.status.lock().unwrap() = "Playing".to_string();
+        }
+
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.status.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn pauses_and_resumes_a_player_on_a_private_bus() {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("dbus-daemon runs");
+        let stdout = daemon.stdout.take().unwrap();
+        let _bus = Bus(daemon);
+        let mut address = String::new();
+        BufReader::new(stdout).read_line(&mut address).unwrap();
+        let address = address.trim();
+
+        let status = Arc::new(Mutex::new("Playing".to_string()));
+        let player = FakePlayer { status: status.clone() };
+        let _player = connection::Builder::address(address)
+            .and_then(|builder| builder.name("org.mpris.MediaPlayer2.fake.instance42"))
+            .and_then(|builder| builder.serve_at(MPRIS_PATH, player))
+            .and_then(|builder| builder.build())
+            .unwrap();
+
+        let backend = MprisBackend::connect_to(address).unwrap();
+        let sessions = backend.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].name, "fake");
+        assert!(sessions[0].playing);
+
+        backend.pause(&sessions[0].id).unwrap();
+        assert_eq!(*status.lock().unwrap(), "Paused");
+        assert!(!backend.is_playing().unwrap());
+
+        backend.resume(&sessions[0].id).unwrap();
+        assert_eq!(*status.lock().unwrap(), "Playing");
+        assert!(backend.is_playing().unwrap());
+    }
+}
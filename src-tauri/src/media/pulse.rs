@@ -0,0 +1,196 @@
+//! PulseAudio sink-inputs via `pactl` (Linux). Also covers PipeWire through
+//! `pipewire-pulse`.
+//!
+//! Volumes are the raw per-channel values `pactl` prints (65536 = 100%), so a
+//! restore writes back exactly what was there before.
+
+use super::volume::{AudioStream, VolumeMixer};
+use std::process::Command;
+
+pub struct PulseMixer;
+
+impl PulseMixer {
+    /// Returns `None` if `pactl` is missing or no server answers.
+    pub fn connect() -> Option<Self> {
+        let output = pactl(&["info"]).ok()?;
+        if output.is_empty() {
+            return None;
+        }
+        Some(Self)
+    }
+}
+
+/// Run `pactl` with the C locale so its output can be parsed.
+fn pactl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| format!("Failed to run pactl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "pactl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `pactl list sink-inputs`. Each block starts with `Sink Input #<index>`,
+/// followed by a `Volume:` line and a `Properties:` section.
+fn parse_sink_inputs(output: &str) -> Vec<(AudioStream, Option<u32>)> {
+    let mut streams = Vec::new();
+    let mut current: Option<(AudioStream, Option<u32>)> = None;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(index) = line.strip_prefix("Sink Input #") {
+            streams.extend(current.take());
+            current = Some((
+                AudioStream {
+                    id: index.trim().to_string(),
+                    name: String::new(),
+                    volume: Vec::new(),
+                },
+                None,
+            ));
+            continue;
+        }
+        let Some((stream, pid)) = current.as_mut() else { continue };
+
+        if let Some(volume) = line.strip_prefix("Volume:") {
+            // "front-left: 32768 /  50% / -18.06 dB,   front-right: 32768 /  50% / -18.06 dB"
+            stream.volume = volume
+                .split(',')
+                .filter_map(|channel| {
+                    let (_, value) = channel.split_once(':')?;
+                    value.split('/').next()?.trim().parse::<f64>().ok()
+                })
+                .collect();
+        } else if let Some((key, value)) = line.split_once(" = ") {
+            let value = value.trim_matches('"');
+            match key {
+                "application.name" => stream.name = value.to_string(),
+                "application.process.binary" if stream.name.is_empty() => {
+                    stream.name = value.to_string()
+                }
+                "application.process.id" => *pid = value.parse().ok(),
+                _ => {}
+            }
+        }
+    }
+    streams.extend(current);
+    streams
+}
+
+/// The streams in `pactl list sink-inputs` output that can be ducked: those with
+/// a volume, other than the ones of process `own_pid`.
+fn other_streams(output: &str, own_pid: u32) -> Vec<AudioStream> {
+    parse_sink_inputs(output)
+        .into_iter()
+        .filter(|(stream, pid)| *pid != Some(own_pid) && !stream.volume.is_empty())
+        .map(|(stream, _)| stream)
+        .collect()
+}
+
+impl VolumeMixer for PulseMixer {
+    fn name(&self) -> &'static str {
+        "pulse"
+    }
+
+    fn list_streams(&self) -> Result<Vec<AudioStream>, String> {
+        let output = pactl(&["list", "sink-inputs"])?;
+        Ok(other_streams(&output, std::process::id()))
+    }
+
+    fn set_volume(&self, id: &str, volume: &[f64]) -> Result<(), String> {
+        let values: Vec<String> = volume
+            .iter()
+            .map(|value| format!("{}", value.round() as u32))
+            .collect();
+        let mut args = vec!["set-sink-input-volume", id];
+        args.extend(values.iter().map(String::as_str));
+        pactl(&args).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `LC_ALL=C pactl list sink-inputs` on PipeWire, trimmed
+    const SINK_INPUTS: &str = "\
+Sink Input #61
+\tDriver: PipeWire
+\tOwner Module: n/a
+\tClient: 60
+\tSink: 52
+\tSample Specification: float32le 2ch 48000Hz
+\tChannel Map: front-left,front-right
+\tFormat: pcm, format.sample_format = \"\\\"float32le\\\"\"  format.rate = \"48000\"
+\tCorked: no
+\tMute: no
+\tVolume: front-left: 65536 / 100% / 0.00 dB,   front-right: 45875 /  70% / -9.29 dB
+\t        balance -0.30
+\tBuffer Latency: 0 usec
+\tSink Latency: 0 usec
+\tResample method: PipeWire
+\tProperties:
+\t\tclient.api = \"pipewire-pulse\"
+\t\tapplication.name = \"Firefox\"
+\t\tapplication.process.id = \"4242\"
+\t\tapplication.process.binary = \"firefox\"
+\t\tmedia.name = \"AudioStream\"
+
+Sink Input #73
+\tDriver: PipeWire
+\tSink: 52
+\tSample Specification: s16le 1ch 44100Hz
+\tChannel Map: mono
+\tVolume: mono: 32768 /  50% / -18.06 dB
+\t        balance 0.00
+\tProperties:
+\t\tapplication.process.id = \"5151\"
+\t\tapplication.process.binary = \"mpv\"
+
+Sink Input #80
+\tDriver: PipeWire
+\tVolume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB
+\t        balance 0.00
+\tProperties:
+\t\tapplication.name = \"Break Timer\"
+\t\tapplication.process.id = \"999\"
+";
+
+    #[test]
+    fn parses_each_channel_and_the_owner() {
+        let streams = parse_sink_inputs(SINK_INPUTS);
+        let summary: Vec<(&str, &str, &[f64], Option<u32>)> = streams
+            .iter()
+            .map(|(stream, pid)| (stream.id.as_str(), stream.name.as_str(), stream.volume.as_slice(), *pid))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("61", "Firefox", &[65536.0, 45875.0][..], Some(4242)),
+                // Named after the binary without an application name
+                ("73", "mpv", &[32768.0][..], Some(5151)),
+                ("80", "Break Timer", &[65536.0, 65536.0][..], Some(999)),
+            ]
+        );
+    }
+
+    #[test]
+    fn own_streams_are_left_out() {
+        let ids: Vec<String> = other_streams(SINK_INPUTS, 999).into_iter().map(|stream| stream.id).collect();
+        assert_eq!(ids, vec!["61", "73"]);
+    }
+
+    #[test]
+    fn streams_without_a_volume_are_left_out() {
+        let output = "Sink Input #5\n\tDriver: PipeWire\n\tProperties:\n\t\tapplication.name = \"Passthrough\"\n";
+        assert_eq!(parse_sink_inputs(output).len(), 1);
+        assert!(other_streams(output, 1).is_empty());
+    }
+}
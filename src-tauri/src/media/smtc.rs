@@ -0,0 +1,89 @@
+//! Windows System Media Transport Controls sessions (browsers, Spotify, ...).
+//!
+//! Sessions are addressed by their `SourceAppUserModelId`.
+
+use super::{MediaBackend, MediaSession};
+use windows::Media::Control::{
+    GlobalSystemMediaTransportControlsSession,
+    GlobalSystemMediaTransportControlsSessionManager,
+    GlobalSystemMediaTransportControlsSessionPlaybackStatus,
+};
+
+pub struct SmtcBackend;
+
+impl SmtcBackend {
+    fn sessions(&self) -> Result<Vec<(String, GlobalSystemMediaTransportControlsSession)>, String> {
+        let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+            .and_then(|op| op.get())
+            .map_err(|e| format!("SMTC manager failed: {:?}", e))?;
+        let sessions_view = manager
+            .GetSessions()
+            .map_err(|e| format!("GetSessions failed: {:?}", e))?;
+        let count = sessions_view.Size().unwrap_or(0);
+
+        let mut sessions = Vec::new();
+        for i in 0..count {
+            let session = match sessions_view.GetAt(i) { Ok(s) => s, Err(_) => continue };
+            let source = session.SourceAppUserModelId()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|_| format!("session_{}", i));
+            sessions.push((source, session));
+        }
+        Ok(sessions)
+    }
+
+    fn find(&self, id: &str) -> Result<GlobalSystemMediaTransportControlsSession, String> {
+        self.sessions()?
+            .into_iter()
+            .find(|(source, _)| source == id)
+            .map(|(_, session)| session)
+            .ok_or_else(|| format!("SMTC session {} not found", id))
+    }
+}
+
+/// `Spotify.exe` → `Spotify`, `Microsoft.ZuneMusic_8wekyb3d8bbwe!Microsoft.ZuneMusic` → `Microsoft.ZuneMusic`
+fn app_name(source: &str) -> String {
+    let name = source.rsplit('!').next().unwrap_or(source);
+    name.strip_suffix(".exe").unwrap_or(name).to_string()
+}
+
+impl MediaBackend for SmtcBackend {
+    fn name(&self) -> &'static str {
+        "smtc"
+    }
+
+    fn list_sessions(&self) -> Result<Vec<MediaSession>, String> {
+        Ok(self
+            .sessions()?
+            .into_iter()
+            .map(|(source, session)| {
+                let playing = session.GetPlaybackInfo()
+                    .and_then(|info| info.PlaybackStatus())
+                    .map(|st| st == GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing)
+                    .unwrap_or(false);
+                MediaSession {
+                    name: app_name(&source),
+                    id: source,
+                    backend: self.name(),
+                    playing,
+                }
+            })
+            .collect())
+    }
+
+    fn pause(&self, id: &str) -> Result<(), String> {
+        self.find(id)?
+            .TryPauseAsync()
+            .and_then(|op| op.get())
+            .map(|_| ())
+            .map_err(|e| format!("TryPauseAsync on {} failed: {:?}", id, e))
+    }
+
+    fn resume(&self, id: &str) -> Result<(), String> {
+        self.find(id)?
+            .TryPlayAsync()
+            .and_then(|op| op.get())
+            .map(|_| ())
+            .map_err(|e| format!("TryPlayAsync on {} failed: {:?}", id, e))
+    }
+}
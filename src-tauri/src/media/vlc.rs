@@ -0,0 +1,160 @@
+//! VLC on Windows, driven with `WM_APPCOMMAND`.
+//!
+//! VLC never registers with SMTC. Its playing state is read from its Core Audio
+//! session: a playing VLC has an Active session, a paused one an Inactive session.
+
+use super::{MediaBackend, MediaSession};
+use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{
+    EnumWindows, GetWindowThreadProcessId, IsWindowVisible, SendMessageTimeoutW,
+    APPCOMMAND_MEDIA_PAUSE, APPCOMMAND_MEDIA_PLAY, SMTO_ABORTIFHUNG, WM_APPCOMMAND,
+};
+
+const SESSION_ID: &str = "vlc";
+
+pub struct VlcBackend;
+
+struct SearchData { pid: u32, hwnd: HWND }
+
+unsafe extern "system" fn find_vlc_visible_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let data = &mut *(lparam as *mut SearchData);
+    let mut wpid: u32 = 0;
+    GetWindowThreadProcessId(hwnd, &mut wpid);
+    if wpid == data.pid && IsWindowVisible(hwnd) != 0 {
+        data.hwnd = hwnd;
+        return 0;
+    }
+    TRUE
+}
+
+fn vlc_pid() -> Option<u32> {
+    use sysinfo::System;
+    let sys = System::new_all();
+    sys.processes().values()
+        .find(|p| p.name().to_lowercase() == "vlc.exe")
+        .map(|p| p.pid().as_u32())
+}
+
+fn vlc_window(pid: u32) -> Option<HWND> {
+    let mut data = SearchData { pid, hwnd: std::ptr::null_mut() };
+    unsafe { EnumWindows(Some(find_vlc_visible_window), &mut data as *mut SearchData as LPARAM); }
+    if data.hwnd.is_null() { None } else { Some(data.hwnd) }
+}
+
+fn send_appcommand(appcommand: i16) -> Result<(), String> {
+    let pid = vlc_pid().ok_or("VLC not running")?;
+    let hwnd = vlc_window(pid).ok_or("VLC running but no visible window found")?;
+    let lparam_val = ((appcommand as isize) << 16) as isize;
+    let mut result: usize = 0;
+    let ret = unsafe {
+        SendMessageTimeoutW(
+            hwnd, WM_APPCOMMAND, hwnd as usize,
+            lparam_val, SMTO_ABORTIFHUNG, 1000, &mut result,
+        )
+    };
+    println!("✅ WM_APPCOMMAND {} sent to VLC (ret={} result={})", appcommand, ret, result);
+    Ok(())
+}
+
+/// Check if VLC is currently playing by inspecting its Core Audio session state.
+/// Returns false if VLC has no audio session.
+fn is_playing_via_audio(vlc_pid: u32) -> bool {
+    use windows::Win32::Media::Audio::{
+        eMultimedia, eRender, IMMDeviceEnumerator, MMDeviceEnumerator,
+        IAudioSessionControl2, IAudioSessionManager2,
+        AudioSessionStateActive,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+    use windows::core::ComInterface;
+
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let enumerator: IMMDeviceEnumerator =
+            match CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL) {
+                Ok(e) => e,
+                Err(e) => { println!("⚠️ CoCreateInstance failed: {:?}", e); return false; }
+            };
+
+        let device = match enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia) {
+            Ok(d) => d,
+            Err(e) => { println!("⚠️ GetDefaultAudioEndpoint failed: {:?}", e); return false; }
+        };
+
+        // IMMDevice::Activate is generic in windows 0.52 — type inferred from return type
+        let session_manager: IAudioSessionManager2 =
+            match device.Activate(CLSCTX_ALL, None) {
+                Ok(m) => m,
+                Err(e) => { println!("⚠️ Activate IAudioSessionManager2 failed: {:?}", e); return false; }
+            };
+
+        let session_enum = match session_manager.GetSessionEnumerator() {
+            Ok(e) => e,
+            Err(e) => { println!("⚠️ GetSessionEnumerator failed: {:?}", e); return false; }
+        };
+
+        let count = match session_enum.GetCount() {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+
+        for i in 0..count {
+            let session = match session_enum.GetSession(i) {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            let session2: IAudioSessionControl2 = match session.cast() {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            let pid = match session2.GetProcessId() {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
+            if pid == vlc_pid {
+                let state = match session2.GetState() {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+                let is_active = state == AudioSessionStateActive;
+                println!("  VLC audio session state: {:?} → playing={}", state, is_active);
+                return is_active;
+            }
+        }
+        println!("  No audio session found for VLC PID {} (no audio or muted)", vlc_pid);
+        false
+    }
+}
+
+impl MediaBackend for VlcBackend {
+    fn name(&self) -> &'static str {
+        "vlc"
+    }
+
+    /// A single session while VLC has a visible window, none otherwise.
+    fn list_sessions(&self) -> Result<Vec<MediaSession>, String> {
+        let pid = match vlc_pid() {
+            Some(pid) => pid,
+            None => return Ok(Vec::new()),
+        };
+        if vlc_window(pid).is_none() {
+            println!("⚠️ VLC running but no visible window found");
+            return Ok(Vec::new());
+        }
+        Ok(vec![MediaSession {
+            id: SESSION_ID.to_string(),
+            name: "VLC".to_string(),
+            backend: self.name(),
+            playing: is_playing_via_audio(pid),
+        }])
+    }
+
+    fn pause(&self, _id: &str) -> Result<(), String> {
+        send_appcommand(APPCOMMAND_MEDIA_PAUSE)
+    }
+
+    fn resume(&self, _id: &str) -> Result<(), String> {
+        send_appcommand(APPCOMMAND_MEDIA_PLAY)
+    }
+}
@@ -0,0 +1,21 @@
+//! Per-application stream volume, used to duck media instead of pausing it.
+
+/// One application's audio stream as seen by a mixer.
+#[derive(Clone, Debug)]
+pub struct AudioStream {
+    /// Mixer-specific handle (sink-input index, session instance id, ...).
+    pub id: String,
+    pub name: String,
+    /// Per-channel volume in the mixer's native unit, so it can be restored exactly.
+    pub volume: Vec<f64>,
+}
+
+/// A system mixer that can read and set the volume of individual streams.
+pub trait VolumeMixer: Send {
+    fn name(&self) -> &'static str;
+
+    /// Streams of other applications; our own stream is never listed.
+    fn list_streams(&self) -> Result<Vec<AudioStream>, String>;
+
+    fn set_volume(&self, id: &str, volume: &[f64]) -> Result<(), String>;
+}
@@ -0,0 +1,608 @@
+use serde::Serialize;
+use sysinfo::System;
+
+use crate::meeting_rules::{self, CompiledRules};
+use crate::window_titles;
+
+/// A meeting is assumed once the combined confidence reaches this value.
+pub const MEETING_THRESHOLD: f32 = 0.5;
+
+/// Browsers, whose camera/mic use is most likely a web meeting.
+const BROWSER_PROCESSES: &[&str] = &[
+    "chrome",
+    "chromium",
+    "firefox",
+    "msedge",
+    "opera",
+    "brave",
+    "vivaldi",
+    "iexplore",
+];
+
+/// Weights of the individual signals, combined as independent evidence:
+/// confidence = 1 - Π(1 - weight). A meeting client being open is a weak hint
+/// (Teams idles in the tray all day); using the camera or microphone is a
+/// strong one.
+const WEIGHT_MEETING_APP: f32 = 0.35;
+const WEIGHT_BROWSER_TITLE: f32 = 0.4;
+const WEIGHT_CAMERA: f32 = 0.6;
+const WEIGHT_MICROPHONE: f32 = 0.45;
+/// Camera or microphone held by a meeting client or browser.
+const WEIGHT_DEVICE_BY_MEETING_APP: f32 = 0.7;
+
+/// Weight of a meeting app or title match. Where camera and microphone use
+/// can't be seen, the match is all the evidence there is and counts as a
+/// meeting on its own, as it did before devices were checked.
+fn presence_weight(weight: f32, devices_visible: bool) -> f32 {
+    if devices_visible {
+        weight
+    } else {
+        weight.max(MEETING_THRESHOLD)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    MeetingApp,
+    BrowserTitle,
+    Camera,
+    Microphone,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MeetingSignal {
+    pub kind: SignalKind,
+    /// Process name or window title that produced the signal.
+    pub detail: String,
+    pub weight: f32,
+}
+
+/// Everything the detector found, with the combined confidence.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MeetingAssessment {
+    pub confidence: f32,
+    pub signals: Vec<MeetingSignal>,
+}
+
+impl MeetingAssessment {
+    pub fn from_signals(signals: Vec<MeetingSignal>) -> Self {
+        let miss = signals
+            .iter()
+            .fold(1.0_f32, |miss, signal| miss * (1.0 - signal.weight.clamp(0.0, 1.0)));
+        Self {
+            confidence: 1.0 - miss,
+            signals,
+        }
+    }
+
+    pub fn is_meeting(&self) -> bool {
+        self.confidence >= MEETING_THRESHOLD
+    }
+
+    /// One line for logs and postponement records, e.g.
+    /// "Meeting detected (82% confidence: zoom, zoom (pid 4242))".
+    pub fn summary(&self) -> String {
+        let details: Vec<&str> = self.signals.iter().map(|s| s.detail.as_str()).collect();
+        format!(
+            "Meeting detected ({:.0}% confidence: {})",
+            self.confidence * 100.0,
+            details.join(", ")
+        )
+    }
+}
+
+fn process_base_name(name: &str) -> String {
+    let name = name.to_lowercase();
+    name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+}
+
+fn is_browser_process(name: &str) -> bool {
+    let name = process_base_name(name);
+    BROWSER_PROCESSES.iter().any(|browser| name.starts_with(browser))
+}
+
+/// A process holding the camera or microphone.
+#[derive(Clone, Debug)]
+pub struct DeviceUser {
+    /// `None` where the platform doesn't report it.
+    pub pid: Option<u32>,
+    pub name: String,
+}
+
+fn device_signal(rules: &CompiledRules, kind: SignalKind, user: &DeviceUser) -> MeetingSignal {
+    let weight = if rules.is_meeting_process(&user.name) || is_browser_process(&user.name) {
+        WEIGHT_DEVICE_BY_MEETING_APP
+    } else if kind == SignalKind::Camera {
+        WEIGHT_CAMERA
+    } else {
+        WEIGHT_MICROPHONE
+    };
+    let detail = match user.pid {
+        Some(pid) => format!("{} (pid {})", user.name, pid),
+        None => user.name.clone(),
+    };
+    MeetingSignal { kind, detail, weight }
+}
+
+/// One rule and what it matched, for `test_meeting_rules`.
+#[derive(Clone, Debug, Serialize)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub target: String,
+}
+
+/// Which rules matched which processes and windows right now.
+#[derive(Clone, Debug, Serialize)]
+pub struct RulesReport {
+    /// Why `meeting_rules.json` was rejected, if it was.
+    pub rules_error: Option<String>,
+    pub process_matches: Vec<RuleMatch>,
+    pub title_matches: Vec<RuleMatch>,
+    pub exclusions: Vec<RuleMatch>,
+    pub assessment: MeetingAssessment,
+}
+
+fn process_names(sys: &System) -> Vec<String> {
+    let mut names: Vec<String> = sys
+        .processes()
+        .values()
+        .map(|proc| proc.name().to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Window titles worth matching: only while a browser runs, so a stray
+/// "discord" in an editor title doesn't count.
+fn candidate_titles(sys: &System) -> Vec<String> {
+    let browser_running = sys
+        .processes()
+        .values()
+        .any(|proc| is_browser_process(proc.name()));
+    if browser_running {
+        window_titles::visible_window_titles()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Run every check against the rules in `meeting_rules.json` and combine the results.
+pub fn detect() -> MeetingAssessment {
+    detect_with(&meeting_rules::current())
+}
+
+pub fn detect_with(rules: &CompiledRules) -> MeetingAssessment {
+    let mut signals = Vec::new();
+    let sys = System::new_all();
+    let devices = [
+        (SignalKind::Camera, camera_users()),
+        (SignalKind::Microphone, microphone_users()),
+    ];
+    let devices_visible = devices.iter().any(|(_, users)| users.is_some());
+
+    // One app is enough; several helpers of the same client shouldn't add up
+    if let Some(app) = process_names(&sys)
+        .into_iter()
+        .find(|name| rules.is_meeting_process(name))
+    {
+        signals.push(MeetingSignal {
+            kind: SignalKind::MeetingApp,
+            detail: app,
+            weight: presence_weight(WEIGHT_MEETING_APP, devices_visible),
+        });
+    }
+
+    if let Some(title) = candidate_titles(&sys)
+        .into_iter()
+        .find(|title| rules.title_rule(title).is_some())
+    {
+        println!("🔍 Meeting detected in browser window: {}", title);
+        signals.push(MeetingSignal {
+            kind: SignalKind::BrowserTitle,
+            detail: title,
+            weight: presence_weight(WEIGHT_BROWSER_TITLE, devices_visible),
+        });
+    }
+
+    // Strongest device user of each kind only, for the same reason
+    for (kind, users) in devices {
+        let strongest = users
+            .unwrap_or_default()
+            .iter()
+            .filter(|user| !rules.is_excluded_process(&user.name))
+            .map(|user| device_signal(rules, kind, user))
+            .max_by(|a, b| a.weight.total_cmp(&b.weight));
+        signals.extend(strongest);
+    }
+
+    let assessment = MeetingAssessment::from_signals(signals);
+    println!(
+        "🔍 Meeting confidence {:.2} from {} signal(s): {:?}",
+        assessment.confidence,
+        assessment.signals.len(),
+        assessment.signals.iter().map(|s| &s.detail).collect::<Vec<_>>()
+    );
+    assessment
+}
+
+/// Report every process and window title the rules match or exclude.
+pub fn test_rules(rules: &CompiledRules, rules_error: Option<String>) -> RulesReport {
+    let sys = System::new_all();
+    let mut process_matches = Vec::new();
+    let mut title_matches = Vec::new();
+    let mut exclusions = Vec::new();
+
+    for name in process_names(&sys) {
+        if rules.is_excluded_process(&name) {
+            exclusions.push(RuleMatch {
+                rule: format!("exclude_processes: {}", name),
+                target: name,
+            });
+        } else if rules.is_meeting_process(&name) {
+            process_matches.push(RuleMatch {
+                rule: format!("processes: {}", name),
+                target: name,
+            });
+        } else if rules.is_presenting_process(&name) {
+            process_matches.push(RuleMatch {
+                rule: format!("presenting_processes: {}", name),
+                target: name,
+            });
+        }
+    }
+
+    for title in candidate_titles(&sys) {
+        if let Some(rule) = rules.title_exclusion(&title) {
+            exclusions.push(RuleMatch {
+                rule: format!("exclude_titles: {}", rule.source),
+                target: title,
+            });
+        } else if let Some(rule) = rules.title_rule(&title) {
+            title_matches.push(RuleMatch {
+                rule: rule.source.clone(),
+                target: title,
+            });
+        }
+    }
+
+    RulesReport {
+        rules_error,
+        process_matches,
+        title_matches,
+        exclusions,
+        assessment: detect_with(rules),
+    }
+}
+
+/// Processes with an open `/dev/video*` handle, found by walking `/proc/*/fd`.
+/// Processes of other users can't be inspected and are skipped. `None` if
+/// `/proc` can't be read.
+#[cfg(target_os = "linux")]
+pub fn camera_users() -> Option<Vec<DeviceUser>> {
+    use std::fs;
+
+    let own_pid = std::process::id();
+    let mut users = Vec::new();
+    let entries = fs::read_dir("/proc").ok()?;
+    for entry in entries.flatten() {
+        let pid: u32 = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            Some(pid) if pid != own_pid => pid,
+            _ => continue,
+        };
+        let fds = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        let uses_camera = fds.flatten().any(|fd| {
+            fs::read_link(fd.path())
+                .map(|target| target.to_string_lossy().starts_with("/dev/video"))
+                .unwrap_or(false)
+        });
+        if uses_camera {
+            let name = fs::read_to_string(entry.path().join("comm"))
+                .map(|comm| comm.trim().to_string())
+                .unwrap_or_else(|_| format!("pid {}", pid));
+            users.push(DeviceUser { pid: Some(pid), name });
+        }
+    }
+    Some(users)
+}
+
+/// Processes recording from a microphone, from PulseAudio/PipeWire source-outputs.
+/// Corked (paused) streams, monitor sources and level meters are ignored.
+/// `None` without `pactl`.
+#[cfg(target_os = "linux")]
+pub fn microphone_users() -> Option<Vec<DeviceUser>> {
+    use std::process::Command;
+
+    let run = |args: &[&str]| -> Option<String> {
+        let output = Command::new("pactl").args(args).env("LC_ALL", "C").output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    let monitors: Vec<String> = run(&["list", "short", "sources"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            let index = columns.next()?;
+            let name = columns.next()?;
+            name.ends_with(".monitor").then(|| index.to_string())
+        })
+        .collect();
+
+    let output = run(&["list", "source-outputs"])?;
+    let own_pid = std::process::id();
+    let users = parse_source_outputs(&output)
+        .into_iter()
+        .filter(|output| !output.corked && !monitors.contains(&output.source))
+        .filter(|output| output.app_id.as_deref() != Some("org.PulseAudio.pavucontrol"))
+        .filter_map(|output| {
+            let pid = output.pid?;
+            (pid != own_pid).then_some(DeviceUser {
+                pid: Some(pid),
+                name: output.name,
+            })
+        })
+        .collect();
+    Some(users)
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct SourceOutput {
+    source: String,
+    corked: bool,
+    name: String,
+    app_id: Option<String>,
+    pid: Option<u32>,
+}
+
+/// Parse `pactl list source-outputs`: blocks start with `Source Output #<index>`
+/// and carry `Source:`, `Corked:` and a `Properties:` section.
+#[cfg(target_os = "linux")]
+fn parse_source_outputs(output: &str) -> Vec<SourceOutput> {
+    let mut outputs = Vec::new();
+    let mut current: Option<SourceOutput> = None;
+    for line in output.lines() {
+        let line = line.trim();
+        if line.starts_with("Source Output #") {
+            outputs.extend(current.take());
+            current = Some(SourceOutput::default());
+            continue;
+        }
+        let Some(out) = current.as_mut() else { continue };
+        if let Some(source) = line.strip_prefix("Source:") {
+            out.source = source.trim().to_string();
+        } else if let Some(corked) = line.strip_prefix("Corked:") {
+            out.corked = corked.trim() == "yes";
+        } else if let Some((key, value)) = line.split_once(" = ") {
+            let value = value.trim_matches('"');
+            match key {
+                "application.process.binary" => out.name = value.to_string(),
+                "application.name" if out.name.is_empty() => out.name = value.to_string(),
+                "application.id" => out.app_id = Some(value.to_string()),
+                "application.process.id" => out.pid = value.parse().ok(),
+                _ => {}
+            }
+        }
+    }
+    outputs.extend(current);
+    outputs
+}
+
+#[cfg(windows)]
+pub fn camera_users() -> Option<Vec<DeviceUser>> {
+    consent_store::users_of("webcam")
+}
+
+#[cfg(windows)]
+pub fn microphone_users() -> Option<Vec<DeviceUser>> {
+    consent_store::users_of("microphone")
+}
+
+/// Camera and microphone use from the privacy consent store in the registry.
+/// Windows keeps a key per app under each capability, with the FILETIMEs
+/// `LastUsedTimeStart` and `LastUsedTimeStop`; the stop time is 0 while the
+/// app holds the device. Desktop apps sit under `NonPackaged`, keyed by their
+/// path with `#` for `\`. Process ids aren't recorded.
+#[cfg(windows)]
+mod consent_store {
+    use std::ptr;
+    use winapi::shared::minwindef::{DWORD, HKEY};
+    use winapi::shared::winerror::ERROR_SUCCESS;
+    use winapi::um::winnt::KEY_READ;
+    use winapi::um::winreg::{RegCloseKey, RegEnumKeyExW, RegOpenKeyExW, RegQueryValueExW, HKEY_CURRENT_USER};
+
+    use super::DeviceUser;
+
+    const CONSENT_STORE: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+
+    struct RegKey(HKEY);
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    impl RegKey {
+        fn open(parent: HKEY, path: &str) -> Option<Self> {
+            let path = wide(path);
+            let mut key: HKEY = ptr::null_mut();
+            let status = unsafe { RegOpenKeyExW(parent, path.as_ptr(), 0, KEY_READ, &mut key) };
+            (status == ERROR_SUCCESS as i32).then_some(Self(key))
+        }
+
+        fn subkeys(&self) -> Vec<String> {
+            let mut names = Vec::new();
+            let mut buffer = [0u16; 512];
+            for index in 0.. {
+                let mut len = buffer.len() as DWORD;
+                let status = unsafe {
+                    RegEnumKeyExW(
+                        self.0,
+                        index,
+                        buffer.as_mut_ptr(),
+                        &mut len,
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                    )
+                };
+                if status != ERROR_SUCCESS as i32 {
+                    break;
+                }
+                names.push(String::from_utf16_lossy(&buffer[..len as usize]));
+            }
+            names
+        }
+
+        fn qword(&self, name: &str) -> Option<u64> {
+            let name = wide(name);
+            let mut value: u64 = 0;
+            let mut size = std::mem::size_of::<u64>() as DWORD;
+            let status = unsafe {
+                RegQueryValueExW(
+                    self.0,
+                    name.as_ptr(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    &mut value as *mut u64 as *mut u8,
+                    &mut size,
+                )
+            };
+            (status == ERROR_SUCCESS as i32).then_some(value)
+        }
+
+        /// Started using the device and hasn't stopped yet.
+        fn in_use(&self) -> bool {
+            self.qword("LastUsedTimeStart").unwrap_or(0) != 0 && self.qword("LastUsedTimeStop") == Some(0)
+        }
+    }
+
+    impl Drop for RegKey {
+        fn drop(&mut self) {
+            unsafe {
+                RegCloseKey(self.0);
+            }
+        }
+    }
+
+    /// `C:#Program Files#Zoom#bin#Zoom.exe` → `Zoom.exe`;
+    /// `MicrosoftTeams_8wekyb3d8bbwe` → `MicrosoftTeams`.
+    fn app_name(key: &str, packaged: bool) -> String {
+        let name = if packaged {
+            key.split('_').next().unwrap_or(key)
+        } else {
+            key.rsplit('#').next().unwrap_or(key)
+        };
+        name.to_string()
+    }
+
+    /// Apps using `capability` right now, or `None` if the store can't be read.
+    pub fn users_of(capability: &str) -> Option<Vec<DeviceUser>> {
+        let store = RegKey::open(HKEY_CURRENT_USER, &format!("{}\\{}", CONSENT_STORE, capability))?;
+        let mut users = Vec::new();
+        for name in store.subkeys() {
+            let Some(key) = RegKey::open(store.0, &name) else { continue };
+            if name == "NonPackaged" {
+                for app in key.subkeys() {
+                    if RegKey::open(key.0, &app).is_some_and(|app_key| app_key.in_use()) {
+                        users.push(DeviceUser { pid: None, name: app_name(&app, false) });
+                    }
+                }
+            } else if key.in_use() {
+                users.push(DeviceUser { pid: None, name: app_name(&name, true) });
+            }
+        }
+        Some(users)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn camera_users() -> Option<Vec<DeviceUser>> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn microphone_users() -> Option<Vec<DeviceUser>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(kind: SignalKind, weight: f32) -> MeetingSignal {
+        MeetingSignal {
+            kind,
+            detail: format!("{:?}", kind),
+            weight,
+        }
+    }
+
+    #[test]
+    fn meeting_app_alone_is_a_meeting_only_without_device_signals() {
+        let app = |devices_visible| {
+            MeetingAssessment::from_signals(vec![signal(
+                SignalKind::MeetingApp,
+                presence_weight(WEIGHT_MEETING_APP, devices_visible),
+            )])
+        };
+        assert!(!app(true).is_meeting());
+        assert!(app(false).is_meeting());
+    }
+
+    #[test]
+    fn browser_title_alone_is_a_meeting_only_without_device_signals() {
+        let title = |devices_visible| {
+            MeetingAssessment::from_signals(vec![signal(
+                SignalKind::BrowserTitle,
+                presence_weight(WEIGHT_BROWSER_TITLE, devices_visible),
+            )])
+        };
+        assert!(!title(true).is_meeting());
+        assert!(title(false).is_meeting());
+    }
+
+    #[test]
+    fn meeting_app_with_microphone_is_a_meeting() {
+        let assessment = MeetingAssessment::from_signals(vec![
+            signal(SignalKind::MeetingApp, WEIGHT_MEETING_APP),
+            signal(SignalKind::Microphone, WEIGHT_MICROPHONE),
+        ]);
+        assert!(assessment.is_meeting());
+        assert!(assessment.confidence < 1.0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn source_outputs_are_parsed_per_block() {
+        let output = "Source Output #12\n\
+            \tSource: 3\n\
+            \tCorked: no\n\
+            \tProperties:\n\
+            \t\tapplication.name = \"Firefox\"\n\
+            \t\tapplication.process.id = \"4242\"\n\
+            \t\tapplication.process.binary = \"firefox\"\n\
+            Source Output #13\n\
+            \tSource: 1\n\
+            \tCorked: yes\n\
+            \tProperties:\n\
+            \t\tapplication.name = \"Recorder\"\n";
+        let outputs = parse_source_outputs(output);
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].name, "firefox");
+        assert_eq!(outputs[0].pid, Some(4242));
+        assert_eq!(outputs[0].source, "3");
+        assert!(!outputs[0].corked);
+        assert_eq!(outputs[1].name, "Recorder");
+        assert_eq!(outputs[1].pid, None);
+        assert!(outputs[1].corked);
+    }
+}
@@ -0,0 +1,394 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use crate::settings;
+
+const RULES_FILE: &str = "meeting_rules.json";
+
+/// User-editable meeting detection rules, stored as `meeting_rules.json` in the
+/// app data directory. Written with the built-in defaults on first start.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeetingRules {
+    /// Meeting client process names, compared case-insensitively without ".exe".
+    pub processes: Vec<String>,
+    /// Regular expressions matched case-insensitively against window titles.
+    pub title_patterns: Vec<String>,
+    /// Web meeting addresses such as `meet.google.com/*`. `*` matches anything,
+    /// and a leading `*.` or trailing `/*` also nothing; matched against window
+    /// titles, which often contain the page address.
+    pub url_patterns: Vec<String>,
+    /// Process names never treated as meeting clients.
+    pub exclude_processes: Vec<String>,
+    /// Regular expressions for window titles that never count as a meeting.
+    pub exclude_titles: Vec<String>,
+    /// Presenting and screen recording process names, handled by the
+    /// "presenting" break setting rather than as meetings.
+    pub presenting_processes: Vec<String>,
+}
+
+impl Default for MeetingRules {
+    fn default() -> Self {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        Self {
+            processes: strings(&[
+                "zoom",
+                "teams",
+                "ms-teams",
+                "teams-for-linux",
+                "skype",
+                "skypeforlinux",
+                "webex",
+                "meet",
+            ]),
+            title_patterns: strings(&[
+                "google meet",
+                "zoom meeting",
+                "microsoft teams",
+                "webex meeting",
+                "gotomeeting",
+                "bluejeans",
+                "discord",
+                "slack call",
+                "skype",
+                "hangouts",
+                "jitsi meet",
+                "bigbluebutton",
+                "ringcentral meetings",
+                "cisco webex",
+                "amazon chime",
+                "facebook messenger rooms",
+                "whatsapp web",
+            ]),
+            url_patterns: strings(&[
+                "meet.google.com/*",
+                "teams.microsoft.com/*",
+                "*.webex.com/*",
+                "join.me/*",
+                "whereby.com/*",
+                "8x8.vc/*",
+            ]),
+            exclude_processes: Vec::new(),
+            exclude_titles: Vec::new(),
+            presenting_processes: strings(&[
+                "obs",
+                "obs64",
+                "obs32",
+                "simplescreenrecorder",
+                "kazam",
+                "vokoscreenng",
+                "kooha",
+                "gpu-screen-recorder",
+                "camrecorder",
+                "pdfpc",
+                "pympress",
+            ]),
+        }
+    }
+}
+
+/// One rule, ready for matching.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    /// The entry as written in the rules file, for reports.
+    pub source: String,
+    regex: Regex,
+}
+
+impl Rule {
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+}
+
+/// Validated rules with every pattern compiled.
+#[derive(Clone, Debug)]
+pub struct CompiledRules {
+    pub processes: Vec<String>,
+    pub exclude_processes: Vec<String>,
+    pub presenting_processes: Vec<String>,
+    pub titles: Vec<Rule>,
+    pub exclude_titles: Vec<Rule>,
+}
+
+fn process_base_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+/// `*.webex.com/*` → `(.*\.)?webex\.com(/.*)?`. A leading `*.` and a trailing
+/// `/*` are optional, as titles often show the bare host or no path.
+fn url_to_regex(pattern: &str) -> String {
+    let (subdomain, rest) = match pattern.strip_prefix("*.") {
+        Some(rest) => (r"(.*\.)?", rest),
+        None => ("", pattern),
+    };
+    let (rest, path) = match rest.strip_suffix("/*") {
+        Some(rest) => (rest, "(/.*)?"),
+        None => (rest, ""),
+    };
+    let host = rest.split('*').map(regex::escape).collect::<Vec<_>>().join(".*");
+    format!("{}{}{}", subdomain, host, path)
+}
+
+impl MeetingRules {
+    /// Check every entry and compile the patterns. All problems are reported at once.
+    pub fn compile(&self) -> Result<CompiledRules, String> {
+        let mut errors = Vec::new();
+
+        let mut names = |field: &str, entries: &[String]| -> Vec<String> {
+            let mut names = Vec::new();
+            for (i, entry) in entries.iter().enumerate() {
+                if entry.trim().is_empty() {
+                    errors.push(format!("{}[{}] is empty", field, i));
+                } else {
+                    names.push(process_base_name(entry));
+                }
+            }
+            names
+        };
+        let processes = names("processes", &self.processes);
+        let exclude_processes = names("exclude_processes", &self.exclude_processes);
+        let presenting_processes = names("presenting_processes", &self.presenting_processes);
+
+        let mut rules = |field: &str, entries: &[String], to_regex: fn(&str) -> String| -> Vec<Rule> {
+            let mut rules = Vec::new();
+            for (i, entry) in entries.iter().enumerate() {
+                if entry.trim().is_empty() {
+                    errors.push(format!("{}[{}] is empty", field, i));
+                    continue;
+                }
+                match compile(&to_regex(entry)) {
+                    Ok(regex) => rules.push(Rule { source: entry.clone(), regex }),
+                    Err(e) => errors.push(format!("{}[{}] \"{}\": {}", field, i, entry, e)),
+                }
+            }
+            rules
+        };
+        let mut titles = rules("title_patterns", &self.title_patterns, str::to_string);
+        titles.extend(rules("url_patterns", &self.url_patterns, url_to_regex));
+        let exclude_titles = rules("exclude_titles", &self.exclude_titles, str::to_string);
+
+        if errors.is_empty() {
+            Ok(CompiledRules {
+                processes,
+                exclude_processes,
+                presenting_processes,
+                titles,
+                exclude_titles,
+            })
+        } else {
+            Err(format!("Invalid meeting rules: {}", errors.join("; ")))
+        }
+    }
+}
+
+impl CompiledRules {
+    pub fn is_meeting_process(&self, name: &str) -> bool {
+        let name = process_base_name(name);
+        self.processes.contains(&name) && !self.exclude_processes.contains(&name)
+    }
+
+    pub fn is_excluded_process(&self, name: &str) -> bool {
+        self.exclude_processes.contains(&process_base_name(name))
+    }
+
+    pub fn is_presenting_process(&self, name: &str) -> bool {
+        let name = process_base_name(name);
+        self.presenting_processes.contains(&name) && !self.exclude_processes.contains(&name)
+    }
+
+    /// The rule a window title matches, unless an exclusion also matches it.
+    pub fn title_rule(&self, title: &str) -> Option<&Rule> {
+        if self.exclude_titles.iter().any(|rule| rule.is_match(title)) {
+            return None;
+        }
+        self.titles.iter().find(|rule| rule.is_match(title))
+    }
+
+    pub fn title_exclusion(&self, title: &str) -> Option<&Rule> {
+        self.exclude_titles.iter().find(|rule| rule.is_match(title))
+    }
+}
+
+struct RulesState {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    rules: Arc<CompiledRules>,
+    /// Problem with the file on disk; the last good rules stay in use meanwhile.
+    error: Option<String>,
+}
+
+static RULES: OnceLock<Mutex<RulesState>> = OnceLock::new();
+
+fn state() -> &'static Mutex<RulesState> {
+    RULES.get_or_init(|| {
+        let rules = MeetingRules::default()
+            .compile()
+            .expect("built-in meeting rules are valid");
+        Mutex::new(RulesState {
+            path: None,
+            modified: None,
+            rules: Arc::new(rules),
+            error: None,
+        })
+    })
+}
+
+pub fn rules_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(settings::app_data_dir(app_handle)?.join(RULES_FILE))
+}
+
+/// Point the rules at `meeting_rules.json`, writing the defaults there if the
+/// file doesn't exist yet, and load it.
+pub fn init(app_handle: &AppHandle) {
+    let path = match rules_path(app_handle) {
+        Ok(path) => path,
+        Err(e) => {
+            println!("⚠️ {}, using built-in meeting rules", e);
+            return;
+        }
+    };
+    if !path.exists() {
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                serde_json::to_string_pretty(&MeetingRules::default()).map_err(|e| e.to_string())
+            })
+            .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            println!("⚠️ Failed to write default meeting rules: {}", e);
+        }
+    }
+    if let Ok(mut state) = state().lock() {
+        state.path = Some(path);
+        state.modified = None;
+    }
+    current();
+}
+
+pub fn load_file(path: &PathBuf) -> Result<MeetingRules, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", RULES_FILE, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse {}: {}", RULES_FILE, e))
+}
+
+/// The rules in effect, reloading `meeting_rules.json` first if it changed on
+/// disk. An invalid file is reported and the previous rules are kept.
+pub fn current() -> Arc<CompiledRules> {
+    let mut state = match state().lock() {
+        Ok(state) => state,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let path = match state.path.clone() {
+        Some(path) => path,
+        None => return state.rules.clone(),
+    };
+
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if modified.is_some() && modified == state.modified {
+        return state.rules.clone();
+    }
+    state.modified = modified;
+
+    match load_file(&path).and_then(|rules| rules.compile()) {
+        Ok(rules) => {
+            println!(
+                "📋 Loaded meeting rules: {} processes, {} title/URL patterns, {} exclusions",
+                rules.processes.len(),
+                rules.titles.len(),
+                rules.exclude_processes.len() + rules.exclude_titles.len()
+            );
+            state.rules = Arc::new(rules);
+            state.error = None;
+        }
+        Err(e) => {
+            println!("⚠️ {} — keeping the previous rules", e);
+            state.error = Some(e);
+        }
+    }
+    state.rules.clone()
+}
+
+/// Why the rules file was rejected, if it was.
+pub fn last_error() -> Option<String> {
+    state().lock().ok().and_then(|state| state.error.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, title: &str) -> bool {
+        compile(&url_to_regex(pattern)).unwrap().is_match(title)
+    }
+
+    #[test]
+    fn url_pattern_path_is_optional() {
+        assert_eq!(url_to_regex("meet.google.com/*"), r"meet\.google\.com(/.*)?");
+        assert!(matches("meet.google.com/*", "Meet - meet.google.com"));
+        assert!(matches("meet.google.com/*", "meet.google.com/abc-defg-hij - Chromium"));
+        assert!(!matches("meet.google.com/*", "calendar.google.com"));
+    }
+
+    #[test]
+    fn url_pattern_subdomain_is_optional() {
+        assert_eq!(url_to_regex("*.webex.com/*"), r"(.*\.)?webex\.com(/.*)?");
+        assert!(matches("*.webex.com/*", "webex.com - Firefox"));
+        assert!(matches("*.webex.com/*", "acme.webex.com/meet/jane"));
+        assert!(!matches("*.webex.com/*", "webex.org"));
+    }
+
+    #[test]
+    fn inner_wildcards_and_dots_are_literal() {
+        assert_eq!(url_to_regex("a*b.com"), r"a.*b\.com");
+        assert!(!matches("8x8.vc/*", "8x8xvc"));
+    }
+
+    #[test]
+    fn default_rules_match_bare_hosts_in_titles() {
+        let rules = MeetingRules::default().compile().unwrap();
+        for title in ["meet.google.com", "Weekly sync - teams.microsoft.com", "webex.com"] {
+            assert!(rules.title_rule(title).is_some(), "{} should match", title);
+        }
+        assert!(rules.title_rule("Inbox - mail.google.com").is_none());
+    }
+
+    #[test]
+    fn exclusions_win_over_matches() {
+        let rules = MeetingRules {
+            exclude_titles: vec!["recording".to_string()],
+            exclude_processes: vec!["Zoom.exe".to_string()],
+            ..MeetingRules::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(rules.title_rule("Zoom meeting recording").is_none());
+        assert!(rules.title_exclusion("Zoom meeting recording").is_some());
+        assert!(!rules.is_meeting_process("zoom"));
+        assert!(rules.is_meeting_process("Teams.exe"));
+    }
+
+    #[test]
+    fn every_invalid_entry_is_reported() {
+        let error = MeetingRules {
+            processes: vec![" ".to_string()],
+            title_patterns: vec!["(unclosed".to_string()],
+            ..MeetingRules::default()
+        }
+        .compile()
+        .unwrap_err();
+        assert!(error.contains("processes[0] is empty"), "{}", error);
+        assert!(error.contains("title_patterns[0] \"(unclosed\""), "{}", error);
+    }
+}
@@ -0,0 +1,146 @@
+use serde::Serialize;
+
+use crate::break_tiers::{BreakTier, NextBreak};
+
+/// The `break_mode` that runs Pomodoro cycles instead of the main break.
+pub const POMODORO_MODE: &str = "pomodoro";
+
+/// Index of the short break among the Pomodoro tiers.
+pub const SHORT_BREAK: usize = 0;
+/// Index of the long break among the Pomodoro tiers.
+pub const LONG_BREAK: usize = 1;
+
+/// Work sessions separated by short breaks, with a long break after every
+/// `long_break_every` of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PomodoroPlan {
+    /// At least 1; 1 makes every break a long one.
+    pub long_break_every: u32,
+}
+
+/// Where the current pomodoro stands in its set, e.g. 3 of 4.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct PomodoroProgress {
+    /// 1-based position of the current pomodoro in its set.
+    pub number: u32,
+    pub of: u32,
+    /// Pomodoros finished since the timer started.
+    pub completed: u32,
+}
+
+impl PomodoroPlan {
+    pub fn new(long_break_every: u32) -> Self {
+        Self {
+            long_break_every: long_break_every.max(1),
+        }
+    }
+
+    /// Whether the pomodoro after `completed` finished ones ends in a long break.
+    pub fn long_break_due(&self, completed: u32) -> bool {
+        (completed + 1).is_multiple_of(self.long_break_every)
+    }
+
+    /// The break ending the pomodoro after `completed` finished ones. A long
+    /// break without a length falls back to a short one.
+    pub fn next_break(&self, tiers: &[BreakTier], completed: u32) -> Option<NextBreak> {
+        let long = tiers.get(LONG_BREAK).filter(|tier| tier.is_valid());
+        let tier = match long {
+            Some(_) if self.long_break_due(completed) => LONG_BREAK,
+            _ => SHORT_BREAK,
+        };
+        tiers.get(tier).map(|break_tier| NextBreak {
+            tier,
+            in_seconds: break_tier.interval_seconds,
+        })
+    }
+
+    /// The current pomodoro, or the one the running break follows.
+    pub fn progress(&self, completed: u32) -> PomodoroProgress {
+        PomodoroProgress {
+            number: completed % self.long_break_every + 1,
+            of: self.long_break_every,
+            completed,
+        }
+    }
+}
+
+/// Tray text such as `Pomodoro 3/4 — 12:04`, with `label` naming the phase.
+pub fn status_text(label: &str, progress: &PomodoroProgress, remaining_seconds: u32, paused: bool) -> String {
+    let mut text = format!(
+        "{} {}/{} — {:02}:{:02}",
+        label,
+        progress.number,
+        progress.of,
+        remaining_seconds / 60,
+        remaining_seconds % 60
+    );
+    if paused {
+        text.push_str(" (paused)");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiers() -> Vec<BreakTier> {
+        let tier = |name: &str, interval_seconds, duration_seconds| BreakTier {
+            name: name.to_string(),
+            interval_seconds,
+            duration_seconds,
+            mode: "force".to_string(),
+            pre_break_seconds: None,
+        };
+        vec![tier("Short break", 25 * 60, 5 * 60), tier("Long break", 25 * 60, 15 * 60)]
+    }
+
+    #[test]
+    fn every_fourth_break_is_long() {
+        let plan = PomodoroPlan::new(4);
+        let kinds: Vec<usize> = (0..8)
+            .map(|completed| plan.next_break(&tiers(), completed).unwrap().tier)
+            .collect();
+        assert_eq!(
+            kinds,
+            [SHORT_BREAK, SHORT_BREAK, SHORT_BREAK, LONG_BREAK, SHORT_BREAK, SHORT_BREAK, SHORT_BREAK, LONG_BREAK]
+        );
+        assert_eq!(plan.next_break(&tiers(), 0).unwrap().in_seconds, 25 * 60);
+    }
+
+    #[test]
+    fn zero_means_every_break_is_long() {
+        let plan = PomodoroPlan::new(0);
+        assert_eq!(plan.long_break_every, 1);
+        assert!((0..3).all(|completed| plan.long_break_due(completed)));
+    }
+
+    #[test]
+    fn long_break_without_a_length_falls_back_to_short() {
+        let mut tiers = tiers();
+        tiers[LONG_BREAK].duration_seconds = 0;
+        assert_eq!(PomodoroPlan::new(4).next_break(&tiers, 3).unwrap().tier, SHORT_BREAK);
+        assert_eq!(PomodoroPlan::new(4).next_break(&[], 3), None);
+    }
+
+    #[test]
+    fn progress_counts_within_the_set() {
+        let plan = PomodoroPlan::new(4);
+        assert_eq!(
+            plan.progress(6),
+            PomodoroProgress {
+                number: 3,
+                of: 4,
+                completed: 6
+            }
+        );
+        assert_eq!(plan.progress(4).number, 1);
+    }
+
+    #[test]
+    fn status_text_shows_position_and_time() {
+        let progress = PomodoroPlan::new(4).progress(2);
+        assert_eq!(status_text("Pomodoro", &progress, 724, false), "Pomodoro 3/4 — 12:04");
+        assert_eq!(status_text("Short break", &progress, 59, true), "Short break 3/4 — 00:59 (paused)");
+    }
+}
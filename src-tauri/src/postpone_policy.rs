@@ -0,0 +1,380 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::settings::AppSettings;
+
+/// How often a postponed break checks whether the meeting is still going.
+pub const MEETING_POLL_SECONDS: u32 = 60;
+
+/// Longest single snooze the user can ask for.
+pub const MAX_SNOOZE_MINUTES: u32 = 24 * 60;
+
+/// Postponements kept for `get_postponements`, oldest dropped first.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// How breaks are held back while the user is in a meeting.
+///
+/// A break that becomes due during a meeting is postponed and re-checked every
+/// `poll_seconds`. Once the meeting is over the break starts after
+/// `grace_seconds`. Breaks stop being postponed after `max_postpones` meetings
+/// in one cycle, or once the user has gone `mandatory_after` without a break.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostponePolicy {
+    pub poll_seconds: u32,
+    pub grace_seconds: u32,
+    /// `None` postpones for any number of meetings.
+    pub max_postpones: Option<u32>,
+    /// `None` never escalates.
+    pub mandatory_after: Option<Duration>,
+}
+
+impl From<&AppSettings> for PostponePolicy {
+    fn from(settings: &AppSettings) -> Self {
+        Self {
+            poll_seconds: MEETING_POLL_SECONDS,
+            grace_seconds: settings.meeting_grace_minutes * 60,
+            max_postpones: match settings.max_meeting_postpones {
+                0 => None,
+                count => Some(count),
+            },
+            mandatory_after: match settings.mandatory_break_hours {
+                0 => None,
+                hours => Some(Duration::from_secs(hours as u64 * 3600)),
+            },
+        }
+    }
+}
+
+/// What to do with a due break, given the result of the meeting check.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PostponeDecision {
+    /// A meeting is going on: postpone and check again after `seconds`.
+    Postpone { seconds: u32, reason: String },
+    /// The meeting we are waiting for is still going: check again after `seconds`.
+    KeepWaiting { seconds: u32 },
+    /// The meeting ended: start the break after `seconds`.
+    Grace { seconds: u32 },
+    /// Start the break now.
+    BreakNow,
+    /// A meeting is going on, but the break can't be held back any longer.
+    Mandatory { reason: String },
+}
+
+impl PostponePolicy {
+    /// Decide what a due break does.
+    ///
+    /// `meeting` describes the meeting found at break time, if any.
+    /// `waiting_for_meeting` is true while the break is already postponed for one.
+    /// `postpone_count` counts the meetings the break was postponed for this cycle,
+    /// and `since_last_break` is how long the user has worked without a break.
+    pub fn decide(
+        &self,
+        meeting: Option<&str>,
+        waiting_for_meeting: bool,
+        postpone_count: u32,
+        since_last_break: Duration,
+    ) -> PostponeDecision {
+        let meeting = match meeting {
+            Some(meeting) => meeting,
+            None if waiting_for_meeting && self.grace_seconds > 0 => {
+                return PostponeDecision::Grace {
+                    seconds: self.grace_seconds,
+                };
+            }
+            None => return PostponeDecision::BreakNow,
+        };
+
+        if let Some(limit) = self.mandatory_after {
+            if since_last_break >= limit {
+                return PostponeDecision::Mandatory {
+                    reason: format!(
+                        "No break for {} — {}",
+                        format_duration(since_last_break),
+                        meeting
+                    ),
+                };
+            }
+        }
+        if waiting_for_meeting {
+            return PostponeDecision::KeepWaiting {
+                seconds: self.poll_seconds,
+            };
+        }
+        if let Some(max) = self.max_postpones {
+            if postpone_count >= max {
+                return PostponeDecision::Mandatory {
+                    reason: format!("Already postponed {} times — {}", postpone_count, meeting),
+                };
+            }
+        }
+        PostponeDecision::Postpone {
+            seconds: self.poll_seconds,
+            reason: meeting.to_string(),
+        }
+    }
+}
+
+/// How far the user may snooze a break, per work cycle.
+///
+/// Each snooze pushes the break back by a number of minutes, `step_minutes` by
+/// default, or until the current meeting ends. At most `max_snoozes` snoozes
+/// and `max_total_seconds` of timed snoozes are allowed before the break.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnoozePolicy {
+    pub step_minutes: u32,
+    /// `None` allows any number of snoozes.
+    pub max_snoozes: Option<u32>,
+    /// `None` allows any total delay.
+    pub max_total_seconds: Option<u32>,
+}
+
+impl From<&AppSettings> for SnoozePolicy {
+    fn from(settings: &AppSettings) -> Self {
+        Self {
+            step_minutes: settings.snooze_minutes.max(1),
+            max_snoozes: match settings.max_snoozes {
+                0 => None,
+                count => Some(count),
+            },
+            max_total_seconds: match settings.max_snooze_minutes {
+                0 => None,
+                minutes => Some(minutes * 60),
+            },
+        }
+    }
+}
+
+/// The snooze still available this cycle, for the pre-break window and tray.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SnoozeOffer {
+    /// The step, or less once the total delay is nearly used up.
+    pub minutes: u32,
+    /// `None` without a limit.
+    pub snoozes_left: Option<u32>,
+    /// Snoozing until the meeting ends is possible (meeting detection is on).
+    pub until_meeting: bool,
+}
+
+impl SnoozePolicy {
+    /// Check a snooze of `seconds`, or until the meeting ends for `None`, against
+    /// the `snooze_count` snoozes totalling `snoozed_seconds` so far this cycle.
+    pub fn decide(&self, snooze_count: u32, snoozed_seconds: u32, seconds: Option<u32>) -> Result<(), String> {
+        if let Some(max) = self.max_snoozes {
+            if snooze_count >= max {
+                return Err(format!("Already snoozed {} times this cycle", snooze_count));
+            }
+        }
+        if let (Some(max_total), Some(seconds)) = (self.max_total_seconds, seconds) {
+            if snoozed_seconds.saturating_add(seconds) > max_total {
+                let left = max_total.saturating_sub(snoozed_seconds) / 60;
+                return Err(format!("Only {} more minutes of snoozing left this cycle", left));
+            }
+        }
+        Ok(())
+    }
+
+    /// The snooze left this cycle, or `None` if the break can't be snoozed again.
+    pub fn offer(&self, snooze_count: u32, snoozed_seconds: u32, until_meeting: bool) -> Option<SnoozeOffer> {
+        let snoozes_left = self.max_snoozes.map(|max| max.saturating_sub(snooze_count));
+        if snoozes_left == Some(0) {
+            return None;
+        }
+        let minutes = match self.max_total_seconds {
+            Some(max_total) => self.step_minutes.min(max_total.saturating_sub(snoozed_seconds) / 60),
+            None => self.step_minutes,
+        };
+        if minutes == 0 && !until_meeting {
+            return None;
+        }
+        Some(SnoozeOffer {
+            minutes,
+            snoozes_left,
+            until_meeting,
+        })
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// One postponed (or no longer postponable) or snoozed break, for `get_postponements`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PostponeRecord {
+    pub at_unix_ms: u64,
+    pub cycle: u32,
+    /// Postponements in this cycle so far, including this one.
+    pub postpone_count: u32,
+    pub reason: String,
+    /// The break started anyway because the policy's limits were reached.
+    pub mandatory: bool,
+    /// The user snoozed the break, rather than a meeting or busy screen holding it back.
+    pub snoozed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn policy() -> PostponePolicy {
+        PostponePolicy {
+            poll_seconds: MEETING_POLL_SECONDS,
+            grace_seconds: 120,
+            max_postpones: Some(2),
+            mandatory_after: Some(2 * HOUR),
+        }
+    }
+
+    #[test]
+    fn no_meeting_starts_the_break() {
+        assert_eq!(policy().decide(None, false, 0, HOUR), PostponeDecision::BreakNow);
+    }
+
+    #[test]
+    fn meeting_postpones_the_break() {
+        assert_eq!(
+            policy().decide(Some("Zoom"), false, 0, HOUR),
+            PostponeDecision::Postpone {
+                seconds: MEETING_POLL_SECONDS,
+                reason: "Zoom".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn waiting_break_keeps_waiting_then_gets_a_grace_period() {
+        let policy = policy();
+        assert_eq!(
+            policy.decide(Some("Zoom"), true, 2, HOUR),
+            PostponeDecision::KeepWaiting {
+                seconds: MEETING_POLL_SECONDS
+            }
+        );
+        assert_eq!(policy.decide(None, true, 2, HOUR), PostponeDecision::Grace { seconds: 120 });
+
+        let no_grace = PostponePolicy {
+            grace_seconds: 0,
+            ..policy
+        };
+        assert_eq!(no_grace.decide(None, true, 2, HOUR), PostponeDecision::BreakNow);
+    }
+
+    #[test]
+    fn too_many_meetings_make_the_break_mandatory() {
+        assert_eq!(
+            policy().decide(Some("Zoom"), false, 2, HOUR),
+            PostponeDecision::Mandatory {
+                reason: "Already postponed 2 times — Zoom".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn long_stretch_without_a_break_is_mandatory_even_while_waiting() {
+        let since = 2 * HOUR + Duration::from_secs(5 * 60);
+        let mandatory = PostponeDecision::Mandatory {
+            reason: "No break for 2h 05m — Zoom".to_string(),
+        };
+        assert_eq!(policy().decide(Some("Zoom"), false, 0, since), mandatory);
+        assert_eq!(policy().decide(Some("Zoom"), true, 1, since), mandatory);
+    }
+
+    #[test]
+    fn zero_settings_mean_no_limit() {
+        let settings = AppSettings {
+            max_meeting_postpones: 0,
+            mandatory_break_hours: 0,
+            ..AppSettings::default()
+        };
+        let policy = PostponePolicy::from(&settings);
+        assert_eq!(policy.max_postpones, None);
+        assert_eq!(policy.mandatory_after, None);
+        assert!(matches!(
+            policy.decide(Some("Zoom"), false, 100, 10 * HOUR),
+            PostponeDecision::Postpone { .. }
+        ));
+    }
+
+    fn snooze() -> SnoozePolicy {
+        SnoozePolicy {
+            step_minutes: 5,
+            max_snoozes: Some(3),
+            max_total_seconds: Some(12 * 60),
+        }
+    }
+
+    #[test]
+    fn snoozes_are_limited_in_number() {
+        let policy = snooze();
+        assert!(policy.decide(2, 0, Some(60)).is_ok());
+        assert_eq!(
+            policy.decide(3, 0, Some(60)),
+            Err("Already snoozed 3 times this cycle".to_string())
+        );
+        // Also when snoozing until the meeting ends
+        assert!(policy.decide(3, 0, None).is_err());
+    }
+
+    #[test]
+    fn snoozes_are_limited_in_total_length() {
+        let policy = snooze();
+        assert!(policy.decide(1, 5 * 60, Some(7 * 60)).is_ok());
+        assert_eq!(
+            policy.decide(1, 10 * 60, Some(5 * 60)),
+            Err("Only 2 more minutes of snoozing left this cycle".to_string())
+        );
+        // Waiting for a meeting has no length to count
+        assert!(policy.decide(1, 12 * 60, None).is_ok());
+    }
+
+    #[test]
+    fn offer_shrinks_to_the_time_left() {
+        let policy = snooze();
+        let offer = |count, seconds, until_meeting| policy.offer(count, seconds, until_meeting);
+        assert_eq!(
+            offer(0, 0, false),
+            Some(SnoozeOffer {
+                minutes: 5,
+                snoozes_left: Some(3),
+                until_meeting: false
+            })
+        );
+        assert_eq!(offer(1, 10 * 60, false).map(|offer| offer.minutes), Some(2));
+        assert_eq!(offer(2, 12 * 60, false), None);
+        // Only waiting for the meeting is left
+        assert_eq!(offer(2, 12 * 60, true).map(|offer| offer.minutes), Some(0));
+        assert_eq!(offer(3, 0, true), None);
+    }
+
+    #[test]
+    fn unlimited_snooze_settings() {
+        let settings = AppSettings {
+            snooze_minutes: 0,
+            max_snoozes: 0,
+            max_snooze_minutes: 0,
+            ..AppSettings::default()
+        };
+        let policy = SnoozePolicy::from(&settings);
+        assert_eq!(policy.step_minutes, 1);
+        assert!(policy.decide(100, 100 * 60, Some(60)).is_ok());
+        assert_eq!(
+            policy.offer(100, 100 * 60, false),
+            Some(SnoozeOffer {
+                minutes: 1,
+                snoozes_left: None,
+                until_meeting: false
+            })
+        );
+    }
+
+    #[test]
+    fn huge_snooze_does_not_wrap_past_the_total() {
+        let policy = snooze();
+        assert!(policy.decide(0, 60, Some(u32::MAX)).is_err());
+        assert!(policy.decide(0, u32::MAX, Some(60)).is_err());
+    }
+}
+
@@ -0,0 +1,267 @@
+use serde::Serialize;
+use sysinfo::System;
+
+use crate::meeting_rules;
+use crate::settings::BusyScreenAction;
+
+/// What is on screen that a break shouldn't cover.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ScreenActivity {
+    /// Title of a window filling its monitor, if any.
+    pub fullscreen: Option<String>,
+    /// A running presenting or screen recording process, if any.
+    pub presenting: Option<String>,
+}
+
+/// How a due break should react to the `ScreenActivity`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScreenVerdict {
+    /// Hold the break, like for a meeting.
+    pub postpone: Option<String>,
+    /// Show the notify window instead of the configured break.
+    pub notify: Option<String>,
+}
+
+impl ScreenActivity {
+    /// Apply the per-condition actions from the settings.
+    pub fn verdict(&self, fullscreen: BusyScreenAction, presenting: BusyScreenAction) -> ScreenVerdict {
+        let mut verdict = ScreenVerdict::default();
+        let conditions = [
+            (presenting, self.presenting.as_ref().map(|p| format!("Presenting ({})", p))),
+            (fullscreen, self.fullscreen.as_ref().map(|t| format!("Fullscreen window: {}", t))),
+        ];
+        for (action, reason) in conditions {
+            let reason = match reason {
+                Some(reason) => reason,
+                None => continue,
+            };
+            match action {
+                BusyScreenAction::Ignore => {}
+                BusyScreenAction::Postpone => {
+                    verdict.postpone.get_or_insert(reason);
+                }
+                BusyScreenAction::Notify => {
+                    verdict.notify.get_or_insert(reason);
+                }
+            }
+        }
+        verdict
+    }
+}
+
+/// Look for a fullscreen window and a presenting process. Each check only runs
+/// when asked for, since the process check walks every process.
+pub fn detect(check_fullscreen: bool, check_presenting: bool) -> ScreenActivity {
+    let activity = ScreenActivity {
+        fullscreen: if check_fullscreen { fullscreen_window() } else { None },
+        presenting: if check_presenting { presenting_process() } else { None },
+    };
+    if activity != ScreenActivity::default() {
+        println!("🖥️ Screen busy: {:?}", activity);
+    }
+    activity
+}
+
+/// The first running process listed in `presenting_processes` of `meeting_rules.json`.
+fn presenting_process() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    if let Some(mode) = windows_screen::presentation_mode() {
+        return Some(mode);
+    }
+
+    let rules = meeting_rules::current();
+    let sys = System::new_all();
+    sys.processes()
+        .values()
+        .map(|proc| proc.name().to_string())
+        .find(|name| rules.is_presenting_process(name))
+}
+
+/// The foreground window, if it covers a whole monitor and isn't our own.
+///
+/// - Windows: the foreground window, if it isn't minimized or cloaked and its
+///   rectangle covers its monitor, or the shell reporting a fullscreen Direct3D app.
+/// - Linux/X11: the `_NET_ACTIVE_WINDOW`, if it has `_NET_WM_STATE_FULLSCREEN`.
+///   Under Wayland this only sees XWayland windows; native Wayland windows
+///   don't expose their state to other clients.
+fn fullscreen_window() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        windows_screen::fullscreen_window()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        x11_screen::fullscreen_window()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_screen {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use winapi::shared::minwindef::{DWORD, LPVOID};
+    use winapi::shared::windef::{HWND, RECT};
+    use winapi::shared::winerror::S_OK;
+    use winapi::um::dwmapi::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+    use winapi::um::processthreadsapi::GetCurrentProcessId;
+    use winapi::um::shellapi::{
+        SHQueryUserNotificationState, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+    };
+    use winapi::um::winuser::{
+        GetClassNameW, GetForegroundWindow, GetMonitorInfoW, GetWindowRect, GetWindowTextW,
+        GetWindowThreadProcessId, IsIconic, IsWindowVisible, MonitorFromWindow, MONITORINFO,
+        MONITOR_DEFAULTTONULL,
+    };
+
+    /// The desktop and taskbar cover the screen too, but aren't apps.
+    const SHELL_CLASSES: &[&str] = &["Progman", "WorkerW", "Shell_TrayWnd", "Shell_SecondaryTrayWnd"];
+
+    fn wide_to_string(buffer: &[u16], len: i32) -> String {
+        OsString::from_wide(&buffer[..len.max(0) as usize])
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn covers_monitor(hwnd: HWND) -> bool {
+        unsafe {
+            let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+            if monitor.is_null() {
+                return false;
+            }
+            let mut info: MONITORINFO = std::mem::zeroed();
+            info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+            let mut rect: RECT = std::mem::zeroed();
+            if GetMonitorInfoW(monitor, &mut info) == 0 || GetWindowRect(hwnd, &mut rect) == 0 {
+                return false;
+            }
+            let screen = info.rcMonitor;
+            rect.left <= screen.left
+                && rect.top <= screen.top
+                && rect.right >= screen.right
+                && rect.bottom >= screen.bottom
+        }
+    }
+
+    /// Hidden UWP apps and windows on other virtual desktops are "cloaked": still
+    /// visible to `IsWindowVisible`, but not drawn.
+    fn is_cloaked(hwnd: HWND) -> bool {
+        let mut cloaked: DWORD = 0;
+        let result = unsafe {
+            DwmGetWindowAttribute(
+                hwnd,
+                DWMWA_CLOAKED,
+                &mut cloaked as *mut DWORD as LPVOID,
+                std::mem::size_of::<DWORD>() as DWORD,
+            )
+        };
+        result == S_OK && cloaked != 0
+    }
+
+    /// The window's title if it is shown and covers its monitor.
+    fn fullscreen_title(hwnd: HWND) -> Option<String> {
+        unsafe {
+            if hwnd.is_null() || IsWindowVisible(hwnd) == 0 || IsIconic(hwnd) != 0 || is_cloaked(hwnd) {
+                return None;
+            }
+            let mut pid = 0;
+            GetWindowThreadProcessId(hwnd, &mut pid);
+            if pid == GetCurrentProcessId() {
+                return None;
+            }
+            let mut class: [u16; 256] = [0; 256];
+            let class_len = GetClassNameW(hwnd, class.as_mut_ptr(), class.len() as i32);
+            if SHELL_CLASSES.contains(&wide_to_string(&class, class_len).as_str()) {
+                return None;
+            }
+            if !covers_monitor(hwnd) {
+                return None;
+            }
+
+            let mut title: [u16; 512] = [0; 512];
+            let title_len = GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32);
+            let title = wide_to_string(&title, title_len);
+            Some(if title.is_empty() { "untitled window".to_string() } else { title })
+        }
+    }
+
+    pub fn fullscreen_window() -> Option<String> {
+        fullscreen_title(unsafe { GetForegroundWindow() }).or_else(|| {
+            let mut state = 0;
+            let ok = unsafe { SHQueryUserNotificationState(&mut state) } == 0;
+            (ok && state == QUNS_RUNNING_D3D_FULL_SCREEN).then(|| "fullscreen game or video".to_string())
+        })
+    }
+
+    /// Windows' own presentation mode, set by PowerPoint slide shows and
+    /// `presentationsettings.exe`.
+    pub fn presentation_mode() -> Option<String> {
+        let mut state = 0;
+        let ok = unsafe { SHQueryUserNotificationState(&mut state) } == 0;
+        (ok && state == QUNS_PRESENTATION_MODE).then(|| "Windows presentation mode".to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11_screen {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            _NET_ACTIVE_WINDOW,
+            _NET_WM_STATE,
+            _NET_WM_STATE_FULLSCREEN,
+            _NET_WM_STATE_HIDDEN,
+            _NET_WM_PID,
+            _NET_WM_NAME,
+            UTF8_STRING,
+        }
+    }
+
+    pub fn fullscreen_window() -> Option<String> {
+        std::env::var_os("DISPLAY")?;
+        let (conn, screen_num) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen_num)?.root;
+        let atoms = Atoms::new(&conn).ok()?.reply().ok()?;
+
+        let window = conn
+            .get_property(false, root, atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?
+            .value32()?
+            .next()
+            .filter(|&window| window != 0)?;
+
+        // All requests go out before the first reply is awaited
+        let state = conn.get_property(false, window, atoms._NET_WM_STATE, AtomEnum::ATOM, 0, 64);
+        let pid = conn.get_property(false, window, atoms._NET_WM_PID, AtomEnum::CARDINAL, 0, 1);
+        let title = conn.get_property(false, window, atoms._NET_WM_NAME, atoms.UTF8_STRING, 0, 256);
+
+        // A window closed since it was read answers with an error
+        let states: Vec<u32> = state.ok()?.reply().ok()?.value32()?.collect();
+        if !states.contains(&atoms._NET_WM_STATE_FULLSCREEN) || states.contains(&atoms._NET_WM_STATE_HIDDEN) {
+            return None;
+        }
+        let pid = pid
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().and_then(|mut values| values.next()));
+        if pid == Some(std::process::id()) {
+            return None;
+        }
+
+        let title = title
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| String::from_utf8_lossy(&reply.value).into_owned())
+            .filter(|title| !title.is_empty());
+        Some(title.unwrap_or_else(|| format!("window 0x{:x}", window)))
+    }
+}
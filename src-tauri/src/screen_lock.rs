@@ -0,0 +1,96 @@
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+/// Screen lock state changes reported by the OS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockEvent {
+    Locked { at: Instant },
+    Unlocked { at: Instant },
+}
+
+#[cfg(target_os = "linux")]
+fn session_proxy(connection: &zbus::blocking::Connection) -> zbus::Result<zbus::blocking::Proxy<'static>> {
+    zbus::blocking::Proxy::new(
+        connection,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1/session/auto",
+        "org.freedesktop.login1.Session",
+    )
+}
+
+/// Lock the current session through logind, falling back to `xdg-screensaver lock`
+/// when logind is unreachable or refuses.
+#[cfg(target_os = "linux")]
+pub fn lock() -> Result<(), String> {
+    let logind = zbus::blocking::Connection::system()
+        .and_then(|connection| session_proxy(&connection)?.call_method("Lock", &()).map(|_| ()));
+    match logind {
+        Ok(()) => {
+            println!("🔒 Session locked via logind");
+            Ok(())
+        }
+        Err(e) => {
+            println!("⚠️ logind Session.Lock failed ({}), trying xdg-screensaver", e);
+            let status = std::process::Command::new("xdg-screensaver")
+                .arg("lock")
+                .status()
+                .map_err(|e| format!("Failed to lock screen: {}", e))?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("xdg-screensaver lock failed: {}", status))
+            }
+        }
+    }
+}
+
+/// The session's `LockedHint`, or `None` if logind can't be asked.
+/// Desktop environments set the hint when their lock screen is up.
+#[cfg(target_os = "linux")]
+pub fn is_locked() -> Option<bool> {
+    let connection = zbus::blocking::Connection::system().ok()?;
+    session_proxy(&connection).ok()?.get_property("LockedHint").ok()
+}
+
+/// Watch `LockedHint` on the current logind session and forward changes to `tx`.
+/// Returns quietly if logind is not reachable.
+#[cfg(target_os = "linux")]
+pub fn spawn_lock_listener(tx: Sender<LockEvent>) {
+    std::thread::spawn(move || {
+        if let Err(e) = listen_locked_hint(&tx) {
+            println!("ℹ️ logind lock signals unavailable: {}", e);
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn listen_locked_hint(tx: &Sender<LockEvent>) -> zbus::Result<()> {
+    let connection = zbus::blocking::Connection::system()?;
+    let proxy = session_proxy(&connection)?;
+
+    println!("🔒 Listening for logind LockedHint changes");
+    let mut was_locked: bool = proxy.get_property("LockedHint")?;
+    if was_locked {
+        let _ = tx.send(LockEvent::Locked { at: Instant::now() });
+    }
+    for change in proxy.receive_property_changed::<bool>("LockedHint") {
+        let locked = change.get()?;
+        if locked == was_locked {
+            continue;
+        }
+        was_locked = locked;
+        let at = Instant::now();
+        let event = if locked {
+            LockEvent::Locked { at }
+        } else {
+            LockEvent::Unlocked { at }
+        };
+        if tx.send(event).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn spawn_lock_listener(_tx: Sender<LockEvent>) {}
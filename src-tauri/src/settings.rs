@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::break_tiers::BreakTier;
+use crate::pomodoro::{self, PomodoroPlan};
+
+/// What to do on startup when a break became due while the app was not running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverduePolicy {
+    /// Start the missed break right away.
+    Fire,
+    /// Treat the missed break as skipped.
+    Skip,
+    /// Start a fresh work cycle.
+    Restart,
+}
+
+/// How a due force or lock break reacts to a fullscreen window or a presentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BusyScreenAction {
+    /// Start the break as usual.
+    Ignore,
+    /// Hold the break until the condition ends, like for a meeting.
+    Postpone,
+    /// Show the notify window instead.
+    Notify,
+}
+
+/// User settings as persisted in `settings.json`.
+///
+/// Field names and defaults mirror `DEFAULT_SETTINGS` in `src/shared/settings.js`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub break_minutes: u32,
+    pub break_seconds: u32,
+    pub break_duration_minutes: u32,
+    pub break_duration_seconds: u32,
+    pub break_mode: String,
+    pub auto_pause: bool,
+    pub meeting_detect: bool,
+    pub pre_break: bool,
+    pub pre_break_minutes: u32,
+    pub pre_break_seconds: u32,
+    pub break_chime: bool,
+    pub recurring: bool,
+    pub autostart: bool,
+    pub auto_start_timer: bool,
+    pub overdue_break_policy: OverduePolicy,
+    pub sleep_counts_as_break: bool,
+    pub lock_counts_as_break: bool,
+    pub idle_detect: bool,
+    pub idle_pause_minutes: u32,
+    /// 0 means "use the break duration".
+    pub idle_reset_minutes: u32,
+    /// If non-empty, auto-pause only touches media players matching one of these.
+    pub media_allow_list: Vec<String>,
+    /// Media players auto-pause never touches, even if allowed above.
+    pub media_deny_list: Vec<String>,
+    /// Lower the volume instead of pausing media during notify-mode breaks.
+    pub duck_notify_breaks: bool,
+    /// Ducked volume, as a percentage of each stream's own level.
+    pub duck_volume_percent: u32,
+    /// Delay between a meeting ending and the postponed break starting.
+    pub meeting_grace_minutes: u32,
+    /// Meetings a break can be postponed for in one cycle; 0 means no limit.
+    pub max_meeting_postpones: u32,
+    /// Hours without a break after which meetings no longer postpone it; 0 means never.
+    pub mandatory_break_hours: u32,
+    /// Move breaks around busy blocks from the calendar files below.
+    pub calendar_enabled: bool,
+    /// `.ics` files, or directories whose `.ics` files are all read.
+    pub calendar_paths: Vec<String>,
+    /// Free minutes wanted between the end of a break and the next meeting.
+    pub calendar_lead_minutes: u32,
+    /// Meetings at least this long get a break before them rather than after.
+    pub calendar_long_meeting_minutes: u32,
+    /// CalDAV server, calendar home or calendar to sync; empty for none.
+    pub caldav_url: String,
+    pub caldav_username: String,
+    pub caldav_refresh_minutes: u32,
+    /// What a break does while a window fills a monitor.
+    pub fullscreen_break_action: BusyScreenAction,
+    /// What a break does while a presenting or recording app runs.
+    pub presenting_break_action: BusyScreenAction,
+    /// Refuse every attempt to skip or end a force break early.
+    pub strict_mode: bool,
+    /// Force breaks that can be skipped per day; 0 means no limit.
+    pub daily_skip_limit: u32,
+    /// Seconds a force break must run before it can be ended early.
+    pub skip_unlock_seconds: u32,
+    /// Minutes one snooze pushes a break back.
+    pub snooze_minutes: u32,
+    /// Snoozes allowed per work cycle; 0 means no limit.
+    pub max_snoozes: u32,
+    /// Total minutes a break can be snoozed by; 0 means no limit.
+    pub max_snooze_minutes: u32,
+    /// Breaks on their own schedules next to the main break above, such as
+    /// short eye breaks.
+    pub extra_break_tiers: Vec<BreakTier>,
+    /// Pomodoro mode: work session length.
+    pub pomodoro_work_minutes: u32,
+    pub pomodoro_short_break_minutes: u32,
+    pub pomodoro_long_break_minutes: u32,
+    /// Pomodoros per set; the last one of a set ends in a long break.
+    pub pomodoro_long_break_every: u32,
+    /// How Pomodoro breaks are shown: `notify` or `force`.
+    pub pomodoro_break_window: String,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            break_minutes: 20,
+            break_seconds: 0,
+            break_duration_minutes: 0,
+            break_duration_seconds: 20,
+            break_mode: "force".to_string(),
+            auto_pause: false,
+            meeting_detect: false,
+            pre_break: false,
+            pre_break_minutes: 0,
+            pre_break_seconds: 30,
+            break_chime: false,
+            recurring: false,
+            autostart: false,
+            auto_start_timer: false,
+            overdue_break_policy: OverduePolicy::Fire,
+            sleep_counts_as_break: true,
+            lock_counts_as_break: true,
+            idle_detect: false,
+            idle_pause_minutes: 5,
+            idle_reset_minutes: 0,
+            media_allow_list: Vec::new(),
+            media_deny_list: Vec::new(),
+            duck_notify_breaks: false,
+            duck_volume_percent: 30,
+            meeting_grace_minutes: 2,
+            max_meeting_postpones: 6,
+            mandatory_break_hours: 3,
+            calendar_enabled: false,
+            calendar_paths: Vec::new(),
+            calendar_lead_minutes: 5,
+            calendar_long_meeting_minutes: 60,
+            caldav_url: String::new(),
+            caldav_username: String::new(),
+            caldav_refresh_minutes: 15,
+            fullscreen_break_action: BusyScreenAction::Notify,
+            presenting_break_action: BusyScreenAction::Postpone,
+            strict_mode: false,
+            daily_skip_limit: 0,
+            skip_unlock_seconds: 0,
+            snooze_minutes: 5,
+            max_snoozes: 3,
+            max_snooze_minutes: 30,
+            extra_break_tiers: Vec::new(),
+            pomodoro_work_minutes: 25,
+            pomodoro_short_break_minutes: 5,
+            pomodoro_long_break_minutes: 15,
+            pomodoro_long_break_every: 4,
+            pomodoro_break_window: "notify".to_string(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Work interval between breaks, in seconds.
+    pub fn work_interval_seconds(&self) -> u32 {
+        self.break_minutes * 60 + self.break_seconds
+    }
+
+    /// Length of a break, in seconds.
+    pub fn break_duration_total_seconds(&self) -> u32 {
+        self.break_duration_minutes * 60 + self.break_duration_seconds
+    }
+
+    /// Seconds before the break at which the pre-break warning is shown,
+    /// or `None` if the warning is disabled.
+    pub fn pre_break_total_seconds(&self) -> Option<u32> {
+        if self.pre_break {
+            Some(self.pre_break_minutes * 60 + self.pre_break_seconds)
+        } else {
+            None
+        }
+    }
+
+    /// The Pomodoro schedule, or `None` outside Pomodoro mode.
+    pub fn pomodoro_plan(&self) -> Option<PomodoroPlan> {
+        (self.break_mode == pomodoro::POMODORO_MODE).then(|| PomodoroPlan::new(self.pomodoro_long_break_every))
+    }
+
+    /// The main break followed by the valid extra tiers. In Pomodoro mode, the
+    /// short and long breaks instead, at `pomodoro::SHORT_BREAK` and `LONG_BREAK`.
+    pub fn break_tiers(&self) -> Vec<BreakTier> {
+        if self.pomodoro_plan().is_some() {
+            let tier = |name: &str, minutes: u32| BreakTier {
+                name: name.to_string(),
+                interval_seconds: self.pomodoro_work_minutes * 60,
+                duration_seconds: minutes * 60,
+                mode: self.pomodoro_break_window.clone(),
+                pre_break_seconds: self.pre_break_total_seconds(),
+            };
+            return vec![
+                tier("Short break", self.pomodoro_short_break_minutes),
+                tier("Long break", self.pomodoro_long_break_minutes),
+            ];
+        }
+        let main = BreakTier {
+            name: "Break".to_string(),
+            interval_seconds: self.work_interval_seconds(),
+            duration_seconds: self.break_duration_total_seconds(),
+            mode: self.break_mode.clone(),
+            pre_break_seconds: self.pre_break_total_seconds(),
+        };
+        std::iter::once(main)
+            .chain(self.extra_break_tiers.iter().filter(|tier| tier.is_valid()).cloned())
+            .collect()
+    }
+}
+
+/// Directory holding `settings.json` and the other per-user state files.
+pub fn app_data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+pub fn save(app_handle: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let app_data_dir = app_data_dir(app_handle)?;
+
+    // Create the directory if it doesn't exist
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    let settings_path = app_data_dir.join("settings.json");
+    let settings_json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(settings_path, settings_json)
+        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+
+    Ok(())
+}
+
+pub fn load(app_handle: &AppHandle) -> Result<Option<AppSettings>, String> {
+    let settings_path = app_data_dir(app_handle)?.join("settings.json");
+
+    if !settings_path.exists() {
+        return Ok(None);
+    }
+
+    let settings_json = fs::read_to_string(settings_path)
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+
+    let settings: AppSettings = serde_json::from_str(&settings_json)
+        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+
+    Ok(Some(settings))
+}
+
+/// Saved settings, or the defaults if nothing has been saved yet or the file is unreadable.
+pub fn load_or_default(app_handle: &AppHandle) -> AppSettings {
+    match load(app_handle) {
+        Ok(Some(settings)) => settings,
+        Ok(None) => AppSettings::default(),
+        Err(e) => {
+            println!("⚠️ {}, using default settings", e);
+            AppSettings::default()
+        }
+    }
+}
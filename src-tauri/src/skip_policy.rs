@@ -0,0 +1,402 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use crate::settings::{self, AppSettings};
+use crate::timer_persistence::unix_ms;
+
+const LEDGER_FILE: &str = "skip_budget.json";
+
+/// Skip attempts kept in the ledger, oldest dropped first.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// A force break this close to its end may be closed without a skip.
+pub const END_GRACE_SECONDS: u32 = 3;
+
+/// When a force break may be skipped or ended early.
+///
+/// `strict` refuses every attempt. Otherwise at most `daily_limit` skips are
+/// allowed per local day, and a running break can only be ended early once it
+/// has been showing for `unlock_delay_seconds`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SkipPolicy {
+    pub strict: bool,
+    /// `None` allows any number of skips.
+    pub daily_limit: Option<u32>,
+    pub unlock_delay_seconds: u32,
+}
+
+impl From<&AppSettings> for SkipPolicy {
+    fn from(settings: &AppSettings) -> Self {
+        Self {
+            strict: settings.strict_mode,
+            daily_limit: match settings.daily_skip_limit {
+                0 => None,
+                limit => Some(limit),
+            },
+            unlock_delay_seconds: settings.skip_unlock_seconds,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipKind {
+    /// Skipping the upcoming break from the pre-break warning.
+    Skip,
+    /// Ending a running break early.
+    EarlyReturn,
+}
+
+impl SkipPolicy {
+    /// Decide whether a skip may go ahead. `used_today` counts the skips allowed
+    /// so far today; `shown_seconds` is how long the running break has been
+    /// showing, or `None` for a break that hasn't started.
+    pub fn decide(&self, used_today: u32, shown_seconds: Option<u32>) -> Result<(), String> {
+        if self.strict {
+            return Err("Strict mode is on: breaks can't be skipped".to_string());
+        }
+        if let Some(limit) = self.daily_limit {
+            if used_today >= limit {
+                return Err(format!("No skips left today (limit {})", limit));
+            }
+        }
+        if let Some(shown) = shown_seconds {
+            if shown < self.unlock_delay_seconds {
+                return Err(format!(
+                    "The break can be ended early in {}s",
+                    self.unlock_delay_seconds - shown
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips left today, or `None` without a limit.
+    pub fn remaining(&self, used_today: u32) -> Option<u32> {
+        if self.strict {
+            return Some(0);
+        }
+        self.daily_limit.map(|limit| limit.saturating_sub(used_today))
+    }
+}
+
+/// One skip attempt, allowed or refused.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkipAttempt {
+    pub at_unix_ms: u64,
+    pub kind: SkipKind,
+    /// Why the attempt was refused; `None` if it was allowed.
+    pub refused: Option<String>,
+}
+
+/// Today's skips as written to `skip_budget.json`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct SkipLedger {
+    /// Local date, `YYYY-MM-DD`; the counts start over on a new day.
+    day: String,
+    used: u32,
+    refused: u32,
+    attempts: Vec<SkipAttempt>,
+}
+
+/// The skip budget as the break windows and settings see it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SkipStatus {
+    pub strict: bool,
+    pub daily_limit: Option<u32>,
+    pub used_today: u32,
+    /// `None` without a daily limit.
+    pub remaining_today: Option<u32>,
+    pub refused_today: u32,
+    pub unlock_delay_seconds: u32,
+}
+
+#[derive(Default)]
+struct SkipState {
+    policy: SkipPolicy,
+    ledger: SkipLedger,
+    path: Option<PathBuf>,
+    /// Windows whose close was refused during the break of this cycle.
+    close_refusals: (u32, HashSet<String>),
+}
+
+fn state() -> &'static Mutex<SkipState> {
+    static STATE: OnceLock<Mutex<SkipState>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(SkipState::default()))
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+impl SkipState {
+    /// Start the counts over if the day changed since the last skip.
+    fn roll_over(&mut self) {
+        let today = today();
+        if self.ledger.day != today {
+            self.ledger = SkipLedger {
+                day: today,
+                attempts: std::mem::take(&mut self.ledger.attempts),
+                ..SkipLedger::default()
+            };
+        }
+    }
+
+    fn status(&self) -> SkipStatus {
+        SkipStatus {
+            strict: self.policy.strict,
+            daily_limit: self.policy.daily_limit,
+            used_today: self.ledger.used,
+            remaining_today: self.policy.remaining(self.ledger.used),
+            refused_today: self.ledger.refused,
+            unlock_delay_seconds: self.policy.unlock_delay_seconds,
+        }
+    }
+
+    fn record(&mut self, kind: SkipKind, refused: Option<String>) {
+        if refused.is_some() {
+            self.ledger.refused += 1;
+        } else {
+            self.ledger.used += 1;
+        }
+        self.ledger.attempts.push(SkipAttempt {
+            at_unix_ms: unix_ms(SystemTime::now()),
+            kind,
+            refused,
+        });
+        if self.ledger.attempts.len() > HISTORY_LIMIT {
+            let excess = self.ledger.attempts.len() - HISTORY_LIMIT;
+            self.ledger.attempts.drain(..excess);
+        }
+        self.save();
+    }
+
+    /// Spend a skip if the policy allows it. `close` is the break's cycle and the
+    /// window label for a window being closed, `None` for an explicit request.
+    fn spend(&mut self, kind: SkipKind, shown_seconds: Option<u32>, close: Option<(u32, &str)>) -> Result<Option<u32>, String> {
+        match self.policy.decide(self.ledger.used, shown_seconds) {
+            Ok(()) => {
+                self.record(kind, None);
+                let remaining = self.policy.remaining(self.ledger.used);
+                println!("⏭️ {:?} allowed, {:?} skips left today", kind, remaining);
+                Ok(remaining)
+            }
+            Err(reason) => {
+                println!("🚫 {:?} refused: {}", kind, reason);
+                let log_refusal = match close {
+                    Some((cycle, label)) => self.first_close_refusal(cycle, label),
+                    None => true,
+                };
+                if log_refusal {
+                    self.record(kind, Some(reason.clone()));
+                }
+                Err(reason)
+            }
+        }
+    }
+
+    /// True the first time closing `label` is refused during the break of `cycle`.
+    fn first_close_refusal(&mut self, cycle: u32, label: &str) -> bool {
+        let (refused_cycle, labels) = &mut self.close_refusals;
+        if *refused_cycle != cycle {
+            *refused_cycle = cycle;
+            labels.clear();
+        }
+        labels.insert(label.to_string())
+    }
+
+    fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        let result = serde_json::to_string_pretty(&self.ledger)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let tmp_path = path.with_extension("json.tmp");
+                fs::write(&tmp_path, json).map_err(|e| e.to_string())?;
+                fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            println!("⚠️ Failed to save skip budget: {}", e);
+        }
+    }
+}
+
+pub fn apply_settings(settings: &AppSettings) {
+    if let Ok(mut state) = state().lock() {
+        state.policy = SkipPolicy::from(settings);
+    }
+}
+
+/// Load today's skips, so restarting the app doesn't refill the budget.
+pub fn init(app_handle: &AppHandle) {
+    let path = match settings::app_data_dir(app_handle) {
+        Ok(dir) => dir.join(LEDGER_FILE),
+        Err(e) => {
+            println!("⚠️ {}, the skip budget won't survive a restart", e);
+            return;
+        }
+    };
+    let ledger = fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<SkipLedger>(&json).ok());
+    if let Ok(mut state) = state().lock() {
+        state.ledger = ledger.unwrap_or_default();
+        state.path = Some(path);
+        state.roll_over();
+    }
+}
+
+/// Ask to skip a force break. An allowed skip uses up one of today's; a refused
+/// one is logged. Returns the skips left today (`None` without a limit).
+pub fn request(kind: SkipKind, shown_seconds: Option<u32>) -> Result<Option<u32>, String> {
+    spend(kind, shown_seconds, None)
+}
+
+/// Like `request`, for closing the force break window `label` during the break
+/// of `cycle`. One attempt to close can reach the window several times, so only
+/// the first refusal per window and break is logged.
+pub fn request_close(kind: SkipKind, shown_seconds: Option<u32>, cycle: u32, label: &str) -> Result<Option<u32>, String> {
+    spend(kind, shown_seconds, Some((cycle, label)))
+}
+
+fn spend(kind: SkipKind, shown_seconds: Option<u32>, close: Option<(u32, &str)>) -> Result<Option<u32>, String> {
+    let mut state = state()
+        .lock()
+        .map_err(|e| format!("Skip budget unavailable: {}", e))?;
+    state.roll_over();
+    state.spend(kind, shown_seconds, close)
+}
+
+pub fn status() -> SkipStatus {
+    match state().lock() {
+        Ok(mut state) => {
+            state.roll_over();
+            state.status()
+        }
+        Err(_) => SkipStatus {
+            strict: false,
+            daily_limit: None,
+            used_today: 0,
+            remaining_today: None,
+            refused_today: 0,
+            unlock_delay_seconds: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limited(limit: u32) -> SkipPolicy {
+        SkipPolicy {
+            strict: false,
+            daily_limit: Some(limit),
+            unlock_delay_seconds: 0,
+        }
+    }
+
+    #[test]
+    fn strict_mode_refuses_everything() {
+        let policy = SkipPolicy {
+            strict: true,
+            ..SkipPolicy::default()
+        };
+        assert!(policy.decide(0, None).is_err());
+        assert!(policy.decide(0, Some(600)).is_err());
+        assert_eq!(policy.remaining(0), Some(0));
+    }
+
+    #[test]
+    fn daily_limit_caps_skips() {
+        let policy = limited(2);
+        assert!(policy.decide(1, None).is_ok());
+        assert_eq!(policy.decide(2, None), Err("No skips left today (limit 2)".to_string()));
+        assert_eq!(policy.remaining(1), Some(1));
+        assert_eq!(policy.remaining(5), Some(0));
+        assert_eq!(SkipPolicy::default().remaining(100), None);
+    }
+
+    #[test]
+    fn early_return_unlocks_after_the_delay() {
+        let policy = SkipPolicy {
+            unlock_delay_seconds: 30,
+            ..SkipPolicy::default()
+        };
+        assert_eq!(policy.decide(0, Some(10)), Err("The break can be ended early in 20s".to_string()));
+        assert!(policy.decide(0, Some(30)).is_ok());
+        // A skip before the break starts isn't held back by the delay
+        assert!(policy.decide(0, None).is_ok());
+    }
+
+    #[test]
+    fn zero_limit_in_settings_means_unlimited() {
+        let settings = AppSettings {
+            daily_skip_limit: 0,
+            ..AppSettings::default()
+        };
+        assert_eq!(SkipPolicy::from(&settings).daily_limit, None);
+    }
+
+    #[test]
+    fn allowed_skips_use_up_the_budget() {
+        let mut state = SkipState {
+            policy: limited(1),
+            ..SkipState::default()
+        };
+        assert_eq!(state.spend(SkipKind::EarlyReturn, Some(60), None), Ok(Some(0)));
+        assert!(state.spend(SkipKind::EarlyReturn, Some(60), None).is_err());
+        assert_eq!(state.ledger.used, 1);
+    }
+
+    #[test]
+    fn refused_closes_are_logged_once_per_window_and_break() {
+        let mut state = SkipState {
+            policy: limited(0),
+            ..SkipState::default()
+        };
+        for _ in 0..3 {
+            assert!(state.spend(SkipKind::EarlyReturn, Some(60), Some((1, "force_break_0"))).is_err());
+        }
+        assert_eq!(state.ledger.refused, 1);
+        assert!(state.spend(SkipKind::EarlyReturn, Some(60), Some((1, "force_break_1"))).is_err());
+        assert_eq!(state.ledger.refused, 2);
+        // The next break logs again
+        assert!(state.spend(SkipKind::EarlyReturn, Some(60), Some((2, "force_break_0"))).is_err());
+        assert_eq!(state.ledger.refused, 3);
+    }
+
+    #[test]
+    fn every_refused_request_is_logged() {
+        let mut state = SkipState {
+            policy: limited(0),
+            ..SkipState::default()
+        };
+        for _ in 0..2 {
+            assert!(state.spend(SkipKind::Skip, None, None).is_err());
+        }
+        assert_eq!(state.ledger.refused, 2);
+        assert_eq!(state.ledger.attempts.len(), 2);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut state = SkipState::default();
+        for _ in 0..HISTORY_LIMIT + 5 {
+            state.record(SkipKind::Skip, None);
+        }
+        assert_eq!(state.ledger.attempts.len(), HISTORY_LIMIT);
+        assert_eq!(state.ledger.used, (HISTORY_LIMIT + 5) as u32);
+    }
+}
@@ -0,0 +1,235 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A gap shorter than this between two samples is treated as scheduling jitter.
+const MIN_SLEEP: Duration = Duration::from_secs(30);
+
+/// Sleep notifications arriving this soon after a handled resume are duplicates
+/// (logind and the gap detector usually both see the same suspend).
+const DEDUPE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Notifications from OS-specific power sources.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerEvent {
+    Suspending { at: SystemTime },
+    Resumed { at: SystemTime },
+}
+
+/// Detects that the machine was suspended and for how long.
+///
+/// Two sources feed it:
+/// - OS notifications (logind `PrepareForSleep` on Linux) sent through `sender()`;
+/// - a portable fallback comparing how far the monotonic and wall clocks moved
+///   between two `poll` calls. `Instant` stops during suspend on Linux, and on
+///   platforms where it keeps running the driver thread simply misses ticks, so
+///   either gap reveals a sleep.
+///
+/// All time comes in through `poll`, so it can be driven by a fake clock.
+pub struct SleepMonitor {
+    expected_interval: Duration,
+    last_sample: Option<(Instant, SystemTime)>,
+    suspended_at: Option<SystemTime>,
+    last_resume: Option<SystemTime>,
+    tx: Sender<PowerEvent>,
+    rx: Receiver<PowerEvent>,
+}
+
+impl SleepMonitor {
+    /// `expected_interval` is how often `poll` is normally called.
+    pub fn new(expected_interval: Duration) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            expected_interval,
+            last_sample: None,
+            suspended_at: None,
+            last_resume: None,
+            tx,
+            rx,
+        }
+    }
+
+    /// Channel for OS power notifications.
+    pub fn sender(&self) -> Sender<PowerEvent> {
+        self.tx.clone()
+    }
+
+    /// Sample the clocks and drain pending notifications.
+    /// Returns how long the machine slept if a resume was detected since the last call.
+    pub fn poll(&mut self, now: Instant, wall: SystemTime) -> Option<Duration> {
+        let mut slept = None;
+
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                PowerEvent::Suspending { at } => self.suspended_at = Some(at),
+                PowerEvent::Resumed { at } => {
+                    if let Some(start) = self.suspended_at.take() {
+                        let duration = at.duration_since(start).unwrap_or(Duration::ZERO);
+                        slept = slept.max(self.accept(at, duration));
+                    }
+                }
+            }
+        }
+
+        if let Some((last_now, last_wall)) = self.last_sample {
+            let mono = now.saturating_duration_since(last_now);
+            match wall.duration_since(last_wall) {
+                Ok(wall_elapsed) => {
+                    // Monotonic clock stood still while the wall clock moved on
+                    let frozen = wall_elapsed.saturating_sub(mono);
+                    // Monotonic clock moved on but we never got scheduled
+                    let missed = mono.saturating_sub(self.expected_interval);
+                    let gap = frozen.max(missed);
+                    if gap >= MIN_SLEEP && self.suspended_at.is_none() {
+                        slept = slept.max(self.accept(wall, gap));
+                    }
+                }
+                Err(e) => {
+                    // Wall clock went backwards (manual change or NTP step); deadlines
+                    // are monotonic so scheduling is unaffected.
+                    println!("🕰️ Wall clock jumped back by {:?}", e.duration());
+                }
+            }
+        }
+
+        self.last_sample = Some((now, wall));
+        slept
+    }
+
+    fn accept(&mut self, at: SystemTime, duration: Duration) -> Option<Duration> {
+        if duration < MIN_SLEEP {
+            return None;
+        }
+        if let Some(last) = self.last_resume {
+            let since = at.duration_since(last).unwrap_or(Duration::ZERO);
+            if since < DEDUPE_WINDOW {
+                return None;
+            }
+        }
+        self.last_resume = Some(at);
+        println!("💤 System resumed after sleeping {:?}", duration);
+        Some(duration)
+    }
+}
+
+/// Listen for logind `PrepareForSleep` on the system bus and forward it to `tx`.
+/// Returns quietly if logind is not reachable; the gap detector still works.
+#[cfg(target_os = "linux")]
+pub fn spawn_logind_listener(tx: Sender<PowerEvent>) {
+    std::thread::spawn(move || {
+        if let Err(e) = listen_prepare_for_sleep(&tx) {
+            println!("ℹ️ logind sleep signals unavailable: {}", e);
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn listen_prepare_for_sleep(tx: &Sender<PowerEvent>) -> zbus::Result<()> {
+    use zbus::blocking::{Connection, Proxy};
+
+    let connection = Connection::system()?;
+    let proxy = Proxy::new(
+        &connection,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+    )?;
+
+    println!("💤 Listening for logind PrepareForSleep");
+    for message in proxy.receive_signal("PrepareForSleep")? {
+        let starting: bool = message.body().deserialize()?;
+        let at = SystemTime::now();
+        let event = if starting {
+            PowerEvent::Suspending { at }
+        } else {
+            PowerEvent::Resumed { at }
+        };
+        if tx.send(event).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn spawn_logind_listener(_tx: Sender<PowerEvent>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_secs(1);
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    /// A monitor that has taken its first sample at `(now, wall)`.
+    fn started() -> (SleepMonitor, Instant, SystemTime) {
+        let mut monitor = SleepMonitor::new(TICK);
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        assert_eq!(monitor.poll(now, wall), None);
+        (monitor, now, wall)
+    }
+
+    #[test]
+    fn regular_ticks_are_not_sleep() {
+        let (mut monitor, mut now, mut wall) = started();
+        for _ in 0..10 {
+            now += TICK;
+            wall += TICK;
+            assert_eq!(monitor.poll(now, wall), None);
+        }
+    }
+
+    #[test]
+    fn frozen_monotonic_clock_reveals_sleep() {
+        let (mut monitor, now, wall) = started();
+        assert_eq!(monitor.poll(now + TICK, wall + 10 * MINUTE), Some(10 * MINUTE - TICK));
+    }
+
+    #[test]
+    fn missed_ticks_reveal_sleep() {
+        let (mut monitor, now, wall) = started();
+        assert_eq!(monitor.poll(now + 10 * MINUTE, wall + 10 * MINUTE), Some(10 * MINUTE - TICK));
+    }
+
+    #[test]
+    fn short_stalls_are_jitter() {
+        let (mut monitor, now, wall) = started();
+        let stall = Duration::from_secs(20);
+        assert_eq!(monitor.poll(now + stall, wall + stall), None);
+    }
+
+    #[test]
+    fn wall_clock_jumping_back_is_not_sleep() {
+        let (mut monitor, now, wall) = started();
+        assert_eq!(monitor.poll(now + TICK, wall - 10 * MINUTE), None);
+        assert_eq!(monitor.poll(now + 2 * TICK, wall - 10 * MINUTE + TICK), None);
+    }
+
+    #[test]
+    fn os_notification_and_gap_count_once() {
+        let (mut monitor, now, wall) = started();
+        let sender = monitor.sender();
+        sender.send(PowerEvent::Suspending { at: wall }).unwrap();
+        sender.send(PowerEvent::Resumed { at: wall + 5 * MINUTE }).unwrap();
+        assert_eq!(monitor.poll(now + TICK, wall + 5 * MINUTE), Some(5 * MINUTE));
+        assert_eq!(monitor.poll(now + 2 * TICK, wall + 5 * MINUTE + TICK), None);
+    }
+
+    #[test]
+    fn no_gap_detection_while_suspending() {
+        let (mut monitor, now, wall) = started();
+        monitor.sender().send(PowerEvent::Suspending { at: wall }).unwrap();
+        // Still waiting for the resume notification, which carries the length
+        assert_eq!(monitor.poll(now + TICK, wall + 5 * MINUTE), None);
+        monitor.sender().send(PowerEvent::Resumed { at: wall + 5 * MINUTE }).unwrap();
+        assert_eq!(monitor.poll(now + 2 * TICK, wall + 5 * MINUTE + TICK), Some(5 * MINUTE));
+    }
+
+    #[test]
+    fn second_sleep_after_the_dedupe_window_counts() {
+        let (mut monitor, now, wall) = started();
+        assert!(monitor.poll(now + TICK, wall + 10 * MINUTE).is_some());
+        let (now, wall) = (now + 2 * TICK, wall + 10 * MINUTE + TICK);
+        assert_eq!(monitor.poll(now, wall), None);
+        assert_eq!(monitor.poll(now + TICK, wall + 10 * MINUTE), Some(10 * MINUTE - TICK));
+    }
+}
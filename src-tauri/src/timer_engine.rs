@@ -0,0 +1,1843 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+use crate::break_tiers::{self, BreakTier};
+use crate::calendar;
+use crate::calendar::schedule::{self, BreakPlan, SchedulePolicy};
+use crate::idle_monitor::IdleMonitor;
+use crate::lifecycle::{self, LifecycleEvent};
+use crate::meeting_detector;
+use crate::pomodoro::{self, PomodoroPlan, PomodoroProgress};
+use crate::postpone_policy::{
+    self, PostponeDecision, PostponePolicy, PostponeRecord, SnoozeOffer, SnoozePolicy, MAX_SNOOZE_MINUTES,
+};
+use crate::screen_activity;
+use crate::screen_lock::{self, LockEvent};
+use crate::settings::{self, AppSettings, BusyScreenAction, OverduePolicy};
+use crate::sleep_monitor::{self, SleepMonitor};
+use crate::timer_persistence::{self, PersistedTimer};
+use crate::window_manager::WindowManager;
+
+/// How often the driver thread advances the engine.
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often the driver asks the OS for the user's idle time.
+const IDLE_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often the driver checks the calendar for meetings near the next break.
+const CALENDAR_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How far ahead the calendar is read.
+const CALENDAR_HORIZON: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Calendar moves allowed per work cycle, so a changing calendar can't keep
+/// shifting the same break back and forth.
+const MAX_CALENDAR_MOVES: u32 = 3;
+
+/// An early-ended break counts as taken if the user was away for at least this
+/// long (or for half the break, if that is shorter).
+const AWAY_CONFIRM: Duration = Duration::from_secs(30);
+
+/// Event carrying a `TimerSnapshot`, emitted on every visible change.
+pub const TIMER_STATE_EVENT: &str = "timer-state";
+
+/// Phases of a work/break cycle.
+///
+/// ```text
+/// Idle → Working → PreBreak → OnBreak → Working (recurring) / Idle
+///           ↘          ↘
+///            Postponed (meeting detected at break time, re-checked until it ends) → OnBreak
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerPhase {
+    Idle,
+    Working,
+    PreBreak,
+    OnBreak,
+    Postponed,
+}
+
+/// The parts of `AppSettings` the engine schedules against.
+#[derive(Clone, Debug)]
+pub struct TimerConfig {
+    /// The main break first, then the extra tiers, each on its own schedule.
+    pub tiers: Vec<BreakTier>,
+    /// Set in Pomodoro mode, where it picks the tier instead of the tier schedules.
+    pub pomodoro: Option<PomodoroPlan>,
+    pub recurring: bool,
+    pub meeting_detect: bool,
+    pub auto_pause: bool,
+    /// Duck media instead of pausing it during notify-mode breaks.
+    pub duck_notify_breaks: bool,
+    pub sleep_counts_as_break: bool,
+    pub lock_counts_as_break: bool,
+    pub idle_detect: bool,
+    pub idle_pause_seconds: u32,
+    /// Idle time after which the work cycle starts over; `None` uses the break duration.
+    pub idle_reset_seconds: Option<u32>,
+    pub postpone: PostponePolicy,
+    pub snooze: SnoozePolicy,
+    /// `None` when calendar awareness is off.
+    pub calendar: Option<SchedulePolicy>,
+    pub fullscreen_action: BusyScreenAction,
+    pub presenting_action: BusyScreenAction,
+}
+
+impl From<&AppSettings> for TimerConfig {
+    fn from(settings: &AppSettings) -> Self {
+        Self {
+            tiers: settings.break_tiers(),
+            pomodoro: settings.pomodoro_plan(),
+            // Pomodoros follow each other by definition
+            recurring: settings.recurring || settings.pomodoro_plan().is_some(),
+            meeting_detect: settings.meeting_detect,
+            auto_pause: settings.auto_pause,
+            duck_notify_breaks: settings.duck_notify_breaks,
+            sleep_counts_as_break: settings.sleep_counts_as_break,
+            lock_counts_as_break: settings.lock_counts_as_break,
+            idle_detect: settings.idle_detect,
+            idle_pause_seconds: settings.idle_pause_minutes * 60,
+            idle_reset_seconds: match settings.idle_reset_minutes {
+                0 => None,
+                minutes => Some(minutes * 60),
+            },
+            postpone: PostponePolicy::from(settings),
+            snooze: SnoozePolicy::from(settings),
+            calendar: calendar::schedule_policy(settings),
+            fullscreen_action: settings.fullscreen_break_action,
+            presenting_action: settings.presenting_break_action,
+        }
+    }
+}
+
+/// Side effects requested by the engine. The engine itself never touches windows
+/// or the OS, so it can be driven by the tray, commands or a fake clock alike.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimerAction {
+    /// Show the pre-break warning with this many seconds left.
+    ShowPreBreak { remaining_seconds: u32 },
+    /// The work interval is over, or a postponed break is due for another check.
+    /// The driver checks for a meeting and a busy screen and hands the result
+    /// to `on_break_due`.
+    BreakDue,
+    /// The break was postponed for a meeting.
+    BreakPostponed { reason: String },
+    /// The user snoozed the upcoming break.
+    BreakSnoozed { cycle: u32, minutes: Option<u32>, reason: String },
+    /// Open the break UI for the given mode.
+    StartBreak { mode: String, duration: u32, auto_pause: bool, duck: bool },
+    /// Reopen the break UI after a restart, without touching media again.
+    ResumeBreak { mode: String, remaining_seconds: u32 },
+    /// The break of this cycle ran its full length.
+    BreakFinished { cycle: u32 },
+}
+
+/// Serializable view of the engine, sent to the UI with `TIMER_STATE_EVENT`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TimerSnapshot {
+    pub phase: TimerPhase,
+    pub paused: bool,
+    pub remaining_seconds: u32,
+    pub total_seconds: u32,
+    pub elapsed_seconds: u32,
+    pub break_mode: String,
+    pub break_seconds: u32,
+    /// Name of the tier the upcoming or running break belongs to.
+    pub break_tier: String,
+    pub recurring: bool,
+    pub cycle: u32,
+    pub postpone_count: u32,
+    /// Times the user snoozed this cycle's break.
+    pub snooze_count: u32,
+    /// The current pomodoro, while one runs.
+    pub pomodoro: Option<PomodoroProgress>,
+    /// Paused automatically because the user is away.
+    pub idle_paused: bool,
+    /// Why the break is postponed, while it is.
+    pub postpone_reason: Option<String>,
+    /// Why the calendar moved this cycle's break, if it did.
+    pub calendar_reason: Option<String>,
+}
+
+pub struct TimerEngine {
+    phase: TimerPhase,
+    config: Option<TimerConfig>,
+    /// Settings saved while a cycle is running; applied at the next phase change.
+    pending_config: Option<TimerConfig>,
+    deadline: Option<Instant>,
+    /// Set while paused; holds the time that was left when pausing.
+    paused_remaining: Option<Duration>,
+    phase_total: Duration,
+    pre_break_shown: bool,
+    cycle: u32,
+    /// Index of the tier the upcoming or running break belongs to.
+    tier: usize,
+    /// Seconds of work since each tier's last break, up to `work_since`.
+    tier_worked: Vec<u32>,
+    /// Start of the work not yet added to `tier_worked`; `None` while paused or on a break.
+    work_since: Option<Instant>,
+    /// Pomodoros finished since the timer started, counted when their break ends.
+    pomodoros: u32,
+    postpone_count: u32,
+    snooze_count: u32,
+    /// Total timed snoozes this cycle.
+    snoozed_seconds: u32,
+    /// The current pause was started by idle detection, not by the user.
+    idle_paused: bool,
+    /// The cycle already restarted during the current idle stretch.
+    idle_reset: bool,
+    /// Longest idle stretch seen during the current break.
+    break_away: Duration,
+    /// Whether an idle backend is available to confirm the user left during a break.
+    idle_supported: bool,
+    /// When the screen was locked, while it stays locked.
+    locked_since: Option<Instant>,
+    /// When the current work cycle started, i.e. the end of the last break.
+    cycle_started_at: Option<Instant>,
+    /// The break is postponed and the meeting it waits for was still going at the last check.
+    waiting_for_meeting: bool,
+    postpone_reason: Option<String>,
+    /// Recent postponements, newest last. Survives `stop()`.
+    postponements: VecDeque<PostponeRecord>,
+    calendar_moves: u32,
+    calendar_reason: Option<String>,
+    /// Mode of the break in progress, when it differs from the configured one.
+    break_mode_override: Option<String>,
+    /// Bumped on every state change so the driver knows when to persist.
+    revision: u64,
+}
+
+impl Default for TimerEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimerEngine {
+    pub fn new() -> Self {
+        Self {
+            phase: TimerPhase::Idle,
+            config: None,
+            pending_config: None,
+            deadline: None,
+            paused_remaining: None,
+            phase_total: Duration::ZERO,
+            pre_break_shown: false,
+            cycle: 0,
+            tier: 0,
+            tier_worked: Vec::new(),
+            work_since: None,
+            pomodoros: 0,
+            postpone_count: 0,
+            snooze_count: 0,
+            snoozed_seconds: 0,
+            idle_paused: false,
+            idle_reset: false,
+            break_away: Duration::ZERO,
+            idle_supported: false,
+            locked_since: None,
+            cycle_started_at: None,
+            waiting_for_meeting: false,
+            postpone_reason: None,
+            postponements: VecDeque::new(),
+            calendar_moves: 0,
+            calendar_reason: None,
+            break_mode_override: None,
+            revision: 0,
+        }
+    }
+
+    pub fn phase(&self) -> TimerPhase {
+        self.phase
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_remaining.is_some()
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Start a new work cycle. `work_seconds` overrides the main break's interval
+    /// (in Pomodoro mode, the work session length).
+    pub fn start(&mut self, now: Instant, mut config: TimerConfig, work_seconds: Option<u32>) -> Result<(), String> {
+        if let Some(seconds) = work_seconds {
+            // Override the tier rather than the countdown, so the tier schedules
+            // pick both the first break and the time until it
+            let overridden = if config.pomodoro.is_some() { config.tiers.len() } else { 1 };
+            for tier in config.tiers.iter_mut().take(overridden) {
+                tier.interval_seconds = seconds;
+            }
+        }
+        let main = config.tiers.first().ok_or("No breaks are configured")?;
+        if main.interval_seconds == 0 {
+            return Err("Break timer must be greater than 00:00".to_string());
+        }
+        if main.duration_seconds == 0 {
+            return Err("Break duration must be greater than 00:00".to_string());
+        }
+
+        self.tier_worked = vec![0; config.tiers.len()];
+        self.config = Some(config);
+        self.pending_config = None;
+        self.cycle = 0;
+        self.pomodoros = 0;
+        self.enter_working(now);
+        Ok(())
+    }
+
+    /// The tier of the upcoming or running break.
+    pub fn current_tier(&self) -> Option<&BreakTier> {
+        self.config.as_ref()?.tiers.get(self.tier)
+    }
+
+    /// Add the work since `work_since` to every tier's schedule, counting up to `until`.
+    fn bank_work(&mut self, until: Instant) {
+        if let Some(since) = self.work_since.take() {
+            let worked = until.saturating_duration_since(since).as_secs() as u32;
+            for tier_worked in &mut self.tier_worked {
+                *tier_worked = tier_worked.saturating_add(worked);
+            }
+        }
+    }
+
+    /// Count a rest of `rested_seconds` as a break of every tier it is long enough for.
+    fn credit_rest(&mut self, rested_seconds: u32) {
+        if let Some(config) = self.config.as_ref() {
+            break_tiers::credit_rest(&config.tiers, &mut self.tier_worked, rested_seconds);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        let revision = self.revision;
+        let idle_supported = self.idle_supported;
+        let locked_since = self.locked_since;
+        let postponements = std::mem::take(&mut self.postponements);
+        *self = Self::new();
+        self.revision = revision + 1;
+        self.idle_supported = idle_supported;
+        self.locked_since = locked_since;
+        self.postponements = postponements;
+    }
+
+    pub fn set_idle_supported(&mut self, supported: bool) {
+        self.idle_supported = supported;
+    }
+
+    /// Replace the configuration. Takes effect at the next phase change so a
+    /// running countdown is never shortened or stretched by a settings save.
+    pub fn update_config(&mut self, config: TimerConfig) {
+        if self.phase == TimerPhase::Idle {
+            self.config = Some(config);
+        } else {
+            self.pending_config = Some(config);
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) -> bool {
+        if self.is_paused() || !matches!(self.phase, TimerPhase::Working | TimerPhase::PreBreak | TimerPhase::Postponed) {
+            return false;
+        }
+        self.paused_remaining = Some(self.remaining(now));
+        self.deadline = None;
+        self.bank_work(now);
+        self.revision += 1;
+        true
+    }
+
+    pub fn resume(&mut self, now: Instant) -> bool {
+        match self.paused_remaining.take() {
+            Some(remaining) => {
+                self.deadline = Some(now + remaining);
+                self.work_since = Some(now);
+                self.idle_paused = false;
+                self.idle_reset = false;
+                self.revision += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        if let Some(remaining) = self.paused_remaining {
+            return remaining;
+        }
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(now))
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Advance the engine to `now` and return the actions the driver must perform.
+    pub fn tick(&mut self, now: Instant) -> Vec<TimerAction> {
+        let mut actions = Vec::new();
+        if self.phase == TimerPhase::Idle || self.is_paused() {
+            return actions;
+        }
+
+        let remaining = self.remaining(now);
+        match self.phase {
+            TimerPhase::Working => {
+                if let Some(pre) = self.current_tier().and_then(|tier| tier.pre_break_seconds) {
+                    let pre = Duration::from_secs(pre as u64);
+                    if !self.pre_break_shown && !remaining.is_zero() && remaining <= pre {
+                        self.pre_break_shown = true;
+                        self.phase = TimerPhase::PreBreak;
+                        self.revision += 1;
+                        actions.push(TimerAction::ShowPreBreak {
+                            remaining_seconds: ceil_seconds(remaining),
+                        });
+                    }
+                }
+                if remaining.is_zero() {
+                    actions.push(TimerAction::BreakDue);
+                }
+            }
+            TimerPhase::PreBreak | TimerPhase::Postponed => {
+                if remaining.is_zero() {
+                    actions.push(TimerAction::BreakDue);
+                }
+            }
+            TimerPhase::OnBreak => {
+                if remaining.is_zero() {
+                    let cycle = self.cycle;
+                    self.finish_break(now);
+                    actions.push(TimerAction::BreakFinished { cycle });
+                }
+            }
+            TimerPhase::Idle => {}
+        }
+        actions
+    }
+
+    /// Push the break back by `seconds`.
+    pub fn postpone(&mut self, now: Instant, seconds: u32) {
+        if self.phase == TimerPhase::Idle {
+            return;
+        }
+        self.phase = TimerPhase::Postponed;
+        self.postpone_count += 1;
+        self.set_deadline(now, seconds);
+    }
+
+    /// Act on a due break, given what holds it back at break time: a meeting, or
+    /// a busy screen set to postpone (`None` if nothing does). Postpones, keeps
+    /// waiting or starts the break according to the `PostponePolicy`. A break
+    /// that starts while `downgrade` is set opens as a notify break instead.
+    pub fn on_break_due(
+        &mut self,
+        now: Instant,
+        now_wall: SystemTime,
+        meeting: Option<&str>,
+        downgrade: Option<&str>,
+    ) -> Option<TimerAction> {
+        if !matches!(self.phase, TimerPhase::Working | TimerPhase::PreBreak | TimerPhase::Postponed) {
+            return None;
+        }
+        let policy = self.config.as_ref()?.postpone.clone();
+        let since_last_break = self
+            .cycle_started_at
+            .map(|started| now.saturating_duration_since(started))
+            .unwrap_or(Duration::ZERO);
+
+        match policy.decide(meeting, self.waiting_for_meeting, self.postpone_count, since_last_break) {
+            PostponeDecision::Postpone { seconds, reason } => {
+                self.postpone(now, seconds);
+                self.waiting_for_meeting = true;
+                self.postpone_reason = Some(reason.clone());
+                self.record_postponement(now_wall, reason.clone(), false, false);
+                println!("🤝 Break postponed ({}): {}", self.postpone_count, reason);
+                Some(TimerAction::BreakPostponed { reason })
+            }
+            PostponeDecision::KeepWaiting { seconds } => {
+                self.set_deadline(now, seconds);
+                None
+            }
+            PostponeDecision::Grace { seconds } => {
+                println!("🤝 Meeting or presentation ended, break starts in {} seconds", seconds);
+                self.waiting_for_meeting = false;
+                self.postpone_reason = Some("Meeting ended".to_string());
+                self.set_deadline(now, seconds);
+                None
+            }
+            PostponeDecision::BreakNow => self.begin_break(now, downgrade),
+            PostponeDecision::Mandatory { reason } => {
+                println!("⛔ Break can't be postponed any longer: {}", reason);
+                self.record_postponement(now_wall, reason, true, false);
+                self.begin_break(now, downgrade)
+            }
+        }
+    }
+
+    /// What the calendar check needs: seconds until the break, the break length
+    /// and the policy. `None` unless a work countdown is running with calendar
+    /// awareness on and moves left this cycle.
+    pub fn calendar_check(&self, now: Instant) -> Option<(i64, i64, SchedulePolicy)> {
+        if self.phase != TimerPhase::Working || self.is_paused() || self.calendar_moves >= MAX_CALENDAR_MOVES {
+            return None;
+        }
+        let policy = self.config.as_ref()?.calendar.clone()?;
+        let break_length = self.current_tier()?.duration_seconds;
+        Some((self.remaining(now).as_secs() as i64, break_length as i64, policy))
+    }
+
+    /// Move the upcoming break to `seconds` from now, keeping the elapsed part
+    /// of the countdown. Returns false if no work countdown is running.
+    pub fn move_break(&mut self, now: Instant, seconds: u32, reason: String) -> bool {
+        if self.phase != TimerPhase::Working || self.is_paused() {
+            return false;
+        }
+        let elapsed = self.phase_total.saturating_sub(self.remaining(now));
+        let remaining = Duration::from_secs(seconds as u64);
+        println!("📅 {} — break in {}s", reason, seconds);
+        self.deadline = Some(now + remaining);
+        self.phase_total = elapsed + remaining;
+        self.calendar_moves += 1;
+        self.calendar_reason = Some(reason);
+        self.revision += 1;
+        true
+    }
+
+    /// Recent postponements, oldest first.
+    pub fn postponements(&self) -> Vec<PostponeRecord> {
+        self.postponements.iter().cloned().collect()
+    }
+
+    fn record_postponement(&mut self, now_wall: SystemTime, reason: String, mandatory: bool, snoozed: bool) {
+        if self.postponements.len() >= postpone_policy::HISTORY_LIMIT {
+            self.postponements.pop_front();
+        }
+        self.postponements.push_back(PostponeRecord {
+            at_unix_ms: timer_persistence::unix_ms(now_wall),
+            cycle: self.cycle,
+            postpone_count: self.postpone_count,
+            reason,
+            mandatory,
+            snoozed,
+        });
+    }
+
+    /// Snooze the upcoming break at the user's request: `minutes` later than it
+    /// would start, or until `meeting` ends when `minutes` is `None`. Limited per
+    /// cycle by the `SnoozePolicy`; a break that already started can't be snoozed.
+    pub fn snooze(
+        &mut self,
+        now: Instant,
+        now_wall: SystemTime,
+        minutes: Option<u32>,
+        meeting: Option<&str>,
+    ) -> Result<TimerAction, String> {
+        match self.phase {
+            TimerPhase::Idle => return Err("The timer isn't running".to_string()),
+            TimerPhase::OnBreak => return Err("The break has already started".to_string()),
+            _ if self.is_paused() => return Err("The timer is paused".to_string()),
+            _ => {}
+        }
+        let config = self.config.clone().ok_or("The timer isn't running")?;
+        match minutes {
+            Some(0) => return Err("Snooze for at least a minute".to_string()),
+            Some(minutes) if minutes > MAX_SNOOZE_MINUTES => {
+                return Err(format!("Snooze for at most {} minutes", MAX_SNOOZE_MINUTES));
+            }
+            _ => {}
+        }
+        let seconds = minutes.map(|minutes| minutes * 60);
+        config.snooze.decide(self.snooze_count, self.snoozed_seconds, seconds)?;
+
+        let remaining = ceil_seconds(self.remaining(now));
+        let reason = match minutes {
+            Some(minutes) => {
+                let deadline = remaining
+                    .checked_add(minutes * 60)
+                    .ok_or("The break is too far off to snooze")?;
+                self.waiting_for_meeting = false;
+                self.set_deadline(now, deadline);
+                self.snoozed_seconds = self.snoozed_seconds.saturating_add(minutes * 60);
+                format!("Snoozed for {} min", minutes)
+            }
+            None => {
+                if !config.meeting_detect {
+                    return Err("Turn on meeting detection to snooze until a meeting ends".to_string());
+                }
+                let meeting = meeting.ok_or("No meeting detected")?;
+                // From when it is due, the break waits like one postponed for a meeting
+                self.waiting_for_meeting = true;
+                let wait = if remaining > 0 { remaining } else { config.postpone.poll_seconds };
+                self.set_deadline(now, wait);
+                format!("Snoozed until the meeting ends — {}", meeting)
+            }
+        };
+        self.phase = TimerPhase::Postponed;
+        self.pre_break_shown = true;
+        self.snooze_count += 1;
+        self.postpone_reason = Some(reason.clone());
+        self.record_postponement(now_wall, reason.clone(), false, true);
+        println!("😴 Break snoozed ({}): {}", self.snooze_count, reason);
+        Ok(TimerAction::BreakSnoozed {
+            cycle: self.cycle,
+            minutes,
+            reason,
+        })
+    }
+
+    /// The snooze still available for this cycle's break, or `None`.
+    pub fn snooze_offer(&self) -> Option<SnoozeOffer> {
+        if matches!(self.phase, TimerPhase::Idle | TimerPhase::OnBreak) {
+            return None;
+        }
+        let config = self.config.as_ref()?;
+        config.snooze.offer(self.snooze_count, self.snoozed_seconds, config.meeting_detect)
+    }
+
+    /// Enter the break phase, as a notify break if `downgrade` gives a reason to.
+    /// Returns `None` if the timer was stopped meanwhile.
+    pub fn begin_break(&mut self, now: Instant, downgrade: Option<&str>) -> Option<TimerAction> {
+        if !matches!(self.phase, TimerPhase::Working | TimerPhase::PreBreak | TimerPhase::Postponed) {
+            return None;
+        }
+        let config = self.config.clone()?;
+        let tier = config.tiers.get(self.tier)?.clone();
+        self.bank_work(now);
+        self.phase = TimerPhase::OnBreak;
+        self.paused_remaining = None;
+        self.idle_paused = false;
+        self.idle_reset = false;
+        self.break_away = Duration::ZERO;
+        self.waiting_for_meeting = false;
+        self.postpone_reason = None;
+        self.set_deadline(now, tier.duration_seconds);
+        self.break_mode_override = match downgrade {
+            Some(reason) if tier.mode != "notify" => {
+                println!("🖥️ {} — showing a notify break instead of {}", reason, tier.mode);
+                Some("notify".to_string())
+            }
+            _ => None,
+        };
+        let mode = self.break_mode_override.clone().unwrap_or(tier.mode);
+        let duck = config.duck_notify_breaks && mode == "notify";
+        Some(TimerAction::StartBreak {
+            mode,
+            duration: tier.duration_seconds,
+            auto_pause: config.auto_pause,
+            duck,
+        })
+    }
+
+    /// End the current break (or skip the upcoming one): start the next cycle if
+    /// recurring is enabled, otherwise go idle. Returns false if nothing was running.
+    pub fn finish_break(&mut self, now: Instant) -> bool {
+        if self.phase == TimerPhase::Idle {
+            return false;
+        }
+        // A break, skipped or not, starts its tier's schedule over, and those of
+        // the shorter tiers it stands in for
+        self.bank_work(now);
+        if let Some(duration) = self.current_tier().map(|tier| tier.duration_seconds) {
+            self.credit_rest(duration);
+        }
+        // Skipping the break before it starts leaves the pomodoro unfinished
+        let pomodoro = self.config.as_ref().is_some_and(|config| config.pomodoro.is_some());
+        if pomodoro && self.phase == TimerPhase::OnBreak {
+            self.pomodoros += 1;
+        }
+        self.apply_pending_config();
+        let recurring = self.config.as_ref().map(|c| c.recurring).unwrap_or(false);
+        if recurring {
+            self.enter_working(now);
+        } else {
+            let config = self.config.take();
+            self.stop();
+            self.config = config;
+        }
+        true
+    }
+
+    pub fn snapshot(&self, now: Instant) -> TimerSnapshot {
+        let remaining = self.remaining(now);
+        let total = ceil_seconds(self.phase_total);
+        let remaining_seconds = ceil_seconds(remaining);
+        let config = self.config.as_ref();
+        let tier = self.current_tier();
+        TimerSnapshot {
+            phase: self.phase,
+            paused: self.is_paused(),
+            remaining_seconds,
+            total_seconds: total,
+            elapsed_seconds: total.saturating_sub(remaining_seconds),
+            break_mode: self
+                .break_mode_override
+                .clone()
+                .or_else(|| tier.map(|tier| tier.mode.clone()))
+                .unwrap_or_else(|| "force".to_string()),
+            break_seconds: tier.map(|tier| tier.duration_seconds).unwrap_or(0),
+            break_tier: tier.map(|tier| tier.name.clone()).unwrap_or_default(),
+            recurring: config.map(|c| c.recurring).unwrap_or(false),
+            cycle: self.cycle,
+            postpone_count: self.postpone_count,
+            snooze_count: self.snooze_count,
+            pomodoro: config
+                .and_then(|c| c.pomodoro)
+                .filter(|_| self.phase != TimerPhase::Idle)
+                .map(|plan| plan.progress(self.pomodoros)),
+            idle_paused: self.idle_paused,
+            postpone_reason: self.postpone_reason.clone(),
+            calendar_reason: self.calendar_reason.clone(),
+        }
+    }
+
+    /// Feed the user's current idle time. Pauses the work timer once the user has
+    /// been away for `idle_pause_seconds`, restarts the cycle once the absence is as
+    /// long as a break, and resumes on the first input. Returns true if anything changed.
+    pub fn on_idle_sample(&mut self, now: Instant, idle: Duration) -> bool {
+        if self.phase == TimerPhase::OnBreak {
+            self.break_away = self.break_away.max(idle);
+            return false;
+        }
+        let config = match self.config.as_ref() {
+            Some(config) if config.idle_detect && self.phase != TimerPhase::Idle => config,
+            _ => return false,
+        };
+        let pause_after = Duration::from_secs(config.idle_pause_seconds.max(1) as u64);
+        let longest_break = break_tiers::longest_break(&config.tiers).unwrap_or(0);
+        let reset_after = Duration::from_secs(
+            config.idle_reset_seconds.unwrap_or(longest_break) as u64,
+        )
+        .max(pause_after);
+
+        if self.idle_paused {
+            if idle < pause_after {
+                println!("⌨️ User is back — resuming work timer");
+                self.resume(now);
+                return true;
+            }
+            if idle >= reset_after && !self.idle_reset {
+                println!("🚶 Away for {:?} — counting it as a break", idle);
+                self.credit_rest(idle.as_secs() as u32);
+                self.apply_pending_config();
+                self.enter_working(now);
+                // Stay paused with a full interval until the user returns
+                self.paused_remaining = Some(self.phase_total);
+                self.deadline = None;
+                self.work_since = None;
+                self.idle_paused = true;
+                self.idle_reset = true;
+                return true;
+            }
+            return false;
+        }
+
+        if !self.is_paused()
+            && matches!(self.phase, TimerPhase::Working | TimerPhase::PreBreak | TimerPhase::Postponed)
+            && idle >= pause_after
+        {
+            println!("🚶 Idle for {:?} — pausing work timer", idle);
+            self.pause(now);
+            self.idle_paused = true;
+            return true;
+        }
+        false
+    }
+
+    /// The user closed the break before it was over. Moves on like `finish_break`
+    /// and reports whether idle detection confirms they actually left the computer
+    /// (`None` when no idle backend is available).
+    pub fn end_break_early(&mut self, now: Instant) -> Option<bool> {
+        let user_left = if self.phase == TimerPhase::OnBreak && self.idle_supported {
+            let break_length = self.phase_total;
+            Some(self.break_away >= AWAY_CONFIRM.min(break_length / 2))
+        } else {
+            None
+        };
+        self.finish_break(now);
+        user_left
+    }
+
+    /// Called after the machine slept for `slept`. A sleep at least as long as a
+    /// break counts as one: the work cycle starts over. Returns true if it did.
+    pub fn on_system_resume(&mut self, now: Instant, slept: Duration) -> bool {
+        if self.phase == TimerPhase::Idle || self.is_paused() || !self.counts_as_break(slept) {
+            return false;
+        }
+
+        println!("💤 Slept {:?} — counting it as a break and restarting the work cycle", slept);
+        if self.phase == TimerPhase::OnBreak {
+            self.finish_break(now);
+        } else {
+            self.bank_work(now.checked_sub(slept).unwrap_or(now));
+            self.credit_rest(slept.as_secs() as u32);
+            self.apply_pending_config();
+            self.enter_working(now);
+        }
+        true
+    }
+
+    pub fn on_screen_locked(&mut self, at: Instant) {
+        println!("🔒 Screen locked");
+        self.locked_since.get_or_insert(at);
+    }
+
+    /// The screen was unlocked. A lock at least as long as the break counts as
+    /// one and restarts the work cycle, like a long sleep. Returns true if the
+    /// cycle was restarted.
+    pub fn on_screen_unlocked(&mut self, at: Instant) -> bool {
+        let locked_for = match self.locked_since.take() {
+            Some(since) => at.saturating_duration_since(since),
+            None => return false,
+        };
+        println!("🔓 Screen unlocked after {:?}", locked_for);
+        if self.phase == TimerPhase::OnBreak {
+            // A locked screen is as good as idle for confirming the user left
+            self.break_away = self.break_away.max(locked_for);
+            return false;
+        }
+        if self.phase == TimerPhase::Idle || self.is_paused() {
+            return false;
+        }
+        let config = match self.config.as_ref() {
+            Some(config) if config.lock_counts_as_break => config,
+            _ => return false,
+        };
+        let shortest_break = break_tiers::shortest_break(&config.tiers).unwrap_or(0);
+        if locked_for < Duration::from_secs(shortest_break as u64) {
+            return false;
+        }
+
+        println!("🔒 Locked for {:?} — counting it as a break and restarting the work cycle", locked_for);
+        self.bank_work(at.checked_sub(locked_for).unwrap_or(at));
+        self.credit_rest(locked_for.as_secs() as u32);
+        self.apply_pending_config();
+        self.enter_working(at);
+        true
+    }
+
+    /// Whether this long away from the computer, asleep or with the app
+    /// closed, counts as a break.
+    fn counts_as_break(&self, away: Duration) -> bool {
+        let config = match self.config.as_ref() {
+            Some(config) if config.sleep_counts_as_break => config,
+            _ => return false,
+        };
+        let shortest_break = break_tiers::shortest_break(&config.tiers).unwrap_or(0);
+        away >= Duration::from_secs(shortest_break as u64)
+    }
+
+    /// Capture the cycle for `timer_state.json`, or `None` when idle.
+    pub fn to_persisted(&self, now: Instant, now_wall: SystemTime, media_paused_by_us: bool) -> Option<PersistedTimer> {
+        if self.phase == TimerPhase::Idle {
+            return None;
+        }
+        let mut state = PersistedTimer::new(self.phase);
+        match self.paused_remaining {
+            Some(remaining) => state.paused_remaining_ms = Some(remaining.as_millis() as u64),
+            None => {
+                let remaining = self.remaining(now);
+                state.deadline_unix_ms = Some(timer_persistence::unix_ms(now_wall + remaining));
+            }
+        }
+        state.phase_total_ms = self.phase_total.as_millis() as u64;
+        state.pre_break_shown = self.pre_break_shown;
+        state.cycle = self.cycle;
+        state.break_tier = self.tier;
+        let unbanked = self
+            .work_since
+            .map(|since| now.saturating_duration_since(since).as_secs() as u32)
+            .unwrap_or(0);
+        state.tier_worked_seconds = self
+            .tier_worked
+            .iter()
+            .map(|worked| worked.saturating_add(unbanked))
+            .collect();
+        state.pomodoros = self.pomodoros;
+        state.postpone_count = self.postpone_count;
+        state.snooze_count = self.snooze_count;
+        state.snoozed_seconds = self.snoozed_seconds;
+        state.cycle_started_unix_ms = self
+            .cycle_started_at
+            .and_then(|started| now_wall.checked_sub(now.saturating_duration_since(started)))
+            .map(timer_persistence::unix_ms);
+        state.waiting_for_meeting = self.waiting_for_meeting;
+        state.postpone_reason = self.postpone_reason.clone();
+        state.break_mode_override = self.break_mode_override.clone();
+        state.media_paused_by_us = media_paused_by_us;
+        state.saved_at_unix_ms = timer_persistence::unix_ms(now_wall);
+        Some(state)
+    }
+
+    /// Rebuild the engine from a saved cycle. Time the app was down counts as a
+    /// break if it would for a sleep; otherwise a break that became due while
+    /// the app was down is handled according to `policy`.
+    pub fn restore(
+        &mut self,
+        saved: &PersistedTimer,
+        config: TimerConfig,
+        policy: OverduePolicy,
+        now: Instant,
+        now_wall: SystemTime,
+    ) -> Vec<TimerAction> {
+        let mut actions = Vec::new();
+        self.stop();
+        if saved.phase == TimerPhase::Idle {
+            self.config = Some(config);
+            return actions;
+        }
+
+        let break_mode = saved
+            .break_mode_override
+            .clone()
+            .or_else(|| config.tiers.get(saved.break_tier).map(|tier| tier.mode.clone()))
+            .unwrap_or_else(|| "force".to_string());
+        self.tier_worked = saved.tier_worked_seconds.clone();
+        self.tier_worked.resize(config.tiers.len(), 0);
+        // Files from older versions, or tiers removed since, fall back to the main break
+        self.tier = if saved.break_tier < config.tiers.len() { saved.break_tier } else { 0 };
+        self.config = Some(config);
+        self.phase = saved.phase;
+        self.phase_total = Duration::from_millis(saved.phase_total_ms);
+        self.pre_break_shown = saved.pre_break_shown;
+        self.cycle = saved.cycle;
+        self.pomodoros = saved.pomodoros;
+        self.postpone_count = saved.postpone_count;
+        self.snooze_count = saved.snooze_count;
+        self.snoozed_seconds = saved.snoozed_seconds;
+        self.waiting_for_meeting = saved.waiting_for_meeting;
+        self.postpone_reason = saved.postpone_reason.clone();
+        self.break_mode_override = saved.break_mode_override.clone();
+        // Files from older versions lack the cycle start; count from now then
+        let worked = saved
+            .cycle_started_unix_ms
+            .map(timer_persistence::from_unix_ms)
+            .and_then(|started| now_wall.duration_since(started).ok())
+            .unwrap_or(Duration::ZERO);
+        self.cycle_started_at = Some(now.checked_sub(worked).unwrap_or(now));
+
+        // A paused cycle cannot become overdue
+        if let Some(paused_ms) = saved.paused_remaining_ms {
+            self.paused_remaining = Some(Duration::from_millis(paused_ms));
+            return actions;
+        }
+
+        // Time the app was down is rest, like a sleep; long enough, it's a break
+        let down = now_wall
+            .duration_since(timer_persistence::from_unix_ms(saved.saved_at_unix_ms))
+            .unwrap_or(Duration::ZERO);
+        if self.counts_as_break(down) {
+            println!("💤 Down for {:?} — counting it as a break and restarting the work cycle", down);
+            if self.phase == TimerPhase::OnBreak {
+                let cycle = self.cycle;
+                self.finish_break(now);
+                actions.push(TimerAction::BreakFinished { cycle });
+            } else {
+                self.credit_rest(down.as_secs() as u32);
+                self.enter_working(now);
+            }
+            return actions;
+        }
+
+        let remaining = saved
+            .deadline_unix_ms
+            .map(timer_persistence::from_unix_ms)
+            .and_then(|deadline| deadline.duration_since(now_wall).ok())
+            .unwrap_or(Duration::ZERO);
+        self.deadline = Some(now + remaining);
+        if self.phase != TimerPhase::OnBreak {
+            self.work_since = Some(now);
+        }
+
+        if !remaining.is_zero() {
+            match self.phase {
+                TimerPhase::OnBreak => actions.push(TimerAction::ResumeBreak {
+                    mode: break_mode,
+                    remaining_seconds: ceil_seconds(remaining),
+                }),
+                TimerPhase::PreBreak => actions.push(TimerAction::ShowPreBreak {
+                    remaining_seconds: ceil_seconds(remaining),
+                }),
+                _ => {}
+            }
+            return actions;
+        }
+
+        if self.phase == TimerPhase::OnBreak {
+            // The break ran out while we were down
+            let cycle = self.cycle;
+            self.finish_break(now);
+            actions.push(TimerAction::BreakFinished { cycle });
+            return actions;
+        }
+
+        println!("⏰ Break became due while the app was not running (policy: {:?})", policy);
+        match policy {
+            OverduePolicy::Fire => actions.push(TimerAction::BreakDue),
+            OverduePolicy::Skip => {
+                self.finish_break(now);
+            }
+            OverduePolicy::Restart => {
+                self.tier_worked.iter_mut().for_each(|worked| *worked = 0);
+                self.enter_working(now);
+            }
+        }
+        actions
+    }
+
+    /// Start the countdown to the next break due on any tier. In Pomodoro mode,
+    /// to the short or long break ending the next pomodoro.
+    fn enter_working(&mut self, now: Instant) {
+        let next = match self.config.as_ref() {
+            Some(config) => {
+                self.tier_worked.resize(config.tiers.len(), 0);
+                match config.pomodoro {
+                    Some(plan) => plan.next_break(&config.tiers, self.pomodoros),
+                    None => break_tiers::next_break(&config.tiers, &self.tier_worked),
+                }
+            }
+            None => None,
+        };
+        self.tier = next.map(|next| next.tier).unwrap_or(0);
+        let work = next.map(|next| next.in_seconds).unwrap_or(0);
+        self.work_since = Some(now);
+        self.phase = TimerPhase::Working;
+        self.paused_remaining = None;
+        self.idle_paused = false;
+        self.idle_reset = false;
+        self.pre_break_shown = false;
+        self.cycle += 1;
+        self.postpone_count = 0;
+        self.snooze_count = 0;
+        self.snoozed_seconds = 0;
+        self.cycle_started_at = Some(now);
+        self.waiting_for_meeting = false;
+        self.postpone_reason = None;
+        self.calendar_moves = 0;
+        self.calendar_reason = None;
+        self.break_mode_override = None;
+        self.set_deadline(now, work);
+    }
+
+    fn set_deadline(&mut self, now: Instant, seconds: u32) {
+        let duration = Duration::from_secs(seconds as u64);
+        self.phase_total = duration;
+        self.deadline = Some(now + duration);
+        self.revision += 1;
+    }
+
+    fn apply_pending_config(&mut self) {
+        if let Some(config) = self.pending_config.take() {
+            self.config = Some(config);
+        }
+    }
+}
+
+fn ceil_seconds(duration: Duration) -> u32 {
+    let millis = duration.as_millis();
+    millis.div_ceil(1000) as u32
+}
+
+static TIMER_ENGINE: OnceLock<Mutex<TimerEngine>> = OnceLock::new();
+
+/// The process-wide timer engine.
+pub fn timer_engine() -> &'static Mutex<TimerEngine> {
+    TIMER_ENGINE.get_or_init(|| Mutex::new(TimerEngine::new()))
+}
+
+/// The current work cycle, counted from when the timer started.
+pub fn current_cycle() -> u32 {
+    match timer_engine().lock() {
+        Ok(engine) => engine.cycle,
+        Err(_) => 0,
+    }
+}
+
+/// Emit the current snapshot to every window.
+pub fn emit_state(app_handle: &AppHandle) {
+    let snapshot = match timer_engine().lock() {
+        Ok(engine) => engine.snapshot(Instant::now()),
+        Err(_) => return,
+    };
+    send_state(app_handle, &snapshot);
+}
+
+/// Send a snapshot as `TIMER_STATE_EVENT` and as a `TimerTick` lifecycle event.
+fn send_state(app_handle: &AppHandle, snapshot: &TimerSnapshot) {
+    if let Err(e) = app_handle.emit(TIMER_STATE_EVENT, snapshot) {
+        println!("⚠️ Failed to emit timer state: {}", e);
+    }
+    lifecycle::emit(app_handle, LifecycleEvent::TimerTick { state: snapshot.clone() });
+    crate::set_tray_status(app_handle, tray_status(snapshot).as_deref());
+}
+
+/// Tray text for a running Pomodoro, e.g. `Pomodoro 3/4 — 12:04`.
+fn tray_status(snapshot: &TimerSnapshot) -> Option<String> {
+    let progress = snapshot.pomodoro.as_ref()?;
+    let label = match snapshot.phase {
+        TimerPhase::OnBreak => snapshot.break_tier.as_str(),
+        _ => "Pomodoro",
+    };
+    Some(pomodoro::status_text(label, progress, snapshot.remaining_seconds, snapshot.paused))
+}
+
+/// Write the current cycle to disk, or remove the file when idle.
+fn persist(app_handle: &AppHandle) {
+    let state = match timer_engine().lock() {
+        Ok(engine) => engine.to_persisted(Instant::now(), SystemTime::now(), crate::get_media_was_playing()),
+        Err(_) => return,
+    };
+    match state {
+        Some(state) => {
+            if let Err(e) = timer_persistence::save(app_handle, &state) {
+                println!("⚠️ Failed to persist timer state: {}", e);
+            }
+        }
+        None => timer_persistence::clear(app_handle),
+    }
+}
+
+/// Restore the cycle saved before the last shutdown or crash.
+fn restore(app_handle: &AppHandle) -> Vec<TimerAction> {
+    let saved = match timer_persistence::load(app_handle) {
+        Some(saved) => saved,
+        None => return Vec::new(),
+    };
+    let settings = settings::load_or_default(app_handle);
+    println!("♻️ Restoring timer state: {:?} (cycle {})", saved.phase, saved.cycle);
+
+    // Break windows resume media based on this flag
+    crate::set_media_was_playing(saved.media_paused_by_us);
+
+    match timer_engine().lock() {
+        Ok(mut engine) => engine.restore(
+            &saved,
+            TimerConfig::from(&settings),
+            settings.overdue_break_policy,
+            Instant::now(),
+            SystemTime::now(),
+        ),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Spawn the thread that advances the engine and carries out its actions.
+/// The schedule keeps running even when the main webview is hidden or throttled.
+pub fn spawn_driver(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut last_snapshot: Option<TimerSnapshot> = None;
+        let mut last_revision: Option<u64> = None;
+        let mut sleep_monitor = SleepMonitor::new(TICK_INTERVAL);
+        sleep_monitor::spawn_logind_listener(sleep_monitor.sender());
+        let (lock_tx, lock_rx) = mpsc::channel();
+        screen_lock::spawn_lock_listener(lock_tx);
+
+        let mut idle_monitor = IdleMonitor::detect();
+        let mut last_idle_sample: Option<Instant> = None;
+        let mut last_calendar_check: Option<Instant> = None;
+        if let Ok(mut engine) = timer_engine().lock() {
+            engine.set_idle_supported(idle_monitor.is_available());
+        }
+
+        for action in restore(&app_handle) {
+            run_action(&app_handle, action);
+        }
+
+        loop {
+            thread::sleep(TICK_INTERVAL);
+
+            // Check for a suspend before ticking, so a deadline that passed while
+            // asleep doesn't fire a break we no longer need
+            if let Some(slept) = sleep_monitor.poll(Instant::now(), SystemTime::now()) {
+                let reset = match timer_engine().lock() {
+                    Ok(mut engine) => engine.on_system_resume(Instant::now(), slept),
+                    Err(_) => false,
+                };
+                if reset {
+                    WindowManager::close_existing_window(&app_handle, "pre_break");
+                }
+            }
+
+            while let Ok(event) = lock_rx.try_recv() {
+                let reset = match (timer_engine().lock(), event) {
+                    (Ok(mut engine), LockEvent::Locked { at }) => {
+                        engine.on_screen_locked(at);
+                        false
+                    }
+                    (Ok(mut engine), LockEvent::Unlocked { at }) => engine.on_screen_unlocked(at),
+                    (Err(_), _) => false,
+                };
+                if reset {
+                    WindowManager::close_existing_window(&app_handle, "pre_break");
+                }
+            }
+
+            if idle_monitor.is_available()
+                && last_idle_sample.map_or(true, |at| at.elapsed() >= IDLE_SAMPLE_INTERVAL)
+            {
+                last_idle_sample = Some(Instant::now());
+                if let Some(idle) = idle_monitor.idle_time() {
+                    if let Ok(mut engine) = timer_engine().lock() {
+                        engine.on_idle_sample(Instant::now(), idle);
+                    }
+                }
+            }
+
+            if last_calendar_check.map_or(true, |at| at.elapsed() >= CALENDAR_CHECK_INTERVAL) {
+                last_calendar_check = Some(Instant::now());
+                plan_break_around_calendar();
+            }
+
+            let actions = match timer_engine().lock() {
+                Ok(mut engine) => engine.tick(Instant::now()),
+                Err(_) => continue,
+            };
+            for action in actions {
+                run_action(&app_handle, action);
+            }
+
+            let (snapshot, revision) = match timer_engine().lock() {
+                Ok(engine) => (engine.snapshot(Instant::now()), engine.revision()),
+                Err(_) => continue,
+            };
+            if last_revision != Some(revision) {
+                persist(&app_handle);
+                last_revision = Some(revision);
+            }
+            if last_snapshot.as_ref() != Some(&snapshot) {
+                send_state(&app_handle, &snapshot);
+                last_snapshot = Some(snapshot);
+            }
+        }
+    });
+}
+
+fn run_action(app_handle: &AppHandle, action: TimerAction) {
+    match action {
+        TimerAction::ShowPreBreak { remaining_seconds } => {
+            println!("🚨 Pre-break warning: {} seconds remaining", remaining_seconds);
+            lifecycle::emit(
+                app_handle,
+                LifecycleEvent::PreBreakWarning { cycle: current_cycle(), remaining_seconds },
+            );
+            if let Err(e) = crate::open_pre_break_window(app_handle.clone(), Some(remaining_seconds)) {
+                println!("⚠️ Failed to show pre-break window: {}", e);
+            }
+        }
+        TimerAction::BreakDue => handle_break_due(app_handle),
+        TimerAction::BreakPostponed { reason } => {
+            lifecycle::emit(app_handle, LifecycleEvent::MeetingPostponed { cycle: current_cycle(), reason });
+            if let Err(e) = crate::open_meeting_notification(app_handle.clone()) {
+                println!("⚠️ Failed to show meeting notification: {}", e);
+            }
+        }
+        TimerAction::BreakSnoozed { cycle, minutes, reason } => {
+            WindowManager::close_existing_window(app_handle, "pre_break");
+            lifecycle::emit(app_handle, LifecycleEvent::BreakSnoozed { cycle, minutes, reason });
+        }
+        TimerAction::StartBreak { mode, duration, auto_pause, duck } => {
+            start_break(app_handle, &mode, duration, auto_pause, duck);
+        }
+        TimerAction::ResumeBreak { mode, remaining_seconds } => {
+            println!("♻️ Reopening {} break with {}s left", mode, remaining_seconds);
+            let result = match mode.as_str() {
+                "notify" => crate::open_notify_window(app_handle.clone(), Some(remaining_seconds)),
+                // Don't lock the screen a second time after a restart
+                "lock" => Ok(()),
+                _ => crate::open_force_break_window(app_handle.clone(), Some(remaining_seconds)),
+            };
+            if let Err(e) = result {
+                println!("❌ Failed to reopen {} break: {}", mode, e);
+            }
+        }
+        TimerAction::BreakFinished { cycle } => {
+            println!("✅ Break finished");
+            lifecycle::emit(app_handle, LifecycleEvent::BreakFinished { cycle });
+        }
+    }
+}
+
+/// Replaces `handleBreakTime` in main.js: check for a meeting and a busy
+/// screen, and let the engine's postponement policy decide whether the break starts.
+fn handle_break_due(app_handle: &AppHandle) {
+    let due = match timer_engine().lock() {
+        Ok(engine) => engine.config.clone().zip(engine.current_tier().map(|tier| tier.mode.clone())),
+        Err(_) => None,
+    };
+    let (config, mode) = match due {
+        Some(due) => due,
+        None => return,
+    };
+
+    // Detection is slow, so it runs without holding the engine lock
+    let meeting = if config.meeting_detect {
+        let assessment = meeting_detector::detect();
+        assessment.is_meeting().then(|| assessment.summary())
+    } else {
+        None
+    };
+    // A notify break can't be downgraded any further
+    let disruptive = mode != "notify";
+    let wants = |action: BusyScreenAction| match action {
+        BusyScreenAction::Ignore => false,
+        BusyScreenAction::Postpone => true,
+        BusyScreenAction::Notify => disruptive,
+    };
+    let screen = screen_activity::detect(wants(config.fullscreen_action), wants(config.presenting_action))
+        .verdict(config.fullscreen_action, config.presenting_action);
+    let hold = meeting.or(screen.postpone);
+
+    let action = match timer_engine().lock() {
+        Ok(mut engine) => engine.on_break_due(
+            Instant::now(),
+            SystemTime::now(),
+            hold.as_deref(),
+            screen.notify.as_deref(),
+        ),
+        Err(_) => None,
+    };
+    if let Some(action) = action {
+        run_action(app_handle, action);
+    }
+}
+
+/// Snooze the upcoming break for `minutes`, the snooze on offer if not given,
+/// or until the current meeting ends. For the `postpone_break` command and the
+/// tray; detects the meeting, so it must not run on the main thread.
+pub fn snooze_break(app_handle: &AppHandle, minutes: Option<u32>, until_meeting_ends: bool) -> Result<TimerSnapshot, String> {
+    // Detection is slow, so it runs without holding the engine lock
+    let meeting = if until_meeting_ends {
+        let assessment = meeting_detector::detect();
+        assessment.is_meeting().then(|| assessment.summary())
+    } else {
+        None
+    };
+
+    let (action, snapshot) = {
+        let mut engine = timer_engine()
+            .lock()
+            .map_err(|e| format!("Timer engine unavailable: {}", e))?;
+        let minutes = match until_meeting_ends {
+            true => None,
+            false => minutes
+                .or_else(|| engine.snooze_offer().map(|offer| offer.minutes).filter(|&minutes| minutes > 0))
+                .or_else(|| engine.config.as_ref().map(|config| config.snooze.step_minutes)),
+        };
+        let action = engine.snooze(Instant::now(), SystemTime::now(), minutes, meeting.as_deref());
+        (action, engine.snapshot(Instant::now()))
+    };
+    let action = action.inspect_err(|reason| println!("🚫 Snooze refused: {}", reason))?;
+    run_action(app_handle, action);
+    send_state(app_handle, &snapshot);
+    Ok(snapshot)
+}
+
+/// Keep the upcoming break out of busy calendar blocks and the minutes before them.
+fn plan_break_around_calendar() {
+    let check = timer_engine()
+        .lock()
+        .ok()
+        .and_then(|engine| engine.calendar_check(Instant::now()));
+    let (break_in, break_length, policy) = match check {
+        Some(check) => check,
+        None => return,
+    };
+
+    // Reading the calendar may hit the disk, so it runs without the engine lock
+    let busy = calendar::busy_windows(SystemTime::now(), CALENDAR_HORIZON);
+    if let BreakPlan::Move { seconds, reason } = schedule::plan_break(&policy, break_in, break_length, &busy) {
+        if let Ok(mut engine) = timer_engine().lock() {
+            engine.move_break(Instant::now(), seconds, reason);
+        }
+    }
+}
+
+fn start_break(app_handle: &AppHandle, mode: &str, duration: u32, auto_pause: bool, duck: bool) {
+    println!("🚨 Break time! mode={}, duration={}s", mode, duration);
+
+    // Break windows read this flag when they close to decide whether to resume media
+    crate::set_media_was_playing(auto_pause);
+    if auto_pause {
+        let action = if duck { "duck" } else { "pause" };
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::control_media(action.to_string()).await {
+                println!("⚠️ Failed to {} media: {}", action, e);
+            }
+        });
+    }
+
+    let result = match mode {
+        "notify" => crate::open_notify_window(app_handle.clone(), Some(duration)),
+        "lock" => crate::lock_screen_now(),
+        _ => crate::open_force_break_window(app_handle.clone(), Some(duration)),
+    };
+    match result {
+        Ok(()) => lifecycle::emit(
+            app_handle,
+            LifecycleEvent::BreakStarted { cycle: current_cycle(), mode: mode.to_string(), duration },
+        ),
+        Err(e) => println!("❌ Failed to start {} break: {}", mode, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    const MINUTE: u32 = 60;
+
+    /// A 10-minute force break every hour, next to a 20-second eye break every 20 minutes.
+    fn tiered_config() -> TimerConfig {
+        TimerConfig::from(&AppSettings {
+            break_minutes: 60,
+            break_seconds: 0,
+            break_duration_minutes: 10,
+            break_duration_seconds: 0,
+            recurring: true,
+            extra_break_tiers: vec![BreakTier {
+                name: "Eye".to_string(),
+                interval_seconds: 20 * MINUTE,
+                duration_seconds: 20,
+                mode: "notify".to_string(),
+                pre_break_seconds: None,
+            }],
+            ..AppSettings::default()
+        })
+    }
+
+    fn after(at: Instant, seconds: u32) -> Instant {
+        at + Duration::from_secs(seconds as u64)
+    }
+
+    /// Run the work countdown out from `now` and take the break that comes due
+    /// in full. Returns the break's tier, the work seconds before it, and when it ended.
+    fn take_break(engine: &mut TimerEngine, now: Instant) -> (String, u32, Instant) {
+        let worked = ceil_seconds(engine.remaining(now));
+        let due = after(now, worked);
+        assert_eq!(engine.tick(due), vec![TimerAction::BreakDue]);
+        let tier = engine.current_tier().expect("a tier is due").clone();
+        match engine.begin_break(due, None) {
+            Some(TimerAction::StartBreak { duration, .. }) => assert_eq!(duration, tier.duration_seconds),
+            other => panic!("expected a break to start, got {:?}", other),
+        }
+        let cycle = engine.cycle;
+        let end = after(due, tier.duration_seconds);
+        assert_eq!(engine.tick(end), vec![TimerAction::BreakFinished { cycle }]);
+        (tier.name, worked, end)
+    }
+
+    #[test]
+    fn work_override_moves_the_main_break_not_the_countdown() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, tiered_config(), Some(60 * MINUTE)).unwrap();
+
+        // The eye break is still the first one due, and the countdown runs to it
+        assert_eq!(engine.current_tier().unwrap().name, "Eye");
+        assert_eq!(engine.remaining(now), Duration::from_secs(20 * MINUTE as u64));
+    }
+
+    #[test]
+    fn eye_and_main_breaks_interleave() {
+        let mut now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, tiered_config(), Some(60 * MINUTE)).unwrap();
+
+        let mut taken = Vec::new();
+        for _ in 0..6 {
+            let (tier, worked, end) = take_break(&mut engine, now);
+            taken.push((tier, worked));
+            now = end;
+        }
+        let expected = ["Eye", "Eye", "Break", "Eye", "Eye", "Break"]
+            .map(|tier| (tier.to_string(), 20 * MINUTE));
+        assert_eq!(taken, expected);
+    }
+
+    #[test]
+    fn main_break_absorbs_an_eye_break_due_shortly_after() {
+        let mut config = tiered_config();
+        config.tiers[0].interval_seconds = 50 * MINUTE;
+        let mut now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, config, None).unwrap();
+
+        let mut taken = Vec::new();
+        for _ in 0..4 {
+            let (tier, worked, end) = take_break(&mut engine, now);
+            taken.push((tier, worked));
+            now = end;
+        }
+        // The main break is due 10 minutes after the second eye break, so it is
+        // taken in its place and starts both schedules over
+        let expected = ["Eye", "Break", "Eye", "Break"].map(|tier| (tier.to_string(), 20 * MINUTE));
+        assert_eq!(taken, expected);
+    }
+
+    #[test]
+    fn paused_time_does_not_count_towards_any_tier() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, tiered_config(), None).unwrap();
+
+        assert!(engine.pause(after(now, 5 * MINUTE)));
+        assert!(engine.resume(after(now, 35 * MINUTE)));
+        let (tier, _, _) = take_break(&mut engine, after(now, 35 * MINUTE));
+        assert_eq!(tier, "Eye");
+        // 20 minutes worked in all; the eye break starts only its own schedule over
+        assert_eq!(engine.tier_worked, vec![20 * MINUTE, 0]);
+    }
+
+    #[test]
+    fn snooze_pushes_the_break_back_until_the_cycle_limit() {
+        let now = Instant::now();
+        let wall = SystemTime::now();
+        let mut engine = TimerEngine::new();
+        let settings = AppSettings {
+            recurring: true,
+            ..AppSettings::default()
+        };
+        engine.start(now, TimerConfig::from(&settings), Some(10 * MINUTE)).unwrap();
+
+        for snoozed in 1..=3 {
+            assert!(engine.snooze(now, wall, Some(5), None).is_ok());
+            assert_eq!(engine.remaining(now), Duration::from_secs((10 + 5 * snoozed) as u64 * 60));
+        }
+        assert_eq!(engine.snooze_offer(), None);
+        assert!(engine.snooze(now, wall, Some(5), None).is_err());
+        assert!(engine.snooze(now, wall, None, Some("Zoom")).is_err());
+
+        // The next cycle starts with a fresh allowance
+        let (_, worked, end) = take_break(&mut engine, now);
+        assert_eq!(worked, 25 * MINUTE);
+        assert_eq!(engine.snooze_offer().map(|offer| offer.snoozes_left), Some(Some(3)));
+        assert!(engine.snooze(end, wall, Some(5), None).is_ok());
+    }
+
+    #[test]
+    fn running_break_cannot_be_snoozed() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine
+            .start(now, TimerConfig::from(&AppSettings::default()), Some(10 * MINUTE))
+            .unwrap();
+        let due = after(now, 10 * MINUTE);
+        engine.tick(due);
+        engine.begin_break(due, None);
+        assert_eq!(
+            engine.snooze(due, SystemTime::now(), Some(5), None),
+            Err("The break has already started".to_string())
+        );
+    }
+
+    #[test]
+    fn pomodoro_takes_a_long_break_after_every_set() {
+        let settings = AppSettings {
+            break_mode: pomodoro::POMODORO_MODE.to_string(),
+            ..AppSettings::default()
+        };
+        let mut now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, TimerConfig::from(&settings), None).unwrap();
+        assert_eq!(engine.snapshot(now).pomodoro.map(|progress| progress.number), Some(1));
+
+        let mut taken = Vec::new();
+        for _ in 0..5 {
+            let (tier, worked, end) = take_break(&mut engine, now);
+            taken.push((tier, worked));
+            now = end;
+        }
+        let expected = ["Short break", "Short break", "Short break", "Long break", "Short break"]
+            .map(|tier| (tier.to_string(), 25 * MINUTE));
+        assert_eq!(taken, expected);
+        assert_eq!(engine.snapshot(now).pomodoro.map(|progress| progress.completed), Some(5));
+    }
+
+    #[test]
+    fn pomodoro_work_override_applies_to_every_pomodoro() {
+        let settings = AppSettings {
+            break_mode: pomodoro::POMODORO_MODE.to_string(),
+            ..AppSettings::default()
+        };
+        let mut now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, TimerConfig::from(&settings), Some(50 * MINUTE)).unwrap();
+        for _ in 0..4 {
+            let (_, worked, end) = take_break(&mut engine, now);
+            assert_eq!(worked, 50 * MINUTE);
+            now = end;
+        }
+    }
+
+    #[test]
+    fn oversized_snooze_is_refused_without_touching_the_timer() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        let settings = AppSettings {
+            max_snoozes: 0,
+            max_snooze_minutes: 0,
+            ..AppSettings::default()
+        };
+        engine.start(now, TimerConfig::from(&settings), Some(10 * MINUTE)).unwrap();
+
+        assert!(engine.snooze(now, SystemTime::now(), Some(u32::MAX), None).is_err());
+        assert!(engine.snooze(now, SystemTime::now(), Some(MAX_SNOOZE_MINUTES + 1), None).is_err());
+        assert_eq!(engine.phase(), TimerPhase::Working);
+        assert_eq!(engine.remaining(now), Duration::from_secs(10 * MINUTE as u64));
+
+        assert!(engine.snooze(now, SystemTime::now(), Some(MAX_SNOOZE_MINUTES), None).is_ok());
+    }
+
+    /// An hourly 10-minute break, saved while running and restored `down` seconds
+    /// later. `save` moves the engine to the state to save; the restored engine
+    /// returns with what `restore` asked for.
+    fn restored(
+        config: TimerConfig,
+        policy: OverduePolicy,
+        down: u32,
+        save: impl FnOnce(&mut TimerEngine, Instant),
+    ) -> (TimerEngine, Vec<TimerAction>, Instant) {
+        let now = Instant::now();
+        let wall = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut engine = TimerEngine::new();
+        engine.start(now, config.clone(), None).unwrap();
+        save(&mut engine, now);
+        let saved = engine.to_persisted(now, wall, false).expect("a running cycle is saved");
+
+        let later = after(now, down);
+        let mut restored = TimerEngine::new();
+        let actions = restored.restore(&saved, config, policy, later, wall + Duration::from_secs(down as u64));
+        (restored, actions, later)
+    }
+
+    fn hourly_config() -> TimerConfig {
+        TimerConfig::from(&AppSettings {
+            break_minutes: 60,
+            break_seconds: 0,
+            break_duration_minutes: 10,
+            break_duration_seconds: 0,
+            recurring: true,
+            ..AppSettings::default()
+        })
+    }
+
+    /// Save with a minute of work left: `start` counts from now, so restore reads
+    /// the 59 minutes worked from the tiers.
+    fn a_minute_before_the_break(engine: &mut TimerEngine, now: Instant) {
+        engine.tier_worked = vec![59 * MINUTE];
+        engine.enter_working(now);
+    }
+
+    #[test]
+    fn overdue_break_follows_the_policy() {
+        // Down for 5 minutes, shorter than the break: it is 4 minutes overdue
+        let (engine, actions, now) =
+            restored(hourly_config(), OverduePolicy::Fire, 5 * MINUTE, a_minute_before_the_break);
+        assert_eq!(actions, vec![TimerAction::BreakDue]);
+        assert_eq!(engine.phase(), TimerPhase::Working);
+        assert_eq!(engine.remaining(now), Duration::ZERO);
+
+        let (engine, actions, now) =
+            restored(hourly_config(), OverduePolicy::Skip, 5 * MINUTE, a_minute_before_the_break);
+        assert!(actions.is_empty());
+        assert_eq!(engine.phase(), TimerPhase::Working);
+        assert_eq!(engine.remaining(now), Duration::from_secs(60 * MINUTE as u64));
+
+        let (engine, actions, now) =
+            restored(hourly_config(), OverduePolicy::Restart, 5 * MINUTE, a_minute_before_the_break);
+        assert!(actions.is_empty());
+        assert_eq!(engine.phase(), TimerPhase::Working);
+        assert_eq!(engine.remaining(now), Duration::from_secs(60 * MINUTE as u64));
+    }
+
+    #[test]
+    fn downtime_as_long_as_a_break_counts_as_one() {
+        let (engine, actions, now) =
+            restored(hourly_config(), OverduePolicy::Fire, 8 * 60 * MINUTE, a_minute_before_the_break);
+        assert!(actions.is_empty());
+        assert_eq!(engine.phase(), TimerPhase::Working);
+        assert_eq!(engine.remaining(now), Duration::from_secs(60 * MINUTE as u64));
+        // A new cycle, after the one saved
+        assert_eq!(engine.cycle, 3);
+    }
+
+    #[test]
+    fn downtime_is_not_a_break_when_sleep_does_not_count() {
+        let mut config = hourly_config();
+        config.sleep_counts_as_break = false;
+        let (_, actions, _) = restored(config, OverduePolicy::Fire, 8 * 60 * MINUTE, a_minute_before_the_break);
+        assert_eq!(actions, vec![TimerAction::BreakDue]);
+    }
+
+    #[test]
+    fn downtime_credits_only_the_tiers_it_covers() {
+        // Down for 5 minutes: long enough for the eye break, not the main one
+        let (engine, actions, now) = restored(tiered_config(), OverduePolicy::Fire, 5 * MINUTE, |engine, now| {
+            engine.tier_worked = vec![50 * MINUTE, 19 * MINUTE];
+            engine.enter_working(now);
+        });
+        assert!(actions.is_empty());
+        assert_eq!(engine.tier_worked, vec![50 * MINUTE, 0]);
+        // The main break comes due before the next eye break
+        assert_eq!(engine.current_tier().unwrap().name, "Break");
+        assert_eq!(engine.remaining(now), Duration::from_secs(10 * MINUTE as u64));
+    }
+
+    #[test]
+    fn paused_cycle_is_restored_paused() {
+        let (engine, actions, now) = restored(hourly_config(), OverduePolicy::Fire, 8 * 60 * MINUTE, |engine, now| {
+            assert!(engine.pause(now));
+        });
+        assert!(actions.is_empty());
+        assert!(engine.is_paused());
+        assert_eq!(engine.remaining(now), Duration::from_secs(60 * MINUTE as u64));
+    }
+
+    #[test]
+    fn running_break_resumes_with_what_is_left() {
+        let (engine, actions, _) = restored(hourly_config(), OverduePolicy::Fire, MINUTE, |engine, now| {
+            engine.tier_worked = vec![60 * MINUTE];
+            engine.enter_working(now);
+            engine.tick(now);
+            engine.begin_break(now, None);
+        });
+        assert_eq!(
+            actions,
+            vec![TimerAction::ResumeBreak {
+                mode: "force".to_string(),
+                remaining_seconds: 9 * MINUTE,
+            }]
+        );
+        assert_eq!(engine.phase(), TimerPhase::OnBreak);
+    }
+
+    #[test]
+    fn break_that_ran_out_while_down_is_finished() {
+        let mut config = hourly_config();
+        // Keeps the downtime from counting as a break by itself
+        config.sleep_counts_as_break = false;
+        let (engine, actions, now) = restored(config, OverduePolicy::Fire, 15 * MINUTE, |engine, now| {
+            engine.tier_worked = vec![60 * MINUTE];
+            engine.enter_working(now);
+            engine.tick(now);
+            engine.begin_break(now, None);
+        });
+        assert_eq!(actions, vec![TimerAction::BreakFinished { cycle: 2 }]);
+        assert_eq!(engine.phase(), TimerPhase::Working);
+        assert_eq!(engine.remaining(now), Duration::from_secs(60 * MINUTE as u64));
+    }
+
+    #[test]
+    fn tier_removed_since_the_save_falls_back_to_the_main_break() {
+        // Saved with the eye break next, restored without it
+        let (engine, actions, now) = restored(hourly_config(), OverduePolicy::Fire, 0, |engine, now| {
+            let mut config = tiered_config();
+            config.tiers[0].interval_seconds = 60 * MINUTE;
+            engine.start(now, config, None).unwrap();
+            assert_eq!(engine.current_tier().unwrap().name, "Eye");
+        });
+        assert!(actions.is_empty());
+        assert_eq!(engine.current_tier().unwrap().name, "Break");
+        assert_eq!(engine.tier_worked.len(), 1);
+        assert_eq!(engine.remaining(now), Duration::from_secs(20 * MINUTE as u64));
+    }
+
+    /// The hourly 10-minute break, pausing after 5 idle minutes.
+    fn idle_config() -> TimerConfig {
+        let mut config = hourly_config();
+        config.idle_detect = true;
+        config.idle_pause_seconds = 5 * MINUTE;
+        config
+    }
+
+    fn minutes(count: u32) -> Duration {
+        Duration::from_secs((count * MINUTE) as u64)
+    }
+
+    #[test]
+    fn short_idle_keeps_the_timer_running() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, idle_config(), None).unwrap();
+
+        let at = after(now, 10 * MINUTE);
+        assert!(!engine.on_idle_sample(at, minutes(4)));
+        assert!(!engine.is_paused());
+        assert_eq!(engine.remaining(at), minutes(50));
+    }
+
+    #[test]
+    fn idle_pauses_the_timer_until_the_user_is_back() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, idle_config(), None).unwrap();
+
+        assert!(engine.on_idle_sample(after(now, 10 * MINUTE), minutes(5)));
+        assert!(engine.is_paused());
+        assert!(engine.snapshot(after(now, 10 * MINUTE)).idle_paused);
+        // Still away, but not for as long as a break
+        assert!(!engine.on_idle_sample(after(now, 12 * MINUTE), minutes(7)));
+        assert!(engine.is_paused());
+
+        let back = after(now, 14 * MINUTE);
+        assert!(engine.on_idle_sample(back, Duration::ZERO));
+        assert!(!engine.is_paused());
+        assert_eq!(engine.remaining(back), minutes(50));
+    }
+
+    #[test]
+    fn idle_as_long_as_a_break_starts_the_cycle_over() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, idle_config(), None).unwrap();
+        assert!(engine.on_idle_sample(after(now, 30 * MINUTE), minutes(5)));
+
+        assert!(engine.on_idle_sample(after(now, 35 * MINUTE), minutes(10)));
+        assert_eq!(engine.cycle, 2);
+        // Waits for the user with a full interval
+        assert!(engine.is_paused());
+        assert_eq!(engine.remaining(after(now, 35 * MINUTE)), minutes(60));
+        // Counted once, however long the user stays away
+        assert!(!engine.on_idle_sample(after(now, 50 * MINUTE), minutes(25)));
+        assert_eq!(engine.cycle, 2);
+
+        let back = after(now, 52 * MINUTE);
+        assert!(engine.on_idle_sample(back, Duration::ZERO));
+        assert!(!engine.is_paused());
+        assert_eq!(engine.remaining(back), minutes(60));
+    }
+
+    #[test]
+    fn idle_is_ignored_when_detection_is_off() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.start(now, hourly_config(), None).unwrap();
+        assert!(!engine.on_idle_sample(after(now, 30 * MINUTE), minutes(20)));
+        assert!(!engine.is_paused());
+    }
+
+    #[test]
+    fn idle_during_a_break_confirms_the_user_left() {
+        let now = Instant::now();
+        let mut engine = TimerEngine::new();
+        engine.set_idle_supported(true);
+        engine.start(now, idle_config(), None).unwrap();
+        let due = after(now, 60 * MINUTE);
+        engine.tick(due);
+        engine.begin_break(due, None);
+
+        // Doesn't pause or reset the break
+        assert!(!engine.on_idle_sample(after(due, MINUTE), minutes(20)));
+        assert_eq!(engine.phase(), TimerPhase::OnBreak);
+        assert!(!engine.is_paused());
+        assert_eq!(engine.end_break_early(after(due, 2 * MINUTE)), Some(true));
+
+        // The next break starts without the last one's idle time
+        let due = after(due, 62 * MINUTE);
+        engine.tick(due);
+        engine.begin_break(due, None);
+        assert!(!engine.on_idle_sample(after(due, MINUTE), Duration::from_secs(5)));
+        assert_eq!(engine.end_break_early(after(due, 2 * MINUTE)), Some(false));
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::settings;
+use crate::timer_engine::TimerPhase;
+
+/// Bumped whenever the on-disk layout changes; older files are ignored.
+const STATE_VERSION: u32 = 1;
+
+const STATE_FILE: &str = "timer_state.json";
+
+/// The running cycle as written to `timer_state.json`.
+///
+/// Deadlines are stored as wall-clock times because `Instant` does not survive
+/// a restart. A paused cycle stores what was left instead of a deadline.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PersistedTimer {
+    pub version: u32,
+    pub phase: TimerPhase,
+    pub deadline_unix_ms: Option<u64>,
+    pub paused_remaining_ms: Option<u64>,
+    pub phase_total_ms: u64,
+    pub pre_break_shown: bool,
+    pub cycle: u32,
+    /// Tier of the upcoming or running break.
+    #[serde(default)]
+    pub break_tier: usize,
+    /// Seconds of work since each tier's last break.
+    #[serde(default)]
+    pub tier_worked_seconds: Vec<u32>,
+    /// Pomodoros finished since the timer started.
+    #[serde(default)]
+    pub pomodoros: u32,
+    pub postpone_count: u32,
+    #[serde(default)]
+    pub snooze_count: u32,
+    #[serde(default)]
+    pub snoozed_seconds: u32,
+    /// End of the last break, for the postponement policy's mandatory-break limit.
+    #[serde(default)]
+    pub cycle_started_unix_ms: Option<u64>,
+    #[serde(default)]
+    pub waiting_for_meeting: bool,
+    #[serde(default)]
+    pub postpone_reason: Option<String>,
+    /// Set when the break in progress was downgraded to a notify break.
+    #[serde(default)]
+    pub break_mode_override: Option<String>,
+    pub media_paused_by_us: bool,
+    pub saved_at_unix_ms: u64,
+}
+
+impl PersistedTimer {
+    pub fn new(phase: TimerPhase) -> Self {
+        Self {
+            version: STATE_VERSION,
+            phase,
+            deadline_unix_ms: None,
+            paused_remaining_ms: None,
+            phase_total_ms: 0,
+            pre_break_shown: false,
+            cycle: 0,
+            break_tier: 0,
+            tier_worked_seconds: Vec::new(),
+            pomodoros: 0,
+            postpone_count: 0,
+            snooze_count: 0,
+            snoozed_seconds: 0,
+            cycle_started_unix_ms: None,
+            waiting_for_meeting: false,
+            postpone_reason: None,
+            break_mode_override: None,
+            media_paused_by_us: false,
+            saved_at_unix_ms: unix_ms(SystemTime::now()),
+        }
+    }
+}
+
+pub fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub fn from_unix_ms(ms: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(ms)
+}
+
+fn state_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(settings::app_data_dir(app_handle)?.join(STATE_FILE))
+}
+
+/// Write the state atomically: write a temp file next to the target, then rename
+/// it over the old one, so a crash mid-write never leaves a truncated file behind.
+pub fn save(app_handle: &AppHandle, state: &PersistedTimer) -> Result<(), String> {
+    let path = state_path(app_handle)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    }
+
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize timer state: {}", e))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("Failed to write timer state: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace timer state: {}", e))?;
+
+    Ok(())
+}
+
+/// Load the saved state. Missing, unreadable or outdated files yield `None`.
+pub fn load(app_handle: &AppHandle) -> Option<PersistedTimer> {
+    let path = state_path(app_handle).ok()?;
+    if !path.exists() {
+        return None;
+    }
+
+    let json = match fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(e) => {
+            println!("⚠️ Failed to read timer state: {}", e);
+            return None;
+        }
+    };
+
+    match serde_json::from_str::<PersistedTimer>(&json) {
+        Ok(state) if state.version == STATE_VERSION => Some(state),
+        Ok(state) => {
+            println!("⚠️ Ignoring timer state with version {}", state.version);
+            None
+        }
+        Err(e) => {
+            println!("⚠️ Failed to parse timer state: {}", e);
+            None
+        }
+    }
+}
+
+pub fn clear(app_handle: &AppHandle) {
+    if let Ok(path) = state_path(app_handle) {
+        if path.exists() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -1,7 +1,21 @@
-use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Manager, Monitor, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 
+use crate::postpone_policy::SnoozeOffer;
+use crate::skip_policy::{self, SkipStatus};
+use crate::window_payload::{self, WindowPayload};
+use crate::window_registry::{self as registry, WindowInfo};
+use crate::window_placement::{self, Anchor, MonitorLayout, Rect};
+
+/// How long `create_window` waits for a window it replaces to go away.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Label prefix of the force break windows, one per monitor (`force_break_0`, …).
+pub const FORCE_BREAK_GROUP: &str = "force_break";
+
 pub struct WindowConfig {
     pub label: String,
     pub url: String,
@@ -19,6 +33,13 @@ pub struct WindowConfig {
     pub transparent: bool,
     pub shadow: bool,
     pub position: Option<(f64, f64)>,
+    /// Label prefix of a window group; closing one window of the group closes all.
+    pub group: Option<&'static str>,
+    /// Served to the window by `get_window_payload` while it is open.
+    pub payload: Option<WindowPayload>,
+    /// Asked with the window's label when the user tries to close the window;
+    /// returning false keeps it open. Closes the app asks for itself always go ahead.
+    pub close_guard: Option<fn(&AppHandle, &str) -> bool>,
 }
 
 impl Default for WindowConfig {
@@ -40,174 +61,316 @@ impl Default for WindowConfig {
             transparent: false,
             shadow: true,
             position: None,
+            group: None,
+            payload: None,
+            close_guard: None,
         }
     }
 }
 
+/// A monitor to cover with a force break window.
+pub struct MonitorArea {
+    pub position: (f64, f64),
+    pub size: (f64, f64),
+    pub primary: bool,
+}
+
 pub struct WindowManager;
 
 impl WindowManager {
+    /// Open a window, replacing an open window with the same label. Creating and
+    /// closing are serialised per label, and this returns once the window is
+    /// built, with the build error if there was one. The window is positioned
+    /// and shown once its page has loaded.
+    ///
+    /// Building waits for the main thread, so this must be called from another
+    /// thread: an async command, `spawn_blocking` or the timer driver.
     pub fn create_window(app_handle: AppHandle, config: WindowConfig) -> Result<(), String> {
-        let handle = app_handle.clone();
-        
-        thread::spawn(move || {
-            println!("📂 Creating window '{}' with URL: {}", config.label, config.url);
-            
-            let mut builder = WebviewWindowBuilder::new(
-                &handle,
-                &config.label,
-                WebviewUrl::App(config.url.into())
-            )
-            .title(&config.title)
-            .inner_size(config.width, config.height)
-            .fullscreen(config.fullscreen)
-            .always_on_top(config.always_on_top)
-            .decorations(config.decorations)
-            .resizable(config.resizable)
-            .focused(config.focused)
-            .visible(config.visible)
-            .skip_taskbar(config.skip_taskbar)
-            .maximized(config.maximized)
-            .transparent(config.transparent)
-            .shadow(config.shadow);
-
-            // Set position if provided
-            if let Some((x, y)) = config.position {
-                builder = builder.position(x, y);
-                println!("🎯 Setting initial position for '{}': ({:.0}, {:.0})", config.label, x, y);
+        let label_lock = registry::label_lock(&config.label);
+        let _serialized = label_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(existing) = app_handle.get_webview_window(&config.label) {
+            println!("📄 Replacing existing window '{}'", config.label);
+            registry::mark_closing(&config.label);
+            let _ = existing.close();
+        }
+        if !registry::wait_closed(&config.label, CLOSE_TIMEOUT) {
+            println!("⚠️ Window '{}' did not close in time, opening the new one anyway", config.label);
+        }
+
+        let id = registry::register(&config.label, &config.url);
+        // Stored before the page loads, so it's there when the window asks
+        let payload_token = config
+            .payload
+            .clone()
+            .map(|payload| window_payload::set(&config.label, payload));
+
+        println!("📂 Creating window '{}' with URL: {}", config.label, config.url);
+
+        // A positioned fullscreen window goes fullscreen once it is on the
+        // right monitor; fullscreen at build time uses the monitor it starts on
+        let fullscreen_later = config.fullscreen && config.position.is_some();
+
+        let mut builder = WebviewWindowBuilder::new(
+            &app_handle,
+            &config.label,
+            WebviewUrl::App(config.url.clone().into())
+        )
+        .title(&config.title)
+        .inner_size(config.width, config.height)
+        .fullscreen(config.fullscreen && !fullscreen_later)
+        .always_on_top(config.always_on_top)
+        .decorations(config.decorations)
+        .resizable(config.resizable)
+        .focused(config.focused)
+        .visible(config.visible)
+        .skip_taskbar(config.skip_taskbar)
+        .maximized(config.maximized)
+        .transparent(config.transparent)
+        .shadow(config.shadow);
+
+        // Set position if provided; the builder takes logical pixels in
+        // the scale of the monitor the window lands on
+        if let Some((x, y)) = config.position {
+            // Keeps the page's own positioning code from moving the window
+            builder = builder.initialization_script("window.RUST_POSITIONED = true;");
+            let scale = app_handle
+                .monitor_from_point(x, y)
+                .ok()
+                .flatten()
+                .map(|monitor| monitor.scale_factor())
+                .unwrap_or(1.0);
+            builder = builder.position(x / scale, y / scale);
+            println!("🎯 Setting initial position for '{}': ({:.0}, {:.0})", config.label, x, y);
+        }
+
+        // Shown once the page has loaded, so the window never flashes empty
+        let shown_label = config.label.clone();
+        let (position, always_on_top, focused) = (config.position, config.always_on_top, config.focused);
+        let loaded = AtomicBool::new(false);
+        builder = builder.on_page_load(move |window, payload| {
+            // A reload mustn't move or refocus a window the user has seen
+            if payload.event() != PageLoadEvent::Finished || loaded.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            // The builder's position can be off by the scale of another monitor
+            if let Some((x, y)) = position {
+                let physical = tauri::PhysicalPosition { x: x as i32, y: y as i32 };
+                if let Err(e) = window.set_position(tauri::Position::Physical(physical)) {
+                    println!("⚠️ Failed to set position for '{}': {}", shown_label, e);
+                }
             }
+            if fullscreen_later {
+                if let Err(e) = window.set_fullscreen(true) {
+                    println!("⚠️ Failed to make '{}' fullscreen: {}", shown_label, e);
+                }
+            }
+            if let Err(e) = window.show() {
+                println!("⚠️ Failed to show window '{}': {}", shown_label, e);
+                return;
+            }
+            println!("✅ Window '{}' shown successfully", shown_label);
+            registry::mark_shown(&shown_label, id);
+            // Some window managers drop always-on-top while a window is hidden
+            if always_on_top {
+                if let Err(e) = window.set_always_on_top(true) {
+                    println!("⚠️ Failed to set always on top for '{}': {}", shown_label, e);
+                }
+            }
+            if focused {
+                if let Err(e) = window.set_focus() {
+                    println!("⚠️ Failed to focus window '{}': {}", shown_label, e);
+                }
+            }
+        });
 
-            match builder.build() {
-                Ok(window) => {
-                    println!("✅ Window '{}' created successfully!", config.label);
-                    
-                    // Inject positioning signal IMMEDIATELY to prevent JS positioning conflicts
-                    if config.position.is_some() {
-                        let js_code = format!(
-                            "window.RUST_POSITIONED = true; \
-                             console.log('🔥 Window {} positioned by Rust - JS positioning disabled');",
-                            config.label
-                        );
-                        let _ = window.eval(&js_code);
-                    }
-                    
-                    // Wait for content to load
-                    thread::sleep(Duration::from_millis(200));
-                    
-                    // Double-check position if specified
-                    if let Some((x, y)) = config.position {
-                        if let Err(e) = window.set_position(tauri::Position::Physical(tauri::PhysicalPosition { 
-                            x: x as i32, 
-                            y: y as i32 
-                        })) {
-                            println!("⚠️ Failed to set position for '{}': {}", config.label, e);
-                        } else {
-                            println!("✅ Position confirmed for '{}': ({:.0}, {:.0})", config.label, x, y);
-                        }
-                    }
-                    
-                    // Show window after positioning
-                    if let Err(e) = window.show() {
-                        println!("⚠️ Failed to show window '{}': {}", config.label, e);
-                    } else {
-                        println!("✅ Window '{}' shown successfully", config.label);
-                    }
-                    
-                    // Set always on top for notification windows
-                    if config.always_on_top {
-                        if let Err(e) = window.set_always_on_top(true) {
-                            println!("⚠️ Failed to set always on top for '{}': {}", config.label, e);
-                        }
-                    }
-                    
-                    // Focus if required
-                    if config.focused {
-                        if let Err(e) = window.set_focus() {
-                            println!("⚠️ Failed to focus window '{}': {}", config.label, e);
-                        }
-                    }
-                    
-                    // Final positioning confirmation
-                    thread::sleep(Duration::from_millis(100));
-                    if config.position.is_some() {
-                        let final_js = format!(
-                            "console.log('🎯 Final positioning confirmation for {}'); \
-                             document.title = '{} - POSITIONED BY RUST';",
-                            config.label, config.title
-                        );
-                        let _ = window.eval(&final_js);
+        let window = match builder.build() {
+            Ok(window) => window,
+            Err(e) => {
+                println!("❌ Failed to create window '{}': {}", config.label, e);
+                if let Some(token) = payload_token {
+                    window_payload::remove(&config.label, token);
+                }
+                let error = format!("Failed to create window '{}': {}", config.label, e);
+                registry::mark_failed(&config.label, id, &error);
+                return Err(error);
+            }
+        };
+        println!("✅ Window '{}' created successfully!", config.label);
+
+        let group_handle = app_handle.clone();
+        let label = config.label.clone();
+        let group = config.group;
+        let close_guard = config.close_guard;
+        window.on_window_event(move |event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                if let Some(guard) = close_guard {
+                    if !registry::is_closing(&label) && !guard(&group_handle, &label) {
+                        println!("🚫 Kept window '{}' open", label);
+                        api.prevent_close();
                     }
                 }
-                Err(e) => {
-                    println!("❌ Failed to create window '{}': {}", config.label, e);
+            }
+            if let WindowEvent::Destroyed = event {
+                let requested = registry::mark_closed(&label, id);
+                if let Some(token) = payload_token {
+                    window_payload::remove(&label, token);
+                }
+                // Closing one window of a group by hand closes the rest; a close
+                // we asked for (replacing it, or closing the group) doesn't.
+                // Closing waits on the label locks, so it can't run on the main thread
+                if let (Some(group), false) = (group, requested) {
+                    let group_handle = group_handle.clone();
+                    thread::spawn(move || WindowManager::close_group(&group_handle, group));
                 }
             }
         });
-        
+
         Ok(())
     }
 
     pub fn get_screen_center_position(app_handle: &AppHandle, window_width: f64, window_height: f64) -> (f64, f64) {
-        match app_handle.primary_monitor() {
-            Ok(Some(monitor)) => {
-                let size = monitor.size();
-                let screen_width = size.width as f64;
-                let screen_height = size.height as f64;
-                
-                let x = (screen_width - window_width) / 2.0;
-                let y = (screen_height - window_height) / 2.0;
-                
-                println!("📺 Screen: {}x{}, Window: {}x{}, Center: ({:.0}, {:.0})", 
-                    screen_width, screen_height, window_width, window_height, x, y);
-                
-                (x, y)
-            }
-            _ => {
-                println!("⚠️ Could not get monitor info, using default center position");
-                (200.0, 200.0)
-            }
-        }
+        Self::popup_position(app_handle, window_width, window_height, Anchor::Center).unwrap_or_else(|| {
+            println!("⚠️ Could not get monitor info, using default center position");
+            (200.0, 200.0)
+        })
     }
 
     pub fn get_bottom_center_position(app_handle: &AppHandle, window_width: f64, window_height: f64, margin_bottom: f64) -> (f64, f64) {
-        match app_handle.primary_monitor() {
-            Ok(Some(monitor)) => {
-                let size = monitor.size();
-                let screen_width = size.width as f64;
-                let screen_height = size.height as f64;
-                
-                let x = (screen_width - window_width) / 2.0;
-                let y = screen_height - window_height - margin_bottom;
-                
-                println!("📺 Screen: {}x{}, Window: {}x{}, Bottom Center: ({:.0}, {:.0})", 
-                    screen_width, screen_height, window_width, window_height, x, y);
-                
-                (x, y)
-            }
-            _ => {
-                println!("⚠️ Could not get monitor info, using default bottom center position");
-                (200.0, 400.0)
-            }
+        let anchor = Anchor::BottomCenter { margin: margin_bottom };
+        Self::popup_position(app_handle, window_width, window_height, anchor).unwrap_or_else(|| {
+            println!("⚠️ Could not get monitor info, using default bottom center position");
+            (200.0, 400.0)
+        })
+    }
+
+    /// Physical position for a popup of logical size `width`x`height` on the
+    /// monitor the user is working on.
+    fn popup_position(app_handle: &AppHandle, width: f64, height: f64, anchor: Anchor) -> Option<(f64, f64)> {
+        let monitors: Vec<MonitorLayout> = app_handle
+            .available_monitors()
+            .ok()?
+            .iter()
+            .map(monitor_layout)
+            .collect();
+        let monitor = Self::active_point(app_handle)
+            .and_then(|point| window_placement::monitor_at(&monitors, point).copied())
+            .or_else(|| app_handle.primary_monitor().ok().flatten().as_ref().map(monitor_layout))
+            .or_else(|| monitors.first().copied())?;
+
+        let (x, y) = window_placement::place(&monitor, (width, height), anchor);
+        let area = monitor.work_area;
+        println!("📺 Work area: {}x{} at ({}, {}) @{}x, Window: {}x{}, {:?}: ({:.0}, {:.0})",
+            area.width, area.height, area.x, area.y, monitor.scale_factor, width, height, anchor, x, y);
+        Some((x, y))
+    }
+
+    /// The cursor, or else the centre of our focused window, in physical pixels.
+    fn active_point(app_handle: &AppHandle) -> Option<(f64, f64)> {
+        if let Ok(cursor) = app_handle.cursor_position() {
+            return Some((cursor.x, cursor.y));
         }
+        let focused = app_handle
+            .webview_windows()
+            .into_values()
+            .find(|window| window.is_focused().unwrap_or(false))?;
+        let position = focused.outer_position().ok()?;
+        let size = focused.outer_size().ok()?;
+        Some((
+            position.x as f64 + size.width as f64 / 2.0,
+            position.y as f64 + size.height as f64 / 2.0,
+        ))
     }
 
+    /// Close the window with the label. A window still being built by
+    /// `create_window` is waited for and closed too, so like `create_window`
+    /// this must not be called on the main thread.
     pub fn close_existing_window(app_handle: &AppHandle, label: &str) {
+        let label_lock = registry::label_lock(label);
+        let _serialized = label_lock.lock().unwrap_or_else(|e| e.into_inner());
+
         if let Some(existing) = app_handle.get_webview_window(label) {
             println!("📄 Closing existing window '{}'", label);
-            let _ = existing.close();
+            registry::mark_closing(label);
+            if let Err(e) = existing.close() {
+                println!("⚠️ Failed to close window '{}': {}", label, e);
+            }
+        }
+    }
+
+    /// Every window opened through `create_window` and what became of it.
+    pub fn list_windows(app_handle: &AppHandle) -> Vec<WindowInfo> {
+        registry::list(|label| app_handle.get_webview_window(label).is_some())
+    }
+
+    /// Close every window whose label starts with `group`, one label at a time
+    /// like `close_existing_window`.
+    pub fn close_group(app_handle: &AppHandle, group: &str) {
+        // Windows still being built are only known to the registry
+        let mut labels: Vec<String> = app_handle
+            .webview_windows()
+            .into_keys()
+            .chain(registry::labels())
+            .filter(|label| label.starts_with(group))
+            .collect();
+        labels.sort();
+        labels.dedup();
+        for label in labels {
+            Self::close_existing_window(app_handle, &label);
+        }
+    }
+
+    /// Every connected monitor in physical pixels, the primary one marked.
+    /// Empty if the monitors can't be listed.
+    pub fn monitor_areas(app_handle: &AppHandle) -> Vec<MonitorArea> {
+        let primary = app_handle
+            .primary_monitor()
+            .ok()
+            .flatten()
+            .map(|monitor| *monitor.position());
+        let monitors = match app_handle.available_monitors() {
+            Ok(monitors) => monitors,
+            Err(e) => {
+                println!("⚠️ Could not list monitors: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut areas: Vec<MonitorArea> = monitors
+            .iter()
+            .map(|monitor| {
+                let position = monitor.position();
+                let size = monitor.size();
+                MonitorArea {
+                    position: (position.x as f64, position.y as f64),
+                    size: (size.width as f64, size.height as f64),
+                    primary: Some(*position) == primary,
+                }
+            })
+            .collect();
+        // Without a known primary monitor the first one shows the countdown
+        if !areas.iter().any(|area| area.primary) {
+            if let Some(first) = areas.first_mut() {
+                first.primary = true;
+            }
         }
+        areas
     }
 }
 
 // Predefined window configurations
 impl WindowConfig {
-    pub fn force_break(duration: u32) -> Self {
+    /// The force break window for the `index`-th monitor. The primary monitor
+    /// shows the countdown and controls; the others a dimmed overlay.
+    pub fn force_break(duration: u32, break_index: u32, index: usize, area: Option<&MonitorArea>) -> Self {
+        let overlay = area.is_some_and(|area| !area.primary);
+        let (width, height) = area.map_or((1920.0, 1080.0), |area| area.size);
         Self {
-            label: "force_break".to_string(),
-            url: format!("force_break.html?duration={}", duration),
+            label: format!("{}_{}", FORCE_BREAK_GROUP, index),
+            url: "force_break.html".to_string(),
             title: "Break Time".to_string(),
-            width: 1920.0,
-            height: 1080.0,
+            width,
+            height,
             fullscreen: true,
             always_on_top: true,
             decorations: false,
@@ -218,18 +381,26 @@ impl WindowConfig {
             maximized: true,
             transparent: false,
             shadow: false,
-            position: None,
+            position: area.map(|area| area.position),
+            group: Some(FORCE_BREAK_GROUP),
+            payload: Some(WindowPayload::ForceBreak {
+                duration,
+                break_index,
+                overlay,
+                skips: skip_policy::status(),
+            }),
+            close_guard: None,
         }
     }
 
-    pub fn notify(app_handle: &AppHandle, duration: u32) -> Self {
+    pub fn notify(app_handle: &AppHandle, duration: u32, break_index: u32) -> Self {
         let window_width = 480.0;
         let window_height = 350.0; // Increased height for better content fit
         let position = WindowManager::get_screen_center_position(app_handle, window_width, window_height);
         
         Self {
             label: "notify".to_string(),
-            url: format!("notify.html?duration={}", duration),
+            url: "notify.html".to_string(),
             title: "Break Time - Break Reminder Pro".to_string(),
             width: window_width,
             height: window_height,
@@ -244,17 +415,27 @@ impl WindowConfig {
             transparent: false,
             shadow: true,
             position: Some(position),
+            group: None,
+            payload: Some(WindowPayload::Notify { duration, break_index }),
+            close_guard: None,
         }
     }
 
-    pub fn pre_break(app_handle: &AppHandle, remaining_seconds: u32) -> Self {
-        let window_width = 220.0;
-        let window_height = 90.0;
+    /// `skips` is today's skip budget when the upcoming break is a force break;
+    /// `snooze` the snooze still on offer this cycle.
+    pub fn pre_break(
+        app_handle: &AppHandle,
+        remaining_seconds: u32,
+        skips: Option<SkipStatus>,
+        snooze: Option<SnoozeOffer>,
+    ) -> Self {
+        let window_width = 260.0;
+        let window_height = 100.0;
         let position = WindowManager::get_bottom_center_position(app_handle, window_width, window_height, 120.0);
         
         Self {
             label: "pre_break".to_string(),
-            url: format!("pre_break.html?seconds={}", remaining_seconds),
+            url: "pre_break.html".to_string(),
             title: "Pre-Break Warning".to_string(),
             width: window_width,
             height: window_height,
@@ -269,6 +450,9 @@ impl WindowConfig {
             transparent: true,
             shadow: false,
             position: Some(position),
+            group: None,
+            payload: Some(WindowPayload::PreBreak { remaining_seconds, skips, snooze }),
+            close_guard: None,
         }
     }
 
@@ -294,6 +478,9 @@ impl WindowConfig {
             transparent: true,
             shadow: false,
             position: Some(position),
+            group: None,
+            payload: Some(WindowPayload::MeetingNotification),
+            close_guard: None,
         }
     }
 
@@ -308,18 +495,9 @@ impl WindowConfig {
         let window_height = 300.0;
         let position = WindowManager::get_screen_center_position(app_handle, window_width, window_height);
         
-        // URL encode the parameters
-        let encoded_version = urlencoding::encode(&version);
-        let encoded_notes = urlencoding::encode(&notes);
-        let encoded_url = urlencoding::encode(&download_url);
-        let encoded_date = urlencoding::encode(&published_at);
-        
         Self {
             label: "update_notification".to_string(),
-            url: format!(
-                "update_notification.html?version={}&notes={}&url={}&date={}",
-                encoded_version, encoded_notes, encoded_url, encoded_date
-            ),
+            url: "update_notification.html".to_string(),
             title: "Update Available".to_string(),
             width: window_width,
             height: window_height,
@@ -334,6 +512,34 @@ impl WindowConfig {
             transparent: false,
             shadow: true,
             position: Some(position),
+            group: None,
+            payload: Some(WindowPayload::UpdateNotification {
+                version,
+                notes,
+                download_url,
+                published_at,
+            }),
+            close_guard: None,
         }
     }
-}
\ No newline at end of file
+}
+
+fn monitor_layout(monitor: &Monitor) -> MonitorLayout {
+    let (position, size) = (monitor.position(), monitor.size());
+    let work_area = monitor.work_area();
+    MonitorLayout {
+        bounds: Rect {
+            x: position.x as f64,
+            y: position.y as f64,
+            width: size.width as f64,
+            height: size.height as f64,
+        },
+        work_area: Rect {
+            x: work_area.position.x as f64,
+            y: work_area.position.y as f64,
+            width: work_area.size.width as f64,
+            height: work_area.size.height as f64,
+        },
+        scale_factor: monitor.scale_factor(),
+    }
+}
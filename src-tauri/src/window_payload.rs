@@ -0,0 +1,80 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::postpone_policy::SnoozeOffer;
+use crate::skip_policy::SkipStatus;
+
+/// The data a window is opened with. Windows fetch their own with the
+/// `get_window_payload` command instead of parsing their URL.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WindowPayload {
+    ForceBreak {
+        duration: u32,
+        /// Work cycle this break ends, counted from when the timer started.
+        break_index: u32,
+        /// A dimmed window on a secondary monitor; the primary one runs the break.
+        overlay: bool,
+        /// Today's skip budget, for the early return button.
+        skips: SkipStatus,
+    },
+    Notify {
+        duration: u32,
+        break_index: u32,
+    },
+    PreBreak {
+        remaining_seconds: u32,
+        /// Today's skip budget; `None` unless the upcoming break is a force break.
+        skips: Option<SkipStatus>,
+        /// The snooze still on offer this cycle; `None` once used up.
+        snooze: Option<SnoozeOffer>,
+    },
+    MeetingNotification,
+    UpdateNotification {
+        version: String,
+        notes: String,
+        download_url: String,
+        published_at: String,
+    },
+}
+
+/// Payloads by window label, each with the token of the window it belongs to.
+struct PayloadStore {
+    payloads: HashMap<String, (u64, WindowPayload)>,
+    next_token: u64,
+}
+
+fn store() -> &'static Mutex<PayloadStore> {
+    static STORE: OnceLock<Mutex<PayloadStore>> = OnceLock::new();
+    STORE.get_or_init(|| {
+        Mutex::new(PayloadStore {
+            payloads: HashMap::new(),
+            next_token: 0,
+        })
+    })
+}
+
+/// Store the payload for the window about to open as `label`, replacing that of
+/// an earlier window with the same label. Returns the token to `remove` it with.
+pub fn set(label: &str, payload: WindowPayload) -> u64 {
+    let mut store = store().lock().unwrap_or_else(|e| e.into_inner());
+    store.next_token += 1;
+    let token = store.next_token;
+    store.payloads.insert(label.to_string(), (token, payload));
+    token
+}
+
+pub fn get(label: &str) -> Option<WindowPayload> {
+    let store = store().lock().unwrap_or_else(|e| e.into_inner());
+    store.payloads.get(label).map(|(_, payload)| payload.clone())
+}
+
+/// Drop the payload of a closed window, unless a new window with the same label
+/// has stored its own since.
+pub fn remove(label: &str, token: u64) {
+    let mut store = store().lock().unwrap_or_else(|e| e.into_inner());
+    if store.payloads.get(label).is_some_and(|(current, _)| *current == token) {
+        store.payloads.remove(label);
+    }
+}
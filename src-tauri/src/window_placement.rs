@@ -0,0 +1,153 @@
+/// A rectangle in physical pixels, in the desktop's coordinate space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn contains(&self, (x, y): (f64, f64)) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Squared distance from the point to the nearest edge; 0 inside.
+    fn distance_squared(&self, (x, y): (f64, f64)) -> f64 {
+        let dx = (self.x - x).max(0.0).max(x - (self.x + self.width));
+        let dy = (self.y - y).max(0.0).max(y - (self.y + self.height));
+        dx * dx + dy * dy
+    }
+}
+
+/// One monitor as the placement sees it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MonitorLayout {
+    pub bounds: Rect,
+    /// `bounds` minus taskbars, docks and panels.
+    pub work_area: Rect,
+    pub scale_factor: f64,
+}
+
+/// Where on the monitor a popup goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    Center,
+    /// Centred horizontally, `margin` logical pixels above the bottom of the work area.
+    BottomCenter { margin: f64 },
+}
+
+/// The monitor containing `point`, or the nearest one if the point is in a gap
+/// between monitors.
+pub fn monitor_at(monitors: &[MonitorLayout], point: (f64, f64)) -> Option<&MonitorLayout> {
+    monitors
+        .iter()
+        .find(|monitor| monitor.bounds.contains(point))
+        .or_else(|| {
+            monitors
+                .iter()
+                .min_by(|a, b| a.bounds.distance_squared(point).total_cmp(&b.bounds.distance_squared(point)))
+        })
+}
+
+/// Physical position of the top-left corner of a window of `size` logical
+/// pixels, placed in the monitor's work area and kept inside it where it fits.
+pub fn place(monitor: &MonitorLayout, size: (f64, f64), anchor: Anchor) -> (f64, f64) {
+    let scale = if monitor.scale_factor > 0.0 { monitor.scale_factor } else { 1.0 };
+    let area = monitor.work_area;
+    let (width, height) = (size.0 * scale, size.1 * scale);
+
+    let x = area.x + (area.width - width) / 2.0;
+    let y = match anchor {
+        Anchor::Center => area.y + (area.height - height) / 2.0,
+        Anchor::BottomCenter { margin } => area.y + area.height - height - margin * scale,
+    };
+
+    // A window larger than the work area keeps its top-left corner visible
+    let clamp = |value: f64, start: f64, length: f64, extent: f64| {
+        value.min(start + length - extent).max(start)
+    };
+    (
+        clamp(x, area.x, area.width, width).round(),
+        clamp(y, area.y, area.height, height).round(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    /// A 150% primary monitor with a 72px taskbar along the bottom, and a
+    /// secondary monitor to its left with a 40px panel along the top.
+    fn layout() -> Vec<MonitorLayout> {
+        vec![
+            MonitorLayout {
+                bounds: rect(0.0, 0.0, 2560.0, 1440.0),
+                work_area: rect(0.0, 0.0, 2560.0, 1368.0),
+                scale_factor: 1.5,
+            },
+            MonitorLayout {
+                bounds: rect(-1920.0, 0.0, 1920.0, 1080.0),
+                work_area: rect(-1920.0, 40.0, 1920.0, 1040.0),
+                scale_factor: 1.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn finds_the_monitor_containing_the_point() {
+        let monitors = layout();
+        assert_eq!(monitor_at(&monitors, (100.0, 100.0)), Some(&monitors[0]));
+        assert_eq!(monitor_at(&monitors, (-100.0, 500.0)), Some(&monitors[1]));
+        assert_eq!(monitor_at(&monitors, (-1920.0, 0.0)), Some(&monitors[1]));
+    }
+
+    #[test]
+    fn point_outside_every_monitor_picks_the_nearest() {
+        let monitors = layout();
+        // Below the secondary monitor, which is shorter than the primary
+        assert_eq!(monitor_at(&monitors, (-500.0, 1300.0)), Some(&monitors[1]));
+        assert_eq!(monitor_at(&monitors, (3000.0, 100.0)), Some(&monitors[0]));
+        assert_eq!(monitor_at(&[], (0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn scales_the_window_to_physical_pixels() {
+        let monitors = layout();
+        // 400x300 logical is 600x450 physical at 150%, centred above the taskbar
+        assert_eq!(place(&monitors[0], (400.0, 300.0), Anchor::Center), (980.0, 459.0));
+    }
+
+    #[test]
+    fn bottom_anchor_sits_above_the_taskbar() {
+        let monitors = layout();
+        let anchor = Anchor::BottomCenter { margin: 20.0 };
+        // 1368 - 450 - 20 * 1.5
+        assert_eq!(place(&monitors[0], (400.0, 300.0), anchor), (980.0, 888.0));
+    }
+
+    #[test]
+    fn places_on_a_monitor_at_negative_coordinates() {
+        let monitors = layout();
+        assert_eq!(place(&monitors[1], (400.0, 300.0), Anchor::Center), (-1160.0, 410.0));
+    }
+
+    #[test]
+    fn oversized_window_keeps_its_corner_in_the_work_area() {
+        let monitors = layout();
+        assert_eq!(place(&monitors[1], (2000.0, 1200.0), Anchor::Center), (-1920.0, 40.0));
+    }
+
+    #[test]
+    fn missing_scale_factor_counts_as_one() {
+        let monitor = MonitorLayout {
+            scale_factor: 0.0,
+            ..layout()[1]
+        };
+        assert_eq!(place(&monitor, (400.0, 300.0), Anchor::Center), (-1160.0, 410.0));
+    }
+}
@@ -0,0 +1,274 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where a window opened by `WindowManager::create_window` is in its life.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowState {
+    /// Built, not shown yet.
+    Creating,
+    Shown,
+    /// Asked to close; waiting for the window to be destroyed.
+    Closing,
+    /// Destroyed, or the build failed.
+    Closed,
+}
+
+/// The last known state of the window with a label, as `list_windows` reports it.
+#[derive(Clone, Debug, Serialize)]
+pub struct WindowInfo {
+    pub label: String,
+    pub url: String,
+    pub state: WindowState,
+    /// Unix time in milliseconds of the last state change.
+    pub since: u64,
+    /// A window with this label exists right now. Differs from `state` only
+    /// when the registry and Tauri disagree.
+    pub open: bool,
+    /// Why the last build failed.
+    pub error: Option<String>,
+    /// Which `create_window` call the entry belongs to, so events of a replaced
+    /// window don't touch its successor.
+    #[serde(skip)]
+    id: u64,
+}
+
+struct Registry {
+    windows: HashMap<String, WindowInfo>,
+    next_id: u64,
+}
+
+fn store() -> &'static (Mutex<Registry>, Condvar) {
+    static REGISTRY: OnceLock<(Mutex<Registry>, Condvar)> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let registry = Registry {
+            windows: HashMap::new(),
+            next_id: 0,
+        };
+        (Mutex::new(registry), Condvar::new())
+    })
+}
+
+fn lock() -> MutexGuard<'static, Registry> {
+    match store().0.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Held while a window with the label is replaced, so two opens of the same
+/// window can't interleave.
+pub fn label_lock(label: &str) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<Mutex<HashMap<String, Arc<Mutex<()>>>>> = OnceLock::new();
+    let mut locks = match LOCKS.get_or_init(|| Mutex::new(HashMap::new())).lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    locks.entry(label.to_string()).or_default().clone()
+}
+
+/// Start tracking a window about to be built. Returns its id for the later updates.
+pub fn register(label: &str, url: &str) -> u64 {
+    let mut registry = lock();
+    registry.next_id += 1;
+    let id = registry.next_id;
+    registry.windows.insert(
+        label.to_string(),
+        WindowInfo {
+            label: label.to_string(),
+            url: url.to_string(),
+            state: WindowState::Creating,
+            since: now_millis(),
+            open: false,
+            error: None,
+            id,
+        },
+    );
+    id
+}
+
+fn update<R>(label: &str, id: u64, apply: impl FnOnce(&mut WindowInfo) -> R) -> Option<R> {
+    let mut registry = lock();
+    let result = registry
+        .windows
+        .get_mut(label)
+        .filter(|window| window.id == id)
+        .map(|window| {
+            window.since = now_millis();
+            apply(window)
+        });
+    store().1.notify_all();
+    result
+}
+
+pub fn mark_shown(label: &str, id: u64) {
+    update(label, id, |window| {
+        // A window closed while it was being shown stays closed
+        if window.state == WindowState::Creating {
+            window.state = WindowState::Shown;
+        }
+    });
+}
+
+/// Returns true if the window was closed through `mark_closing`, false if it went
+/// away on its own (closed by the user or the OS).
+pub fn mark_closed(label: &str, id: u64) -> bool {
+    update(label, id, |window| {
+        let requested = window.state == WindowState::Closing;
+        window.state = WindowState::Closed;
+        requested
+    })
+    .unwrap_or(true)
+}
+
+pub fn mark_failed(label: &str, id: u64, error: &str) {
+    update(label, id, |window| {
+        window.state = WindowState::Closed;
+        window.error = Some(error.to_string());
+    });
+}
+
+/// Note that the window is being closed, whichever `create_window` call opened it.
+pub fn mark_closing(label: &str) {
+    let mut registry = lock();
+    if let Some(window) = registry.windows.get_mut(label) {
+        if matches!(window.state, WindowState::Creating | WindowState::Shown) {
+            window.state = WindowState::Closing;
+            window.since = now_millis();
+        }
+    }
+}
+
+pub fn is_closing(label: &str) -> bool {
+    lock()
+        .windows
+        .get(label)
+        .is_some_and(|window| window.state == WindowState::Closing)
+}
+
+/// Wait until the window with the label is no longer closing. False on timeout.
+pub fn wait_closed(label: &str, timeout: Duration) -> bool {
+    let (_, changed) = store();
+    let result = changed.wait_timeout_while(lock(), timeout, |registry| {
+        registry
+            .windows
+            .get(label)
+            .is_some_and(|window| window.state == WindowState::Closing)
+    });
+    match result {
+        Ok((_, wait)) => !wait.timed_out(),
+        Err(poisoned) => !poisoned.into_inner().1.timed_out(),
+    }
+}
+
+/// Labels of the windows that aren't closed, including ones still being built.
+pub fn labels() -> Vec<String> {
+    lock()
+        .windows
+        .values()
+        .filter(|window| window.state != WindowState::Closed)
+        .map(|window| window.label.clone())
+        .collect()
+}
+
+/// Every tracked window by label. `is_open` asks Tauri whether the window exists;
+/// it runs without the registry lock held, since window events take it too.
+pub fn list(is_open: impl Fn(&str) -> bool) -> Vec<WindowInfo> {
+    let mut windows: Vec<WindowInfo> = lock().windows.values().cloned().collect();
+    windows.sort_by(|a, b| a.label.cmp(&b.label));
+    for window in &mut windows {
+        window.open = is_open(&window.label);
+    }
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // The registry is global, so every test uses its own labels
+
+    fn state(label: &str) -> Option<WindowState> {
+        lock().windows.get(label).map(|window| window.state)
+    }
+
+    #[test]
+    fn updates_for_a_replaced_window_are_ignored() {
+        let old = register("test-stale", "old.html");
+        let new = register("test-stale", "new.html");
+
+        mark_shown("test-stale", old);
+        assert_eq!(state("test-stale"), Some(WindowState::Creating));
+        mark_failed("test-stale", old, "gone");
+        assert_eq!(state("test-stale"), Some(WindowState::Creating));
+        // The old window's destruction is not the new one's
+        assert!(mark_closed("test-stale", old));
+        assert_eq!(state("test-stale"), Some(WindowState::Creating));
+
+        mark_shown("test-stale", new);
+        assert_eq!(state("test-stale"), Some(WindowState::Shown));
+    }
+
+    #[test]
+    fn mark_closed_tells_requested_closes_apart() {
+        let id = register("test-requested", "a.html");
+        mark_shown("test-requested", id);
+        mark_closing("test-requested");
+        assert!(mark_closed("test-requested", id));
+        assert_eq!(state("test-requested"), Some(WindowState::Closed));
+
+        let id = register("test-by-hand", "a.html");
+        mark_shown("test-by-hand", id);
+        assert!(!mark_closed("test-by-hand", id));
+        assert_eq!(state("test-by-hand"), Some(WindowState::Closed));
+    }
+
+    #[test]
+    fn closing_a_window_being_shown_keeps_it_closing() {
+        let id = register("test-early-close", "a.html");
+        mark_closing("test-early-close");
+        mark_shown("test-early-close", id);
+        assert_eq!(state("test-early-close"), Some(WindowState::Closing));
+    }
+
+    #[test]
+    fn wait_closed_times_out_while_the_window_is_closing() {
+        register("test-slow-close", "a.html");
+        mark_closing("test-slow-close");
+        assert!(!wait_closed("test-slow-close", Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn wait_closed_returns_once_the_window_is_destroyed() {
+        let id = register("test-fast-close", "a.html");
+        mark_closing("test-fast-close");
+        let destroy = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            mark_closed("test-fast-close", id);
+        });
+        assert!(wait_closed("test-fast-close", Duration::from_secs(5)));
+        destroy.join().unwrap();
+        // Nothing to wait for when the window isn't closing
+        assert!(wait_closed("test-never-opened", Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn labels_skip_closed_windows() {
+        let id = register("test-labels-closed", "a.html");
+        mark_closed("test-labels-closed", id);
+        register("test-labels-creating", "a.html");
+        let labels = labels();
+        assert!(labels.contains(&"test-labels-creating".to_string()));
+        assert!(!labels.contains(&"test-labels-closed".to_string()));
+    }
+}
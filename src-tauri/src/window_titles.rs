@@ -0,0 +1,500 @@
+use std::sync::{Mutex, OnceLock};
+
+/// A way of listing the titles of the user's open top-level windows.
+pub trait WindowTitleSource: Send {
+    fn name(&self) -> &'static str;
+
+    /// Titles of the open windows, or `None` if the source stopped working.
+    fn titles(&mut self) -> Option<Vec<String>>;
+}
+
+/// Lists window titles for browser meeting detection.
+///
+/// Backends are tried in order and the first one that answers is kept:
+/// - Windows: `EnumWindows` / `GetWindowTextW`
+/// - Linux/Wayland: the `ext-foreign-toplevel-list-v1` protocol (wlroots
+///   compositors, KDE; not GNOME)
+/// - Linux/X11: the EWMH `_NET_CLIENT_LIST` and `_NET_WM_NAME` properties.
+///   Under Wayland this only sees XWayland windows.
+pub struct WindowTitles {
+    source: Option<Box<dyn WindowTitleSource>>,
+}
+
+impl WindowTitles {
+    pub fn detect() -> Self {
+        for mut source in candidate_sources() {
+            if source.titles().is_some() {
+                println!("🪟 Window titles using {}", source.name());
+                return Self { source: Some(source) };
+            }
+        }
+        println!("ℹ️ No window title backend available");
+        Self { source: None }
+    }
+
+    pub fn backend_name(&self) -> Option<&'static str> {
+        self.source.as_ref().map(|source| source.name())
+    }
+
+    pub fn titles(&mut self) -> Vec<String> {
+        let source = match self.source.as_mut() {
+            Some(source) => source,
+            None => return Vec::new(),
+        };
+        match source.titles() {
+            Some(titles) => titles,
+            None => {
+                println!("⚠️ Window titles from {} unavailable", source.name());
+                Vec::new()
+            }
+        }
+    }
+}
+
+fn candidate_sources() -> Vec<Box<dyn WindowTitleSource>> {
+    #[allow(unused_mut)]
+    let mut sources: Vec<Box<dyn WindowTitleSource>> = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    sources.push(Box::new(windows_titles::EnumWindowsSource));
+
+    #[cfg(target_os = "linux")]
+    {
+        // The Wayland list includes XWayland windows too, so it goes first
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            if let Some(source) = wayland_titles::ForeignToplevelSource::connect() {
+                sources.push(Box::new(source));
+            }
+        }
+        if std::env::var_os("DISPLAY").is_some() {
+            if let Some(source) = x11_titles::X11TitleSource::connect(None) {
+                sources.push(Box::new(source));
+            }
+        }
+    }
+
+    sources
+}
+
+static WINDOW_TITLES: OnceLock<Mutex<WindowTitles>> = OnceLock::new();
+
+fn window_titles() -> &'static Mutex<WindowTitles> {
+    WINDOW_TITLES.get_or_init(|| Mutex::new(WindowTitles::detect()))
+}
+
+/// Titles of all open top-level windows, from the backend picked on first use.
+pub fn visible_window_titles() -> Vec<String> {
+    window_titles()
+        .lock()
+        .map(|mut titles| titles.titles())
+        .unwrap_or_default()
+}
+
+/// The backend in use, detecting one if that hasn't happened yet.
+pub fn backend_name() -> Option<&'static str> {
+    window_titles().lock().ok()?.backend_name()
+}
+
+#[cfg(target_os = "windows")]
+mod windows_titles {
+    use super::WindowTitleSource;
+
+    pub struct EnumWindowsSource;
+
+    impl WindowTitleSource for EnumWindowsSource {
+        fn name(&self) -> &'static str {
+            "EnumWindows"
+        }
+
+        fn titles(&mut self) -> Option<Vec<String>> {
+            use std::ffi::OsString;
+            use std::os::windows::ffi::OsStringExt;
+            use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
+            use winapi::shared::windef::HWND;
+            use winapi::um::winuser::{EnumWindows, GetWindowTextW, IsWindowVisible};
+
+            // Callback function for EnumWindows
+            unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
+                let titles = &mut *(lparam as *mut Vec<String>);
+
+                // Only check visible windows
+                if IsWindowVisible(hwnd) == 0 {
+                    return TRUE;
+                }
+
+                // Get window title
+                let mut title: [u16; 512] = [0; 512];
+                let title_len = GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32);
+
+                if title_len > 0 {
+                    let title_os_string = OsString::from_wide(&title[..title_len as usize]);
+                    if let Ok(title_string) = title_os_string.into_string() {
+                        titles.push(title_string);
+                    }
+                }
+
+                TRUE // Continue enumeration
+            }
+
+            let mut titles: Vec<String> = Vec::new();
+            let ok = unsafe {
+                EnumWindows(Some(enum_windows_proc), &mut titles as *mut Vec<String> as LPARAM)
+            };
+            (ok != 0).then_some(titles)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11_titles {
+    use super::WindowTitleSource;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            _NET_CLIENT_LIST,
+            _NET_WM_NAME,
+            UTF8_STRING,
+        }
+    }
+
+    /// Longest title read, in 32-bit units.
+    const TITLE_LENGTH: u32 = 256;
+
+    /// Reads the window manager's client list from the root window.
+    /// Needs an EWMH window manager; a bare X server has no `_NET_CLIENT_LIST`.
+    pub struct X11TitleSource {
+        conn: RustConnection,
+        root: Window,
+        atoms: Atoms,
+    }
+
+    impl X11TitleSource {
+        /// Connect to `display` (e.g. `":99"` for an Xvfb server), or to
+        /// `$DISPLAY` when `None`.
+        pub fn connect(display: Option<&str>) -> Option<Self> {
+            let (conn, screen_num) = x11rb::connect(display).ok()?;
+            let root = conn.setup().roots.get(screen_num)?.root;
+            let atoms = Atoms::new(&conn).ok()?.reply().ok()?;
+            Some(Self { conn, root, atoms })
+        }
+
+        fn client_list(&self) -> Option<Vec<Window>> {
+            let reply = self
+                .conn
+                .get_property(false, self.root, self.atoms._NET_CLIENT_LIST, AtomEnum::WINDOW, 0, u32::MAX)
+                .ok()?
+                .reply()
+                .ok()?;
+            if reply.type_ == x11rb::NONE {
+                return None;
+            }
+            reply.value32().map(|windows| windows.collect())
+        }
+
+        /// `_NET_WM_NAME` (UTF-8), falling back to the legacy Latin-1 `WM_NAME`.
+        /// All requests go out before the first reply is awaited.
+        fn window_titles(&self, windows: &[Window]) -> Vec<String> {
+            let requests: Vec<_> = windows
+                .iter()
+                .map(|&window| {
+                    let utf8 = self.conn.get_property(
+                        false,
+                        window,
+                        self.atoms._NET_WM_NAME,
+                        self.atoms.UTF8_STRING,
+                        0,
+                        TITLE_LENGTH,
+                    );
+                    let legacy = self.conn.get_property(
+                        false,
+                        window,
+                        AtomEnum::WM_NAME,
+                        AtomEnum::STRING,
+                        0,
+                        TITLE_LENGTH,
+                    );
+                    (utf8, legacy)
+                })
+                .collect();
+
+            let mut titles = Vec::new();
+            for (utf8, legacy) in requests {
+                // Windows closed since the list was read answer with an error
+                let utf8 = utf8.ok().and_then(|cookie| cookie.reply().ok());
+                let legacy = legacy.ok().and_then(|cookie| cookie.reply().ok());
+                titles.extend(pick_title(
+                    utf8.as_ref().map(|reply| reply.value.as_slice()),
+                    legacy.as_ref().map(|reply| reply.value.as_slice()),
+                ));
+            }
+            titles
+        }
+    }
+
+    /// The title from a window's `_NET_WM_NAME` and Latin-1 `WM_NAME` values,
+    /// the first unless it is empty.
+    fn pick_title(utf8: Option<&[u8]>, legacy: Option<&[u8]>) -> Option<String> {
+        let title: String = match (utf8, legacy) {
+            (Some(utf8), _) if !utf8.is_empty() => String::from_utf8_lossy(utf8).into_owned(),
+            (_, Some(legacy)) => legacy.iter().map(|&b| b as char).collect(),
+            _ => return None,
+        };
+        (!title.is_empty()).then_some(title)
+    }
+
+    impl WindowTitleSource for X11TitleSource {
+        fn name(&self) -> &'static str {
+            "X11 _NET_CLIENT_LIST"
+        }
+
+        fn titles(&mut self) -> Option<Vec<String>> {
+            let windows = self.client_list()?;
+            Some(self.window_titles(&windows))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use x11rb::protocol::xproto::{CreateWindowAux, PropMode, WindowClass};
+        use x11rb::wrapper::ConnectionExt as _;
+
+        #[test]
+        fn utf8_title_wins_over_wm_name() {
+            assert_eq!(
+                pick_title(Some("Zoom – Meeting".as_bytes()), Some(b"Zoom - Meeting")),
+                Some("Zoom – Meeting".to_string())
+            );
+        }
+
+        #[test]
+        fn wm_name_is_read_as_latin1_without_a_utf8_title() {
+            let latin1: &[u8] = b"R\xe9union";
+            assert_eq!(pick_title(None, Some(latin1)), Some("Réunion".to_string()));
+            assert_eq!(pick_title(Some(b""), Some(latin1)), Some("Réunion".to_string()));
+        }
+
+        #[test]
+        fn windows_without_a_title_are_skipped() {
+            assert_eq!(pick_title(None, None), None);
+            assert_eq!(pick_title(Some(b""), Some(b"")), None);
+        }
+
+        #[test]
+        #[ignore = "needs an X server at $TEST_DISPLAY, such as Xvfb :99"]
+        fn reads_titles_of_the_client_list() {
+            let display = std::env::var("TEST_DISPLAY").expect("TEST_DISPLAY is set");
+            let (conn, screen_num) = x11rb::connect(Some(&display)).unwrap();
+            let root = conn.setup().roots[screen_num].root;
+            let atoms = Atoms::new(&conn).unwrap().reply().unwrap();
+            let create_window = || {
+                let window = conn.generate_id().unwrap();
+                conn.create_window(
+                    x11rb::COPY_DEPTH_FROM_PARENT,
+                    window,
+                    root,
+                    0,
+                    0,
+                    10,
+                    10,
+                    0,
+                    WindowClass::INPUT_OUTPUT,
+                    x11rb::COPY_FROM_PARENT,
+                    &CreateWindowAux::new(),
+                )
+                .unwrap();
+                window
+            };
+
+            let modern = create_window();
+            conn.change_property8(
+                PropMode::REPLACE,
+                modern,
+                atoms._NET_WM_NAME,
+                atoms.UTF8_STRING,
+                "Zoom Meeting – Über".as_bytes(),
+            )
+            .unwrap();
+            let legacy = create_window();
+            conn.change_property8(PropMode::REPLACE, legacy, AtomEnum::WM_NAME, AtomEnum::STRING, b"R\xe9union")
+                .unwrap();
+            // Stands in for the window manager
+            conn.change_property32(
+                PropMode::REPLACE,
+                root,
+                atoms._NET_CLIENT_LIST,
+                AtomEnum::WINDOW,
+                &[modern, legacy],
+            )
+            .unwrap();
+            conn.sync().unwrap();
+
+            let mut source = X11TitleSource::connect(Some(&display)).expect("the display is reachable");
+            assert_eq!(
+                source.titles(),
+                Some(vec!["Zoom Meeting – Über".to_string(), "Réunion".to_string()])
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod wayland_titles {
+    use super::WindowTitleSource;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use wayland_client::backend::ObjectId;
+    use wayland_client::protocol::wl_registry;
+    use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+    use wayland_protocols::ext::foreign_toplevel_list::v1::client::{
+        ext_foreign_toplevel_handle_v1, ext_foreign_toplevel_list_v1,
+    };
+
+    #[derive(Default)]
+    struct Toplevel {
+        /// Title sent since the last `done`, applied atomically on `done`.
+        pending: Option<String>,
+        title: String,
+    }
+
+    #[derive(Default)]
+    struct State {
+        list: Option<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1>,
+        toplevels: HashMap<ObjectId, Toplevel>,
+        /// Committed titles, shared with the source.
+        titles: Arc<Mutex<HashMap<ObjectId, String>>>,
+    }
+
+    impl Dispatch<wl_registry::WlRegistry, ()> for State {
+        fn event(
+            state: &mut Self,
+            registry: &wl_registry::WlRegistry,
+            event: wl_registry::Event,
+            _: &(),
+            _: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_registry::Event::Global { name, interface, .. } = event {
+                if interface == "ext_foreign_toplevel_list_v1" {
+                    state.list = Some(registry.bind(name, 1, qh, ()));
+                }
+            }
+        }
+    }
+
+    impl Dispatch<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, ()> for State {
+        fn event(
+            state: &mut Self,
+            _: &ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1,
+            event: ext_foreign_toplevel_list_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            match event {
+                ext_foreign_toplevel_list_v1::Event::Toplevel { toplevel } => {
+                    state.toplevels.insert(toplevel.id(), Toplevel::default());
+                }
+                ext_foreign_toplevel_list_v1::Event::Finished => {
+                    state.list = None;
+                }
+                _ => {}
+            }
+        }
+
+        event_created_child!(State, ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, [
+            ext_foreign_toplevel_list_v1::EVT_TOPLEVEL_OPCODE => (ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, ()),
+        ]);
+    }
+
+    impl Dispatch<ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, ()> for State {
+        fn event(
+            state: &mut Self,
+            handle: &ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1,
+            event: ext_foreign_toplevel_handle_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            let id = handle.id();
+            match event {
+                ext_foreign_toplevel_handle_v1::Event::Title { title } => {
+                    if let Some(toplevel) = state.toplevels.get_mut(&id) {
+                        toplevel.pending = Some(title);
+                    }
+                }
+                ext_foreign_toplevel_handle_v1::Event::Done => {
+                    if let Some(toplevel) = state.toplevels.get_mut(&id) {
+                        if let Some(title) = toplevel.pending.take() {
+                            toplevel.title = title;
+                        }
+                        if let Ok(mut titles) = state.titles.lock() {
+                            titles.insert(id, toplevel.title.clone());
+                        }
+                    }
+                }
+                ext_foreign_toplevel_handle_v1::Event::Closed => {
+                    state.toplevels.remove(&id);
+                    if let Ok(mut titles) = state.titles.lock() {
+                        titles.remove(&id);
+                    }
+                    handle.destroy();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub struct ForeignToplevelSource {
+        titles: Arc<Mutex<HashMap<ObjectId, String>>>,
+        alive: Arc<Mutex<bool>>,
+    }
+
+    impl ForeignToplevelSource {
+        /// Connect to the compositor and subscribe to the toplevel list.
+        /// Returns `None` if the compositor lacks `ext-foreign-toplevel-list-v1`
+        /// or doesn't offer it to ordinary clients.
+        pub fn connect() -> Option<Self> {
+            let conn = Connection::connect_to_env().ok()?;
+            let mut queue = conn.new_event_queue();
+            let qh = queue.handle();
+            conn.display().get_registry(&qh, ());
+
+            let mut state = State::default();
+            queue.roundtrip(&mut state).ok()?;
+            state.list.as_ref()?;
+            // Second roundtrip: the initial toplevels and their titles
+            queue.roundtrip(&mut state).ok()?;
+
+            let titles = state.titles.clone();
+            let alive = Arc::new(Mutex::new(true));
+            let thread_alive = alive.clone();
+            std::thread::spawn(move || {
+                while state.list.is_some() && queue.blocking_dispatch(&mut state).is_ok() {}
+                println!("⚠️ Wayland toplevel list stopped");
+                if let Ok(mut alive) = thread_alive.lock() {
+                    *alive = false;
+                }
+            });
+
+            Some(Self { titles, alive })
+        }
+    }
+
+    impl WindowTitleSource for ForeignToplevelSource {
+        fn name(&self) -> &'static str {
+            "ext-foreign-toplevel-list-v1"
+        }
+
+        fn titles(&mut self) -> Option<Vec<String>> {
+            if !*self.alive.lock().ok()? {
+                return None;
+            }
+            let titles = self.titles.lock().ok()?;
+            Some(titles.values().filter(|title| !title.is_empty()).cloned().collect())
+        }
+    }
+}
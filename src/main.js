@@ -5,16 +5,13 @@ import { updateManager } from './shared/update-manager.js';
 
 const { invoke } = window.__TAURI__.core;
 
-// Timer state
-let timerInterval = null;
+const { listen } = window.__TAURI__.event;
+
+// Timer state — mirrored from the Rust timer engine via `timer-state` events
+let timerState = null;
 let timerSeconds = 0;
 let isTimerRunning = false;
 let isTimerPaused = false;
-let startTime = null;
-let pausedTime = 0;
-let preBreakTriggered = false;
-let currentTimerSettings = null; // Cache settings for current timer session
-let recurringTimeout = null; // Track recurring timer timeout
 
 // Settings management
 async function saveSettings() {
@@ -57,122 +54,94 @@ function applySettingsToUI(settings) {
   }
 }
 
-// Timer functions
+// Timer functions — the countdown itself runs in Rust (timer_engine.rs)
 async function startTimer(seconds) {
   console.log(`🚀 Starting timer with ${seconds} seconds (${Math.floor(seconds/60)}:${seconds%60})`);
-  
-  // Load settings once at the start of the timer
-  currentTimerSettings = await loadSettings();
-  console.log('⚙️ Settings loaded for timer session:', currentTimerSettings);
-  
-  timerSeconds = seconds;
-  isTimerRunning = true;
-  startTime = Date.now();
-  preBreakTriggered = false; // Reset pre-break flag
-  updateTimerDisplay();
-  updateTimerControls();
-  updatePanelVisibility();
-
-  if (timerInterval) clearInterval(timerInterval);
-  timerInterval = setInterval(async () => {
-    timerSeconds--;
-    updateTimerDisplay();
-    updateRunningTime();
-    
-    // Debug logging for last 30 seconds
-    if (timerSeconds <= 30) {
-      console.log(`⏰ Timer countdown: ${timerSeconds} seconds remaining`);
-    }
-    
-    // Check for pre-break warning trigger
-    await checkPreBreakTrigger();
-    
-    if (timerSeconds <= 0) {
-      console.log('⏰ Timer reached zero! Triggering break...');
-      clearInterval(timerInterval);
-      
-      // Don't set isTimerRunning = false yet if recurring is enabled
-      // This prevents UI from jumping back to settings panel
-      const willRecur = currentTimerSettings && currentTimerSettings.recurring;
-      if (!willRecur) {
-        isTimerRunning = false;
-        updatePanelVisibility();
-      }
-      
-      updateTimerControls();
-      handleBreakTime();
-    }
-  }, 1000);
+  try {
+    applyTimerState(await invoke('start_timer', { seconds }));
+  } catch (error) {
+    console.error('Failed to start timer:', error);
+    document.getElementById('timer-status').textContent = `Could not start timer: ${error}`;
+  }
 }
 
-function pauseTimer() {
-  if (timerInterval) {
-    clearInterval(timerInterval);
-    timerInterval = null;
+async function pauseTimer() {
+  try {
+    applyTimerState(await invoke('pause_timer'));
+  } catch (error) {
+    console.error('Failed to pause timer:', error);
   }
-  isTimerPaused = true;
-  pausedTime += Date.now() - startTime;
-  updateTimerControls();
 }
 
-function resumeTimer() {
+async function resumeTimer() {
   if (!isTimerPaused) return;
-  
-  isTimerPaused = false;
-  startTime = Date.now();
-  
-  timerInterval = setInterval(async () => {
-    timerSeconds--;
-    updateTimerDisplay();
-    updateRunningTime();
-    
-    // Debug logging for last 30 seconds
-    if (timerSeconds <= 30) {
-      console.log(`⏰ Timer countdown (resumed): ${timerSeconds} seconds remaining`);
-    }
-    
-    // Check for pre-break warning trigger
-    await checkPreBreakTrigger();
-    
-    if (timerSeconds <= 0) {
-      console.log('⏰ Timer reached zero (resumed)! Triggering break...');
-      clearInterval(timerInterval);
-      isTimerPaused = false;
-      
-      // Don't set isTimerRunning = false yet if recurring is enabled
-      // This prevents UI from jumping back to settings panel
-      const willRecur = currentTimerSettings && currentTimerSettings.recurring;
-      if (!willRecur) {
-        isTimerRunning = false;
-        updatePanelVisibility();
-      }
-      
-      updateTimerControls();
-      handleBreakTime();
-    }
-  }, 1000);
-  
-  updateTimerControls();
+  try {
+    applyTimerState(await invoke('resume_timer'));
+  } catch (error) {
+    console.error('Failed to resume timer:', error);
+  }
 }
 
-function stopTimer() {
-  if (timerInterval) {
-    clearInterval(timerInterval);
-    timerInterval = null;
-  }
-  if (recurringTimeout) {
-    clearTimeout(recurringTimeout);
-    recurringTimeout = null;
+async function stopTimer() {
+  try {
+    applyTimerState(await invoke('stop_timer'));
+  } catch (error) {
+    console.error('Failed to stop timer:', error);
   }
-  isTimerRunning = false;
-  isTimerPaused = false;
-  timerSeconds = 0;
-  startTime = null;
-  pausedTime = 0;
-  currentTimerSettings = null; // Clear cached settings
+}
+
+// Apply a TimerSnapshot from the Rust engine to the UI
+function applyTimerState(state) {
+  const previousPhase = timerState?.phase;
+  const previousReason = timerState?.postpone_reason;
+  const previousCalendarReason = timerState?.calendar_reason;
+  timerState = state;
+  isTimerRunning = state.phase !== 'idle';
+  isTimerPaused = state.paused;
+  timerSeconds = state.phase === 'on_break' ? 0 : state.remaining_seconds;
+
   updateTimerDisplay();
+  updateRunningTime();
   updateTimerControls();
-  updatePanelVisibility();
+  if (state.idle_paused) {
+    document.getElementById('timer-status').textContent = '🚶 Paused while you are away';
+  }
+  if (previousPhase !== state.phase) {
+    updatePanelVisibility();
+    updatePhaseStatus(previousPhase, state);
+  } else if (previousReason !== state.postpone_reason || previousCalendarReason !== state.calendar_reason) {
+    updatePhaseStatus(previousPhase, state);
+  }
+}
+
+function updatePhaseStatus(previousPhase, state) {
+  const statusElement = document.getElementById('timer-status');
+  if (!statusElement) return;
+
+  switch (state.phase) {
+    case 'pre_break':
+      statusElement.textContent = `Pre-break warning shown - break starting in ${state.remaining_seconds} seconds`;
+      break;
+    case 'postponed':
+      statusElement.textContent = state.postpone_reason
+        ? `Break postponed - ${state.postpone_reason}`
+        : 'Meeting detected - break postponed until it ends';
+      break;
+    case 'on_break': {
+      const name = state.break_tier || 'Break';
+      statusElement.textContent = state.recurring
+        ? `${name} in progress... Next timer starts in ${Math.ceil(state.break_seconds / 60)} minutes`
+        : `${name} in progress - ${state.break_seconds}s break!`;
+      break;
+    }
+    case 'working':
+      if (previousPhase === 'on_break') {
+        statusElement.textContent = '🔄 Starting next timer session...';
+      } else if (state.calendar_reason) {
+        statusElement.textContent = `📅 ${state.calendar_reason}`;
+      }
+      break;
+  }
 }
 
 function updateTimerDisplay() {
@@ -181,7 +150,13 @@ function updateTimerDisplay() {
 
   if (timerSeconds > 0) {
     countdown.textContent = TimerUtils.formatTime(timerSeconds);
-    label.textContent = "Next break in";
+    const tier = timerState?.break_tier;
+    const pomodoro = timerState?.pomodoro;
+    if (pomodoro) {
+      label.textContent = `Pomodoro ${pomodoro.number}/${pomodoro.of} — ${tier.toLowerCase()} in`;
+    } else {
+      label.textContent = tier && tier !== 'Break' ? `Next ${tier.toLowerCase()} in` : "Next break in";
+    }
 
     // Add pulse effect when less than 1 minute
     UIUtils.toggleClass("timer-countdown", "pulse", timerSeconds <= 60);
@@ -257,16 +232,8 @@ function updatePanelVisibility() {
 }
 
 function updateRunningTime() {
-  if (!startTime && !pausedTime) return;
-
-  let totalElapsed;
-  if (isTimerPaused) {
-    totalElapsed = Math.floor(pausedTime / 1000);
-  } else {
-    totalElapsed = Math.floor((pausedTime + (Date.now() - startTime)) / 1000);
-  }
-  
-  UIUtils.updateText('running-time', TimerUtils.formatTime(totalElapsed));
+  if (!timerState || timerState.phase === 'idle') return;
+  UIUtils.updateText('running-time', TimerUtils.formatTime(timerState.elapsed_seconds));
 }
 
 function updateInfoPanel() {
@@ -278,7 +245,10 @@ function updateInfoPanel() {
 
   const breakDurationDisplay = document.getElementById('break-duration-display');
   if (breakDurationDisplay) {
-    breakDurationDisplay.textContent = `${durationMin}:${durationSec}`;
+    // Pomodoro breaks alternate between short and long; show the upcoming one
+    breakDurationDisplay.textContent = timerState?.pomodoro
+      ? TimerUtils.formatTime(timerState.break_seconds)
+      : `${durationMin}:${durationSec}`;
   }
 
   // Update break mode display
@@ -290,7 +260,8 @@ function updateInfoPanel() {
     const modeInfo = {
       'force': { name: 'Force Break', icon: '🔒' },
       'notify': { name: 'Notify Only', icon: '🔔' },
-      'lock': { name: 'Lock Screen', icon: '🛡️' }
+      'lock': { name: 'Lock Screen', icon: '🛡️' },
+      'pomodoro': { name: 'Pomodoro', icon: '🍅' }
     };
     const info = modeInfo[breakMode] || modeInfo['force'];
     modeTextCompact.textContent = info.name;
@@ -322,268 +293,54 @@ function getBreakDurationValue() {
   return totalSeconds;
 }
 
-// Pre-break trigger check
-async function checkPreBreakTrigger() {
-  if (preBreakTriggered) return; // Already triggered
-  if (!currentTimerSettings) return; // No settings loaded
-  
-  try {
-    if (!currentTimerSettings.pre_break) return; // Pre-break not enabled
-    
-    const preBreakTimingSeconds = (currentTimerSettings.pre_break_minutes * 60) + currentTimerSettings.pre_break_seconds;
-    
-    // Trigger pre-break when remaining time equals pre-break timing
-    if (timerSeconds === preBreakTimingSeconds) {
-      console.log(`🚨 PRE-BREAK TRIGGER! ${preBreakTimingSeconds} seconds remaining (should show at bottom center)`);
-      preBreakTriggered = true;
-      
-      await invoke("pre_break_notification_window", { remainingSeconds: timerSeconds });
-      
-      const minutes = Math.floor(preBreakTimingSeconds / 60);
-      const seconds = preBreakTimingSeconds % 60;
-      let timingText = '';
-      if (minutes > 0) {
-        timingText = `${minutes} minute${minutes > 1 ? 's' : ''}`;
-        if (seconds > 0) {
-          timingText += ` ${seconds} second${seconds > 1 ? 's' : ''}`;
-        }
-      } else {
-        timingText = `${seconds} second${seconds > 1 ? 's' : ''}`;
-      }
-      
-      document.getElementById('timer-status').textContent = `Pre-break warning shown - break starting in ${timingText}`;
-    }
-  } catch (error) {
-    console.error('Error checking pre-break trigger:', error);
-  }
-}
-
-// Break handling
-async function handleBreakTime() {
-  console.log('🚨 BREAK TIME TRIGGERED! Starting break handling...');
-  try {
-    // Save current settings first to ensure we use the latest values
-    await saveSettings();
-    
-    // Small delay to ensure settings are saved
-    await new Promise(resolve => setTimeout(resolve, 100));
-    
-    // Use cached settings from timer session
-    const settings = currentTimerSettings || await loadSettings();
-    const breakMode = document.querySelector('input[name="break-mode"]:checked').value;
-
-    console.log('🔧 Break time! Using cached settings:', settings);
-    console.log('🔧 Pre-break enabled:', settings.pre_break);
-    console.log('🔧 Pre-break timing:', settings.pre_break_minutes, 'minutes', settings.pre_break_seconds, 'seconds');
-    console.log('🔧 Break mode:', breakMode);
-
-    // Double-check that settings match UI
-    const uiDurationMinutes = parseInt(document.getElementById("break-duration-minutes").value) || 0;
-    const uiDurationSeconds = parseInt(document.getElementById("break-duration-seconds").value) || 0;
-    console.log('UI values:', { minutes: uiDurationMinutes, seconds: uiDurationSeconds });
-    
-    if (settings.break_duration_minutes !== uiDurationMinutes || settings.break_duration_seconds !== uiDurationSeconds) {
-      console.warn('Settings mismatch! Saving again...');
-      await saveSettings();
-      await new Promise(resolve => setTimeout(resolve, 100));
-    }
-
-    // Check if we're in a meeting and should skip break
-    if (settings.meeting_detect) {
-      const inMeeting = await invoke("is_meeting_active");
-      if (inMeeting) {
-        console.log("Meeting detected, postponing break...");
-        document.getElementById('timer-status').textContent = 'Meeting detected - break postponed by 10 minutes';
-        
-        // Show meeting detected notification
-        try {
-          await invoke("meeting_detected_notification");
-        } catch (error) {
-          console.error("Failed to show meeting notification:", error);
-        }
-        
-        await startTimer(10 * 60);
-        return;
-      }
-    }
-
-    // Trigger the main break immediately (pre-break was already shown during countdown)
-    await triggerMainBreak(breakMode, settings);
-
-  } catch (error) {
-    console.error("Error handling break time:", error);
-    document.getElementById('timer-status').textContent = 'Error triggering break. Please try again.';
-  }
-}
-
-async function triggerMainBreak(breakMode, settings) {
-  try {
-    if (settings.auto_pause) {
-      // Always call control_media — the Rust side handles both VLC (WM_APPCOMMAND)
-      // and SMTC players (Spotify, YouTube, etc.) independently.
-      // State is stored server-side via set_media_was_playing so all webview windows
-      // (force_break, notify) can read it — localStorage is per-webview and not shared.
-      console.log('🎵 auto_pause enabled — pausing media and recording state');
-      await invoke('set_media_was_playing', { wasPlaying: true });
-      await invoke('control_media', { action: 'pause' });
-    } else {
-      // auto_pause is off — record that so break windows skip resume
-      await invoke('set_media_was_playing', { wasPlaying: false });
-    }
-
-    const breakDurationSeconds = getBreakDurationValue();
-
-    switch (breakMode) {
-      case "force":
-        await invoke("force_break_window", { duration: breakDurationSeconds });
-        document.getElementById('timer-status').textContent = `Force break window opened - ${breakDurationSeconds}s break!`;
-        break;
-      case "notify":
-        await invoke("notify_window", { duration: breakDurationSeconds });
-        document.getElementById('timer-status').textContent = `Break notification shown - ${breakDurationSeconds}s break!`;
-        break;
-      case "lock":
-        await invoke("lock_screen");
-        document.getElementById('timer-status').textContent = 'Screen locked for break time';
-        break;
-    }
-
-    if (settings.recurring) {
-      console.log(`🔄 Setting up recurring timer for ${breakDurationSeconds} seconds`);
-      
-      // Update status to show break is in progress
-      document.getElementById('timer-status').textContent = `Break in progress... Next timer starts in ${Math.ceil(breakDurationSeconds/60)} minutes`;
-      
-      recurringTimeout = setTimeout(async () => {
-        console.log('🔄 Recurring timer triggered - starting next timer');
-        const breakTimerSeconds = getBreakTimerValue();
-        
-        // Update status before starting new timer
-        document.getElementById('timer-status').textContent = '🔄 Starting next timer session...';
-        
-        // Small delay to show the message
-        setTimeout(async () => {
-          await startTimer(breakTimerSeconds);
-        }, 500);
-      }, breakDurationSeconds * 1000);
-    } else {
-      // If not recurring, now we can set timer as stopped
-      isTimerRunning = false;
-      updatePanelVisibility();
-    }
-
-  } catch (error) {
-    console.error("Error triggering break:", error);
-    document.getElementById('timer-status').textContent = 'Error during break. Please check your settings.';
-  }
-}
-
-// Handle break skip (when user clicks skip break button)
+// Handle break skip (when user clicks skip break button).
+// The engine has already moved on; this only updates the status line.
 function handleBreakSkipped() {
   console.log('⏭️ Break was skipped by user');
-  
-  // Clear any existing timers
-  if (timerInterval) {
-    clearInterval(timerInterval);
-    timerInterval = null;
-  }
-  if (recurringTimeout) {
-    clearTimeout(recurringTimeout);
-    recurringTimeout = null;
-  }
-  
-  // Store recurring setting before clearing timer state
-  const wasRecurring = currentTimerSettings && currentTimerSettings.recurring;
-  console.log('DEBUG: currentTimerSettings:', currentTimerSettings);
-  console.log('DEBUG: wasRecurring:', wasRecurring);
-  
-  // If recurring is enabled, start the next timer immediately (keep UI on timer screen)
-  if (wasRecurring) {
-    console.log('🔄 Starting next timer immediately due to break skip');
-    
-    const statusElement = document.getElementById('timer-status');
-    if (statusElement) {
-      statusElement.textContent = '⏭️ Break skipped - Starting next timer session...';
-    }
-    
-    // Start next timer immediately
-    setTimeout(async () => {
-      const breakTimerSeconds = getBreakTimerValue();
-      await startTimer(breakTimerSeconds);
-    }, 500);
-  } else {
-    // If recurring is not enabled, stop the timer and show settings
-    console.log('✅ Recurring not enabled, stopping timer');
-    isTimerRunning = false;
-    isTimerPaused = false;
-    timerSeconds = 0;
-    
-    const statusElement = document.getElementById('timer-status');
-    if (statusElement) {
-      statusElement.textContent = '⏭️ Break skipped - Timer session finished';
-    }
-    
-    // Clear settings and reset UI state
-    currentTimerSettings = null;
-    updateTimerDisplay();
-    updateTimerControls();
-    updatePanelVisibility();
+  const statusElement = document.getElementById('timer-status');
+  if (statusElement) {
+    statusElement.textContent = timerState?.recurring
+      ? '⏭️ Break skipped - Starting next timer session...'
+      : '⏭️ Break skipped - Timer session finished';
   }
 }
 
 // Handle early return from break (when user closes break window early)
 function handleEarlyBreakReturn() {
   console.log('🏃 User returned early from break');
-  console.log('🔧 Current timer settings:', currentTimerSettings);
-  console.log('🔧 Recurring enabled:', currentTimerSettings?.recurring);
-  console.log('🔧 Timer currently running:', isTimerRunning);
-  
-  // Clear the existing recurring timeout since user returned early
-  if (recurringTimeout) {
-    console.log('⏰ Clearing existing recurring timeout');
-    clearTimeout(recurringTimeout);
-    recurringTimeout = null;
+  const statusElement = document.getElementById('timer-status');
+  if (statusElement) {
+    statusElement.textContent = timerState?.recurring
+      ? '🔄 Break ended early - Starting next timer session...'
+      : '✅ Break completed early - Timer session finished';
   }
-  
-  // If recurring is enabled, start the next timer immediately (keep UI on timer screen)
-  if (currentTimerSettings && currentTimerSettings.recurring) {
-    console.log('🔄 Starting next timer immediately due to early return');
-    
-    // Show immediate feedback to user
-    const statusElement = document.getElementById('timer-status');
-    if (statusElement) {
-      statusElement.textContent = '🔄 Break ended early - Starting next timer session...';
-    }
-    
-    // Start immediately with minimal delay
-    setTimeout(async () => {
-      const breakTimerSeconds = getBreakTimerValue();
-      console.log('🚀 Starting new timer with', breakTimerSeconds, 'seconds');
-      await startTimer(breakTimerSeconds);
-    }, 500); // Minimal delay just to show the message
-  } else {
-    // If recurring is not enabled, stop timer and show settings
-    console.log('✅ Recurring not enabled, finishing session');
-    
-    // Now we can stop the timer since recurring is not enabled
-    isTimerRunning = false;
-    isTimerPaused = false;
-    
-    const statusElement = document.getElementById('timer-status');
-    if (statusElement) {
-      statusElement.textContent = '✅ Break completed early - Timer session finished';
-    }
-    
-    // Clear settings and reset UI state
-    currentTimerSettings = null;
-    updateTimerDisplay();
-    updateTimerControls();
-    updatePanelVisibility();
+}
+
+// Break lifecycle events from Rust (see lifecycle.rs for the payloads)
+function handleLifecycleEvent(event) {
+  switch (event.type) {
+    case 'break_skipped':
+      handleBreakSkipped();
+      break;
+    case 'break_ended_early':
+      handleEarlyBreakReturn();
+      break;
+    case 'break_snoozed':
+      // The status line follows the timer state, which carries the reason
+      console.log('😴 Break snoozed:', event.reason);
+      break;
   }
 }
 
+function isPomodoroMode() {
+  return document.querySelector('input[name="break-mode"]:checked')?.value === 'pomodoro';
+}
+
 // Input validation
 function validateTimeInputs() {
+  // Pomodoro lengths come from the settings page
+  if (isPomodoroMode()) return null;
+
   const totalBreakTime = getBreakTimerValue();
   const totalDuration = getBreakDurationValue();
 
@@ -637,10 +394,17 @@ window.addEventListener("DOMContentLoaded", async () => {
   const savedSettings = await loadSettings();
   applySettingsToUI(savedSettings);
 
-  // Initialize UI state
-  updateTimerDisplay();
-  updateTimerControls();
-  updatePanelVisibility();
+  // Follow the Rust timer engine and pick up a cycle that is already running
+  await listen('timer-state', (event) => applyTimerState(event.payload));
+  await listen('lifecycle', (event) => handleLifecycleEvent(event.payload));
+  try {
+    applyTimerState(await invoke('get_timer_state'));
+  } catch (error) {
+    console.error('Failed to get timer state:', error);
+    updateTimerDisplay();
+    updateTimerControls();
+    updatePanelVisibility();
+  }
   
   // Debug: Log current values
   console.log('Current break duration values:', {
@@ -666,13 +430,14 @@ window.addEventListener("DOMContentLoaded", async () => {
 
     console.log('💾 Saving settings before starting timer...');
     await saveSettings();
-    const breakTimerSeconds = getBreakTimerValue();
+    // In Pomodoro mode the engine picks the work length itself
+    const breakTimerSeconds = isPomodoroMode() ? null : getBreakTimerValue();
     console.log('⏰ Break timer value:', breakTimerSeconds, 'seconds');
     await startTimer(breakTimerSeconds);
   });
 
-  document.getElementById('stop-timer').addEventListener('click', () => {
-    stopTimer();
+  document.getElementById('stop-timer').addEventListener('click', async () => {
+    await stopTimer();
   });
 
   // Settings form
@@ -694,24 +459,14 @@ window.addEventListener("DOMContentLoaded", async () => {
   // Add pause timer functionality
   const pauseBtn = document.getElementById('pause-timer');
   if (pauseBtn) {
-    pauseBtn.addEventListener('click', () => {
+    pauseBtn.addEventListener('click', async () => {
       if (isTimerPaused) {
-        resumeTimer();
+        await resumeTimer();
       } else {
-        pauseTimer();
+        await pauseTimer();
       }
     });
   }
-  // Make functions available globally for Rust to call
-  window.handleBreakSkipped = handleBreakSkipped;
-  window.handleEarlyBreakReturn = handleEarlyBreakReturn;
-  
-  // Debug: Log when functions are made available
-  console.log('✅ Global functions registered:', {
-    handleBreakSkipped: typeof window.handleBreakSkipped,
-    handleEarlyBreakReturn: typeof window.handleEarlyBreakReturn
-  });
-  
   // Check for updates on startup (with delay to not interfere with app loading)
   setTimeout(() => {
     updateManager.checkForUpdates().then(result => {
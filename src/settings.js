@@ -18,7 +18,41 @@ async function saveSettings() {
       pre_break_minutes: parseInt(document.getElementById("pre-break-minutes").value) || 0,
       pre_break_seconds: parseInt(document.getElementById("pre-break-seconds").value) || 0,
       break_chime: document.getElementById("break-chime").checked,
-      autostart: document.getElementById("autostart").checked
+      sleep_counts_as_break: document.getElementById("sleep-counts-as-break").checked,
+      lock_counts_as_break: document.getElementById("lock-counts-as-break").checked,
+      idle_detect: document.getElementById("idle-detect").checked,
+      idle_pause_minutes: parseInt(document.getElementById("idle-pause-minutes").value) || 5,
+      idle_reset_minutes: parseInt(document.getElementById("idle-reset-minutes").value) || 0,
+      extra_break_tiers: readBreakTiers(),
+      media_allow_list: parsePlayerList("media-allow-list"),
+      media_deny_list: parsePlayerList("media-deny-list"),
+      duck_notify_breaks: document.getElementById("duck-notify-breaks").checked,
+      duck_volume_percent: parseInt(document.getElementById("duck-volume-percent").value) || 0,
+      meeting_grace_minutes: parseInt(document.getElementById("meeting-grace-minutes").value) || 0,
+      max_meeting_postpones: parseInt(document.getElementById("max-meeting-postpones").value) || 0,
+      mandatory_break_hours: parseInt(document.getElementById("mandatory-break-hours").value) || 0,
+      calendar_enabled: document.getElementById("calendar-enabled").checked,
+      calendar_paths: parsePlayerList("calendar-paths"),
+      calendar_lead_minutes: parseInt(document.getElementById("calendar-lead-minutes").value) || 0,
+      calendar_long_meeting_minutes: parseInt(document.getElementById("calendar-long-meeting-minutes").value) || 60,
+      caldav_url: document.getElementById("caldav-url").value.trim(),
+      caldav_username: document.getElementById("caldav-username").value.trim(),
+      caldav_refresh_minutes: parseInt(document.getElementById("caldav-refresh-minutes").value) || 15,
+      fullscreen_break_action: document.getElementById("fullscreen-break-action").value,
+      presenting_break_action: document.getElementById("presenting-break-action").value,
+      strict_mode: document.getElementById("strict-mode").checked,
+      daily_skip_limit: parseInt(document.getElementById("daily-skip-limit").value) || 0,
+      skip_unlock_seconds: parseInt(document.getElementById("skip-unlock-seconds").value) || 0,
+      snooze_minutes: parseInt(document.getElementById("snooze-minutes").value) || 5,
+      max_snoozes: parseInt(document.getElementById("max-snoozes").value) || 0,
+      max_snooze_minutes: parseInt(document.getElementById("max-snooze-minutes").value) || 0,
+      pomodoro_work_minutes: parseInt(document.getElementById("pomodoro-work-minutes").value) || 25,
+      pomodoro_short_break_minutes: parseInt(document.getElementById("pomodoro-short-break-minutes").value) || 5,
+      pomodoro_long_break_minutes: parseInt(document.getElementById("pomodoro-long-break-minutes").value) || 0,
+      pomodoro_long_break_every: parseInt(document.getElementById("pomodoro-long-break-every").value) || 4,
+      pomodoro_break_window: document.getElementById("pomodoro-break-window").value,
+      autostart: document.getElementById("autostart").checked,
+      overdue_break_policy: document.getElementById("overdue-break-policy").value
     };
 
     await settingsManager.save(newSettings);
@@ -40,16 +74,55 @@ function applySettingsToUI(settings) {
       pre_break_minutes: 'pre-break-minutes',
       pre_break_seconds: 'pre-break-seconds',
       break_chime: 'break-chime',
-      autostart: 'autostart'
+      sleep_counts_as_break: 'sleep-counts-as-break',
+      lock_counts_as_break: 'lock-counts-as-break',
+      idle_detect: 'idle-detect',
+      idle_pause_minutes: 'idle-pause-minutes',
+      idle_reset_minutes: 'idle-reset-minutes',
+      duck_notify_breaks: 'duck-notify-breaks',
+      duck_volume_percent: 'duck-volume-percent',
+      meeting_grace_minutes: 'meeting-grace-minutes',
+      max_meeting_postpones: 'max-meeting-postpones',
+      mandatory_break_hours: 'mandatory-break-hours',
+      calendar_enabled: 'calendar-enabled',
+      calendar_lead_minutes: 'calendar-lead-minutes',
+      calendar_long_meeting_minutes: 'calendar-long-meeting-minutes',
+      caldav_url: 'caldav-url',
+      caldav_username: 'caldav-username',
+      caldav_refresh_minutes: 'caldav-refresh-minutes',
+      fullscreen_break_action: 'fullscreen-break-action',
+      presenting_break_action: 'presenting-break-action',
+      strict_mode: 'strict-mode',
+      daily_skip_limit: 'daily-skip-limit',
+      skip_unlock_seconds: 'skip-unlock-seconds',
+      snooze_minutes: 'snooze-minutes',
+      max_snoozes: 'max-snoozes',
+      max_snooze_minutes: 'max-snooze-minutes',
+      pomodoro_work_minutes: 'pomodoro-work-minutes',
+      pomodoro_short_break_minutes: 'pomodoro-short-break-minutes',
+      pomodoro_long_break_minutes: 'pomodoro-long-break-minutes',
+      pomodoro_long_break_every: 'pomodoro-long-break-every',
+      pomodoro_break_window: 'pomodoro-break-window',
+      autostart: 'autostart',
+      overdue_break_policy: 'overdue-break-policy'
     };
 
     UIUtils.applySettingsToForm(settings, fieldMappings);
+    document.getElementById("media-allow-list").value = (settings.media_allow_list || []).join('\n');
+    document.getElementById("media-deny-list").value = (settings.media_deny_list || []).join('\n');
+    document.getElementById("calendar-paths").value = (settings.calendar_paths || []).join('\n');
+    renderBreakTiers(settings.extra_break_tiers || []);
     
     // Apply update check setting
     const updateCheckEnabled = updateManager.isUpdateCheckEnabled();
     document.getElementById('auto-update-check').checked = updateCheckEnabled;
     
     updatePreBreakTimingVisibility();
+    updateIdleTimingVisibility();
+    updateMeetingPostponeVisibility();
+    updateCalendarVisibility();
+    updateCalDavPasswordHint();
+    updateMediaRulesVisibility();
     updateLastCheckInfo();
     console.log('Settings applied to UI:', settings);
   } catch (error) {
@@ -62,6 +135,209 @@ function updatePreBreakTimingVisibility() {
   UIUtils.toggleElementVisibility("pre-break-timing-card", preBreakEnabled);
 }
 
+function updateIdleTimingVisibility() {
+  const idleEnabled = document.getElementById("idle-detect").checked;
+  UIUtils.toggleElementVisibility("idle-timing-card", idleEnabled);
+}
+
+function updateMeetingPostponeVisibility() {
+  const meetingDetectEnabled = document.getElementById("meeting-detect").checked;
+  UIUtils.toggleElementVisibility("meeting-postpone-card", meetingDetectEnabled);
+}
+
+function updateCalendarVisibility() {
+  const calendarEnabled = document.getElementById("calendar-enabled").checked;
+  UIUtils.toggleElementVisibility("calendar-card", calendarEnabled);
+}
+
+async function updateCalDavPasswordHint() {
+  const hasPassword = await invoke('has_caldav_password').catch(() => false);
+  document.getElementById("caldav-password").placeholder = hasPassword ? '•••••••• (saved)' : '';
+}
+
+async function saveCalDavPassword() {
+  const input = document.getElementById("caldav-password");
+  try {
+    // The password is tied to the server and user name, so save those first
+    await saveSettings();
+    await invoke('set_caldav_password', { password: input.value });
+    input.value = '';
+  } catch (error) {
+    document.getElementById("calendar-status").textContent = `Failed to save password: ${error}`;
+  }
+  updateCalDavPasswordHint();
+}
+
+function describeCalDav(caldav) {
+  const synced = caldav.synced_unix_ms
+    ? `synced ${new Date(caldav.synced_unix_ms).toLocaleString()}`
+    : 'not synced yet';
+  const lines = [`CalDAV: ${caldav.calendars.length} calendar(s), ${caldav.event_count} event(s), ${synced}`];
+  if (caldav.error) {
+    lines.push(`⚠️ ${caldav.error}`);
+  }
+  return lines;
+}
+
+async function syncCalDav() {
+  const status = document.getElementById("calendar-status");
+  status.textContent = 'Syncing…';
+  try {
+    await saveSettings();
+    const caldav = await invoke('sync_caldav');
+    status.textContent = describeCalDav(caldav).join('\n');
+  } catch (error) {
+    status.textContent = `CalDAV sync failed: ${error}`;
+  }
+}
+
+async function showUpcomingMeetings() {
+  const status = document.getElementById("calendar-status");
+  try {
+    const report = await invoke('get_calendar_busy', { hours: 24 });
+    const lines = [`${report.files.length} file(s), ${report.event_count} event(s)`];
+    report.errors.forEach(error => lines.push(`⚠️ ${error}`));
+    if (report.caldav) {
+      lines.push(...describeCalDav(report.caldav));
+    }
+    report.busy.forEach(block => {
+      const start = new Date(block.start_unix_ms).toLocaleString();
+      const end = new Date(block.end_unix_ms).toLocaleTimeString();
+      lines.push(`${start} – ${end}: ${block.summary}`);
+    });
+    if (report.busy.length === 0) {
+      lines.push('No meetings in the next 24 hours');
+    }
+    status.textContent = lines.join('\n');
+  } catch (error) {
+    status.textContent = `Failed to read calendar: ${error}`;
+  }
+}
+
+function updateMediaRulesVisibility() {
+  const autoPauseEnabled = document.getElementById("auto-pause").checked;
+  UIUtils.toggleElementVisibility("media-rules-card", autoPauseEnabled);
+  const duckEnabled = document.getElementById("duck-notify-breaks").checked;
+  UIUtils.toggleElementVisibility("duck-volume-group", duckEnabled);
+}
+
+// Media player rules
+// Extra break tiers, one editable row each
+const MICRO_BREAK_TIER = {
+  name: 'Micro-break',
+  interval_seconds: 20 * 60,
+  duration_seconds: 20,
+  mode: 'notify',
+  pre_break_seconds: null
+};
+
+function renderBreakTiers(tiers) {
+  const list = document.getElementById("break-tiers-list");
+  list.innerHTML = '';
+  tiers.forEach(tier => list.appendChild(createBreakTierRow(tier)));
+}
+
+function createBreakTierRow(tier) {
+  const row = document.createElement('div');
+  row.className = 'break-tier';
+  row.innerHTML = `
+    <div>
+      <label class="form-label">Name</label>
+      <input type="text" class="form-input" data-field="name" />
+    </div>
+    <div>
+      <label class="form-label">Mode</label>
+      <select class="form-select" data-field="mode">
+        <option value="notify">Notify</option>
+        <option value="force">Force</option>
+        <option value="lock">Lock screen</option>
+      </select>
+    </div>
+    <div>
+      <label class="form-label">Every (minutes)</label>
+      <input type="number" class="time-input" data-field="interval" min="1" max="240" />
+    </div>
+    <div>
+      <label class="form-label">For (seconds)</label>
+      <input type="number" class="time-input" data-field="duration" min="1" max="3600" />
+    </div>
+    <div>
+      <label class="form-label">Warn before (seconds, 0 for none)</label>
+      <input type="number" class="time-input" data-field="pre-break" min="0" max="600" />
+    </div>
+    <div style="display: flex; align-items: flex-end;">
+      <button type="button" class="btn btn-danger" data-field="remove">🗑️ Remove</button>
+    </div>
+  `;
+  const field = name => row.querySelector(`[data-field="${name}"]`);
+  field('name').value = tier.name;
+  field('mode').value = tier.mode;
+  field('interval').value = Math.round(tier.interval_seconds / 60);
+  field('duration').value = tier.duration_seconds;
+  field('pre-break').value = tier.pre_break_seconds ?? 0;
+  row.querySelectorAll('input, select').forEach(input => input.addEventListener('change', saveSettings));
+  field('remove').addEventListener('click', () => {
+    row.remove();
+    saveSettings();
+  });
+  return row;
+}
+
+function readBreakTiers() {
+  return Array.from(document.querySelectorAll("#break-tiers-list .break-tier")).map(row => {
+    const field = name => row.querySelector(`[data-field="${name}"]`);
+    const preBreak = parseInt(field('pre-break').value) || 0;
+    return {
+      name: field('name').value.trim() || MICRO_BREAK_TIER.name,
+      interval_seconds: (parseInt(field('interval').value) || 0) * 60,
+      duration_seconds: parseInt(field('duration').value) || 0,
+      mode: field('mode').value,
+      pre_break_seconds: preBreak > 0 ? preBreak : null
+    };
+  });
+}
+
+function addBreakTier() {
+  document.getElementById("break-tiers-list").appendChild(createBreakTierRow(MICRO_BREAK_TIER));
+  saveSettings();
+}
+
+function parsePlayerList(id) {
+  return document.getElementById(id).value
+    .split('\n')
+    .map(entry => entry.trim())
+    .filter(entry => entry.length > 0);
+}
+
+async function refreshMediaSessions() {
+  const select = document.getElementById("media-session-select");
+  try {
+    const sessions = await invoke('list_media_sessions');
+    select.innerHTML = '';
+    if (sessions.length === 0) {
+      select.add(new Option('No players found — start one and refresh', ''));
+      return;
+    }
+    sessions.forEach(session => {
+      const state = session.playing ? ' (playing)' : '';
+      select.add(new Option(`${session.name} — ${session.backend}${state}`, session.name));
+    });
+  } catch (error) {
+    console.error('Failed to list media sessions:', error);
+  }
+}
+
+function addSelectedPlayer(listId) {
+  const name = document.getElementById("media-session-select").value;
+  if (!name) return;
+  const entries = parsePlayerList(listId);
+  if (!entries.some(entry => entry.toLowerCase() === name.toLowerCase())) {
+    entries.push(name);
+    document.getElementById(listId).value = entries.join('\n');
+    saveSettings();
+  }
+}
+
 // Update functions
 function updateLastCheckInfo() {
   const lastCheckInfo = updateManager.getLastCheckInfo();
@@ -170,7 +446,8 @@ window.addEventListener("DOMContentLoaded", async () => {
 
   // Settings form
   const settingsInputs = [
-    "auto-pause", "meeting-detect", "pre-break", "break-chime"
+    "auto-pause", "meeting-detect", "pre-break", "break-chime", "sleep-counts-as-break", "lock-counts-as-break", "overdue-break-policy",
+    "fullscreen-break-action", "presenting-break-action", "strict-mode", "pomodoro-break-window"
   ];
 
   settingsInputs.forEach(id => {
@@ -200,10 +477,71 @@ window.addEventListener("DOMContentLoaded", async () => {
     saveSettings();
   });
 
+  // Meeting detection toggle shows the postponement limits
+  document.getElementById("meeting-detect").addEventListener('change', updateMeetingPostponeVisibility);
+
+  // Calendar awareness
+  document.getElementById("calendar-enabled").addEventListener('change', () => {
+    updateCalendarVisibility();
+    saveSettings();
+  });
+  document.getElementById("calendar-paths").addEventListener('change', saveSettings);
+  document.getElementById("calendar-check-btn").addEventListener('click', showUpcomingMeetings);
+  document.getElementById("caldav-sync-btn").addEventListener('click', syncCalDav);
+  document.getElementById("caldav-password").addEventListener('change', saveCalDavPassword);
+  ["caldav-url", "caldav-username"].forEach(id => {
+    document.getElementById(id).addEventListener('change', async () => {
+      await saveSettings();
+      updateCalDavPasswordHint();
+    });
+  });
+
+  // Auto-pause toggle shows the per-player rules
+  document.getElementById("auto-pause").addEventListener('change', updateMediaRulesVisibility);
+
+  // Media player rules
+  document.getElementById("duck-notify-breaks").addEventListener('change', () => {
+    updateMediaRulesVisibility();
+    saveSettings();
+  });
+  document.getElementById("media-allow-list").addEventListener('change', saveSettings);
+  document.getElementById("media-deny-list").addEventListener('change', saveSettings);
+  document.getElementById("media-allow-btn").addEventListener('click', () => addSelectedPlayer("media-allow-list"));
+  document.getElementById("media-deny-btn").addEventListener('click', () => addSelectedPlayer("media-deny-list"));
+  document.getElementById("media-refresh-btn").addEventListener('click', refreshMediaSessions);
+  refreshMediaSessions();
+
+  // Extra break tiers
+  document.getElementById("add-break-tier-btn").addEventListener('click', addBreakTier);
+
+  // Idle detection toggle
+  document.getElementById("idle-detect").addEventListener('change', () => {
+    updateIdleTimingVisibility();
+    saveSettings();
+  });
+
   // Time input validation
   const timeInputs = [
     { element: document.getElementById("pre-break-minutes"), max: 10 },
-    { element: document.getElementById("pre-break-seconds"), max: 59 }
+    { element: document.getElementById("pre-break-seconds"), max: 59 },
+    { element: document.getElementById("idle-pause-minutes"), max: 60 },
+    { element: document.getElementById("idle-reset-minutes"), max: 120 },
+    { element: document.getElementById("duck-volume-percent"), max: 100 },
+    { element: document.getElementById("meeting-grace-minutes"), max: 30 },
+    { element: document.getElementById("max-meeting-postpones"), max: 50 },
+    { element: document.getElementById("mandatory-break-hours"), max: 12 },
+    { element: document.getElementById("calendar-lead-minutes"), max: 30 },
+    { element: document.getElementById("calendar-long-meeting-minutes"), max: 480 },
+    { element: document.getElementById("caldav-refresh-minutes"), max: 240 },
+    { element: document.getElementById("daily-skip-limit"), max: 50 },
+    { element: document.getElementById("skip-unlock-seconds"), max: 600 },
+    { element: document.getElementById("snooze-minutes"), max: 60 },
+    { element: document.getElementById("max-snoozes"), max: 20 },
+    { element: document.getElementById("max-snooze-minutes"), max: 240 },
+    { element: document.getElementById("pomodoro-work-minutes"), max: 120 },
+    { element: document.getElementById("pomodoro-short-break-minutes"), max: 60 },
+    { element: document.getElementById("pomodoro-long-break-minutes"), max: 120 },
+    { element: document.getElementById("pomodoro-long-break-every"), max: 12 }
   ];
 
   UIUtils.setupTimeInputs(timeInputs, saveSettings);
@@ -222,6 +560,10 @@ window.addEventListener("DOMContentLoaded", async () => {
     'debug-media-pause': () => DebugUtils.testMediaPause(),
     'debug-meeting-check': () => DebugUtils.testMeetingCheck(),
     'debug-browser-meeting-check': () => DebugUtils.testBrowserMeetingCheck(),
+    'debug-meeting-rules': () => DebugUtils.testMeetingRules(),
+    'debug-postponements': () => DebugUtils.showPostponements(),
+    'debug-skip-status': () => DebugUtils.showSkipStatus(),
+    'debug-screen-activity': () => DebugUtils.checkScreenActivity(),
     'debug-meeting-notification': () => DebugUtils.testMeetingNotification(),
     'debug-autostart-check': () => DebugUtils.testAutostartCheck(),
     'debug-test-updates': () => DebugUtils.testUpdateCheck(),
@@ -229,6 +571,7 @@ window.addEventListener("DOMContentLoaded", async () => {
     'debug-close-force': () => DebugUtils.closeWindow('force_break'),
     'debug-close-notify': () => DebugUtils.closeWindow('notify'),
     'debug-close-prebreak': () => DebugUtils.closeWindow('pre_break'),
+    'debug-list-windows': () => DebugUtils.listWindows(),
     'debug-test-sequence': () => DebugUtils.runTestSequence(),
     'debug-help': () => DebugUtils.showHelp(),
     'debug-clear': () => DebugUtils.clear()
@@ -105,6 +105,11 @@ export class DebugUtils {
       this.log('Checking meeting status...');
       const inMeeting = await invoke("is_meeting_active");
       this.log(`Meeting detection result: ${inMeeting ? 'In meeting' : 'No meeting detected'}`);
+      const assessment = await invoke("detect_meeting");
+      this.log(`Confidence: ${Math.round(assessment.confidence * 100)}%`);
+      assessment.signals.forEach(signal => {
+        this.log(`  ${signal.kind}: ${signal.detail} (weight ${signal.weight})`);
+      });
     } catch (error) {
       this.log(`❌ Error checking meeting status: ${error}`);
     }
@@ -120,6 +125,87 @@ export class DebugUtils {
     }
   }
 
+  static async testMeetingRules() {
+    try {
+      this.log('Testing meeting rules (meeting_rules.json)...');
+      const report = await invoke("test_meeting_rules");
+      if (report.rules_error) {
+        this.log(`⚠️ ${report.rules_error}`);
+      }
+      report.process_matches.forEach(m => this.log(`  Process "${m.target}" ← ${m.rule}`));
+      report.title_matches.forEach(m => this.log(`  Window "${m.target}" ← ${m.rule}`));
+      report.exclusions.forEach(m => this.log(`  Excluded "${m.target}" ← ${m.rule}`));
+      if (report.process_matches.length + report.title_matches.length + report.exclusions.length === 0) {
+        this.log('  No rule matched any process or window');
+      }
+      this.log(`Confidence: ${Math.round(report.assessment.confidence * 100)}%`);
+    } catch (error) {
+      this.log(`❌ Error testing meeting rules: ${error}`);
+    }
+  }
+
+  static async showPostponements() {
+    try {
+      this.log('Recent meeting postponements...');
+      const records = await invoke("get_postponements");
+      if (records.length === 0) {
+        this.log('  No breaks postponed yet');
+      }
+      records.forEach(r => {
+        const time = new Date(r.at_unix_ms).toLocaleTimeString();
+        const kind = r.snoozed ? '😴 Snoozed' : r.mandatory ? '⛔ Mandatory' : `🤝 #${r.postpone_count}`;
+        this.log(`  ${time} cycle ${r.cycle} ${kind}: ${r.reason}`);
+      });
+    } catch (error) {
+      this.log(`❌ Error loading postponements: ${error}`);
+    }
+  }
+
+  static async showSkipStatus() {
+    try {
+      this.log('Today\'s skip budget...');
+      const status = await invoke("get_skip_status");
+      const limit = status.daily_limit ?? 'no limit';
+      this.log(`  Strict mode: ${status.strict ? 'on' : 'off'}`);
+      this.log(`  Used: ${status.used_today} (limit: ${limit}, left: ${status.remaining_today ?? 'unlimited'})`);
+      this.log(`  Refused: ${status.refused_today}`);
+      this.log(`  Early return unlocks after: ${status.unlock_delay_seconds}s`);
+    } catch (error) {
+      this.log(`❌ Error loading skip budget: ${error}`);
+    }
+  }
+
+  static async checkScreenActivity() {
+    try {
+      this.log('Checking for fullscreen windows and presenting apps...');
+      const activity = await invoke("detect_screen_activity");
+      this.log(`  Fullscreen: ${activity.fullscreen ?? 'none'}`);
+      this.log(`  Presenting: ${activity.presenting ?? 'none'}`);
+    } catch (error) {
+      this.log(`❌ Error checking screen activity: ${error}`);
+    }
+  }
+
+  static async listWindows() {
+    try {
+      this.log('Listing tracked windows...');
+      const windows = await invoke("list_windows");
+      if (windows.length === 0) {
+        this.log('  No windows opened yet');
+      }
+      for (const w of windows) {
+        const since = new Date(w.since).toLocaleTimeString();
+        const mismatch = w.open !== (w.state !== 'closed') ? ' ⚠️ out of step' : '';
+        this.log(`  ${w.label}: ${w.state} since ${since} (${w.url})${mismatch}`);
+        if (w.error) {
+          this.log(`    Last error: ${w.error}`);
+        }
+      }
+    } catch (error) {
+      this.log(`❌ Error listing windows: ${error}`);
+    }
+  }
+
   static async testMeetingNotification() {
     try {
       this.log('Testing meeting detected notification...');
@@ -219,11 +305,13 @@ export class DebugUtils {
     this.log('  ⏸️ Media Pause - Tests media control functionality');
     this.log('  👥 Meeting Check - Tests desktop meeting detection');
     this.log('  🌐 Browser Meeting - Tests browser meeting detection');
+    this.log('  ⏭️ Skip Budget - Shows today\'s skips and refusals');
     this.log('  🚀 Autostart - Tests Windows autostart status');
     this.log('  🗑️ Clear Settings - Resets all settings to defaults');
     this.log('');
     this.log('WINDOW CONTROLS:');
     this.log('  ❌ Close windows - Closes specific break windows');
+    this.log('  🪟 List Windows - Shows each window\'s lifecycle state');
     this.log('  🧪 Full Test - Runs comprehensive test sequence');
     this.log('=====================================');
   }
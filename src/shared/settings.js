@@ -20,7 +20,41 @@ export const DEFAULT_SETTINGS = {
   break_chime: false,
   recurring: false,
   autostart: false,
-  auto_start_timer: false
+  auto_start_timer: false,
+  overdue_break_policy: 'fire',
+  sleep_counts_as_break: true,
+  lock_counts_as_break: true,
+  idle_detect: false,
+  idle_pause_minutes: 5,
+  idle_reset_minutes: 0,
+  media_allow_list: [],
+  media_deny_list: [],
+  duck_notify_breaks: false,
+  duck_volume_percent: 30,
+  meeting_grace_minutes: 2,
+  max_meeting_postpones: 6,
+  mandatory_break_hours: 3,
+  calendar_enabled: false,
+  calendar_paths: [],
+  calendar_lead_minutes: 5,
+  calendar_long_meeting_minutes: 60,
+  caldav_url: '',
+  caldav_username: '',
+  caldav_refresh_minutes: 15,
+  fullscreen_break_action: 'notify',
+  presenting_break_action: 'postpone',
+  strict_mode: false,
+  daily_skip_limit: 0,
+  skip_unlock_seconds: 0,
+  snooze_minutes: 5,
+  max_snoozes: 3,
+  max_snooze_minutes: 30,
+  extra_break_tiers: [],
+  pomodoro_work_minutes: 25,
+  pomodoro_short_break_minutes: 5,
+  pomodoro_long_break_minutes: 15,
+  pomodoro_long_break_every: 4,
+  pomodoro_break_window: 'notify'
 };
 
 /**
@@ -40,18 +40,14 @@ export class TimerUtils {
   }
 
   /**
-   * Get break duration from URL parameters or localStorage
+   * Get break duration from the window payload or localStorage
    */
-  static getBreakDuration(defaultDuration = 300) {
+  static getBreakDuration(payload, defaultDuration = 300) {
     try {
-      // First try URL parameters
-      const urlParams = new URLSearchParams(window.location.search);
-      const duration = urlParams.get('duration');
-      
-      if (duration && !isNaN(parseInt(duration))) {
-        const durationInt = parseInt(duration);
-        console.log('Using duration from URL:', durationInt);
-        return durationInt;
+      // First try the window payload
+      if (payload && Number.isInteger(payload.duration)) {
+        console.log('Using duration from window payload:', payload.duration);
+        return payload.duration;
       }
 
       // Fallback to localStorage
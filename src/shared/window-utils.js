@@ -28,6 +28,20 @@ export class WindowUtils {
     }
   }
 
+  /**
+   * Get the payload this window was opened with, or null if it has none
+   */
+  static async getPayload() {
+    try {
+      const payload = await invoke('get_window_payload');
+      console.log('📦 Window payload:', payload);
+      return payload;
+    } catch (error) {
+      console.warn('⚠️ No window payload:', error);
+      return null;
+    }
+  }
+
   /**
    * Get screen dimensions from Tauri
    */